@@ -1,12 +1,46 @@
+use rand::Rng;
+
 use crate::common::block::Block;
+use crate::world::world::World;
 
 #[derive(Clone)]
 pub struct GrassBlock;
 
+impl GrassBlock {
+    /// Grass survives only with nothing opaque or liquid on top of it.
+    fn can_survive(world: &World, pos: (i32, i32, i32)) -> bool {
+        match world.get_block((pos.0, pos.1 + 1, pos.2)) {
+            Some(above) => above.is_transparent() && above.get_fluid().is_none(),
+            None => true,
+        }
+    }
+}
+
 impl Block for GrassBlock {
     fn get_id(&self) -> &'static str { "grass" }
     fn get_name(&self) -> &'static str { "grass" }
     fn get_texture_path(&self) -> &'static str { "assets/textures/block/grass.png" }
     fn is_solid(&self) -> bool { true }
     fn is_transparent(&self) -> bool { false }
-}
\ No newline at end of file
+    fn has_random_ticks(&self) -> bool { true }
+
+    fn on_random_tick(&self, world: &mut World, pos: (i32, i32, i32)) {
+        if !Self::can_survive(world, pos) {
+            world.set_block(pos, "dirt", 0);
+            return;
+        }
+
+        // Spread to nearby dirt that has room to grow
+        let mut rng = rand::rng();
+        for _ in 0..4 {
+            let target = (
+                pos.0 + rng.random_range(-1..=1),
+                pos.1 + rng.random_range(-3..=1),
+                pos.2 + rng.random_range(-1..=1),
+            );
+            if world.get_block_id(target) == "dirt" && Self::can_survive(world, target) {
+                world.set_block(target, "grass", 0);
+            }
+        }
+    }
+}
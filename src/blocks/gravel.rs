@@ -0,0 +1,13 @@
+use crate::common::block::Block;
+
+#[derive(Clone)]
+pub struct GravelBlock;
+
+impl Block for GravelBlock {
+    fn get_id(&self) -> &'static str { "gravel" }
+    fn get_name(&self) -> &'static str { "gravel" }
+    fn get_texture_path(&self) -> &'static str { "assets/textures/block/gravel.png" }
+    fn is_solid(&self) -> bool { true }
+    fn is_transparent(&self) -> bool { false }
+    fn has_gravity(&self) -> bool { true }
+}
@@ -0,0 +1,29 @@
+use crate::world::world::World;
+use crate::common::block::{Block, FluidProperties};
+
+#[derive(Clone)]
+pub struct LavaBlock;
+
+impl Block for LavaBlock {
+    fn get_id(&self) -> &'static str { "lava" }
+    fn get_name(&self) -> &'static str { "lava" }
+    fn get_texture_path(&self) -> &'static str { "assets/textures/block/lava.png" }
+    fn is_solid(&self) -> bool { false }
+    fn is_transparent(&self) -> bool { true }
+    fn get_fluid(&self) -> Option<FluidProperties> {
+        Some(FluidProperties {
+            max_spread: 3,
+            tick_delay: 30,
+            infinite_source: false,
+            drag: 6.0,
+            buoyancy: 2.0,
+            fog_color: [0.8, 0.25, 0.05],
+            fog_distance: 2.0,
+        })
+    }
+    fn on_tick(&self, world: &mut World, pos: (i32, i32, i32)) { world.update_fluid(pos); }
+    fn on_placed(&self, world: &mut World, pos: (i32, i32, i32)) { world.schedule_fluid_tick(pos); }
+    fn on_neighbour_changed(&self, world: &mut World, pos: (i32, i32, i32), _neighbour: (i32, i32, i32)) {
+        world.schedule_fluid_tick(pos);
+    }
+}
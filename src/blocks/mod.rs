@@ -1,4 +1,8 @@
 pub mod air;
 pub mod grass;
 pub mod stone;
-pub mod dirt;
\ No newline at end of file
+pub mod dirt;
+pub mod water;
+pub mod lava;
+pub mod sand;
+pub mod gravel;
@@ -0,0 +1,13 @@
+use crate::common::block::Block;
+
+#[derive(Clone)]
+pub struct SandBlock;
+
+impl Block for SandBlock {
+    fn get_id(&self) -> &'static str { "sand" }
+    fn get_name(&self) -> &'static str { "sand" }
+    fn get_texture_path(&self) -> &'static str { "assets/textures/block/sand.png" }
+    fn is_solid(&self) -> bool { true }
+    fn is_transparent(&self) -> bool { false }
+    fn has_gravity(&self) -> bool { true }
+}
@@ -0,0 +1,29 @@
+use crate::world::world::World;
+use crate::common::block::{Block, FluidProperties};
+
+#[derive(Clone)]
+pub struct WaterBlock;
+
+impl Block for WaterBlock {
+    fn get_id(&self) -> &'static str { "water" }
+    fn get_name(&self) -> &'static str { "water" }
+    fn get_texture_path(&self) -> &'static str { "assets/textures/block/water.png" }
+    fn is_solid(&self) -> bool { false }
+    fn is_transparent(&self) -> bool { true }
+    fn get_fluid(&self) -> Option<FluidProperties> {
+        Some(FluidProperties {
+            max_spread: 7,
+            tick_delay: 5,
+            infinite_source: true,
+            drag: 3.0,
+            buoyancy: 6.0,
+            fog_color: [0.1, 0.25, 0.55],
+            fog_distance: 24.0,
+        })
+    }
+    fn on_tick(&self, world: &mut World, pos: (i32, i32, i32)) { world.update_fluid(pos); }
+    fn on_placed(&self, world: &mut World, pos: (i32, i32, i32)) { world.schedule_fluid_tick(pos); }
+    fn on_neighbour_changed(&self, world: &mut World, pos: (i32, i32, i32), _neighbour: (i32, i32, i32)) {
+        world.schedule_fluid_tick(pos);
+    }
+}
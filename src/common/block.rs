@@ -1,3 +1,36 @@
+use crate::engine::render::animation::TextureAnimation;
+use crate::world::world::World;
+
+/// How a block's faces are drawn by the chunk renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderLayer {
+    /// Fully opaque, drawn in the main pass.
+    Opaque,
+    /// Drawn in the main pass, texels with low alpha are discarded (leaves, grates).
+    Cutout,
+    /// Alpha-blended, drawn after all opaque geometry (glass, ice).
+    Translucent,
+}
+
+/// Flow behaviour of a liquid block. Fluid levels live in the block state,
+/// see `world::fluid` for the encoding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FluidProperties {
+    /// How many blocks a flowing fluid travels horizontally from its source (at most 7).
+    pub max_spread: u8,
+    /// Simulation ticks between flow updates.
+    pub tick_delay: u64,
+    /// Two neighbouring sources turn a flowing block into a new source.
+    pub infinite_source: bool,
+    /// Fraction of velocity removed per second for anything moving inside the fluid.
+    pub drag: f32,
+    /// Upward acceleration applied to anything submerged.
+    pub buoyancy: f32,
+    /// Colour of the fog seen with the camera inside the fluid.
+    pub fog_color: [f32; 3],
+    /// Distance at which the fog hides everything.
+    pub fog_distance: f32,
+}
 
 pub trait Block {
     fn get_id(&self) -> &'static str;
@@ -5,4 +38,46 @@ pub trait Block {
     fn get_texture_path(&self) -> &'static str;
     fn is_solid(&self) -> bool;
     fn is_transparent(&self) -> bool;
-}
\ No newline at end of file
+
+    fn get_render_layer(&self) -> RenderLayer {
+        if self.is_transparent() { RenderLayer::Translucent } else { RenderLayer::Opaque }
+    }
+
+    fn get_fluid(&self) -> Option<FluidProperties> {
+        None
+    }
+
+    /// Plays the texture as a vertical strip of frames. Without this a `.anim` file next to
+    /// the texture still animates it.
+    fn get_texture_animation(&self) -> Option<TextureAnimation> {
+        None
+    }
+
+    /// Falls as a `FallingBlock` when there is nothing underneath (sand, gravel).
+    fn has_gravity(&self) -> bool {
+        false
+    }
+
+    /// Opt in to `on_random_tick`; checked for every randomly picked block.
+    fn has_random_ticks(&self) -> bool {
+        false
+    }
+
+    // World hooks. Each is called on a detached copy of the block, with `pos` being
+    // where the block lives, so implementations may freely read and modify `world`.
+
+    /// Delayed update requested through `World::schedule_tick`.
+    fn on_tick(&self, _world: &mut World, _pos: (i32, i32, i32)) {}
+
+    /// Random update, a few blocks per chunk section every tick (grass spreading, crops).
+    fn on_random_tick(&self, _world: &mut World, _pos: (i32, i32, i32)) {}
+
+    /// A block next to this one was replaced; `neighbour` is its position.
+    fn on_neighbour_changed(&self, _world: &mut World, _pos: (i32, i32, i32), _neighbour: (i32, i32, i32)) {}
+
+    /// Placed by a player.
+    fn on_placed(&self, _world: &mut World, _pos: (i32, i32, i32)) {}
+
+    /// Broken by a player; the position is already air.
+    fn on_broken(&self, _world: &mut World, _pos: (i32, i32, i32)) {}
+}
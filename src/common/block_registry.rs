@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use crate::engine::Engine;
 
 use crate::common::block::Block;
-use crate::blocks::{air::AirBlock, stone::StoneBlock, dirt::DirtBlock, grass::GrassBlock};
+use crate::blocks::{air::AirBlock, stone::StoneBlock, dirt::DirtBlock, grass::GrassBlock, water::WaterBlock, lava::LavaBlock, sand::SandBlock, gravel::GravelBlock};
 use crate::modding::lua_block::LuaBlock;
 
 
@@ -14,18 +14,41 @@ pub struct BlockRegistry {
 
 impl BlockRegistry {
     pub fn new(engine: &mut Engine) -> Self {
-        let mut registry = Self {
+        let mut registry = Self::empty();
+        for block in Self::builtin_blocks() {
+            registry.register_block(block, engine);
+        }
+        registry
+    }
+    
+    /// Built-in blocks without any textures, for running the world without a renderer (tests, tools).
+    pub fn headless() -> Self {
+        let mut registry = Self::empty();
+        for block in Self::builtin_blocks() {
+            registry.blocks.insert(block.get_id().to_string(), block);
+        }
+        registry
+    }
+    
+    fn empty() -> Self {
+        Self {
             blocks: HashMap::new(),
             texture_paths: Vec::new(),
             texture_indices: HashMap::new(),
-        };
-        
-        registry.register_block(Box::new(AirBlock), engine);
-        registry.register_block(Box::new(DirtBlock), engine);
-        registry.register_block(Box::new(StoneBlock), engine);
-        registry.register_block(Box::new(GrassBlock), engine);
-        
-        registry
+        }
+    }
+    
+    fn builtin_blocks() -> Vec<Box<dyn Block>> {
+        vec![
+            Box::new(AirBlock),
+            Box::new(DirtBlock),
+            Box::new(StoneBlock),
+            Box::new(GrassBlock),
+            Box::new(WaterBlock),
+            Box::new(LavaBlock),
+            Box::new(SandBlock),
+            Box::new(GravelBlock),
+        ]
     }
     
     pub fn register_lua_block(&mut self, lua_block: LuaBlock, engine: &mut Engine) {
@@ -34,7 +57,9 @@ impl BlockRegistry {
     
     pub fn load_textures(&self, engine: &mut crate::engine::Engine) {
         for path in &self.texture_paths {
-            engine.add_texture(path, None);
+            if let Err(e) = engine.add_texture(path, None) {
+                println!("Failed to load block texture: {}", e);
+            }
         }
     }
     
@@ -45,11 +70,17 @@ impl BlockRegistry {
         println!("Registering block {} with texture: {}", block_id, texture_path);
         
         if !texture_path.is_empty() && !self.texture_paths.contains(&texture_path.to_string()) {
-            let some_id = engine.add_texture(texture_path, None);
-            if let Some(texture_id) = some_id {
-                let texture_index = self.texture_paths.len() as u32;
-                self.texture_paths.push(texture_path.to_string());
-                self.texture_indices.insert(block_id.to_string(), texture_index);
+            let added = match block.get_texture_animation() {
+                Some(animation) => engine.add_animated_texture(texture_path, None, animation),
+                None => engine.add_texture(texture_path, None),
+            };
+            match added {
+                Ok(_) => {
+                    let texture_index = self.texture_paths.len() as u32;
+                    self.texture_paths.push(texture_path.to_string());
+                    self.texture_indices.insert(block_id.to_string(), texture_index);
+                }
+                Err(e) => println!("Block {} has no texture: {}", block_id, e),
             }
         }
         
@@ -67,14 +98,21 @@ impl BlockRegistry {
                 "stone" => Box::new(StoneBlock) as Box<dyn Block>,
                 "dirt" => Box::new(DirtBlock) as Box<dyn Block>,
                 "grass" => Box::new(GrassBlock) as Box<dyn Block>,
+                "water" => Box::new(WaterBlock) as Box<dyn Block>,
+                "lava" => Box::new(LavaBlock) as Box<dyn Block>,
+                "sand" => Box::new(SandBlock) as Box<dyn Block>,
+                "gravel" => Box::new(GravelBlock) as Box<dyn Block>,
                 _ => {
                     if let Some(block) = self.blocks.get(id) {
                         Box::new(LuaBlock {
-                            id: block.get_id().to_string(),
-                            name: block.get_name().to_string(),
-                            texture_path: block.get_texture_path().to_string(),
+                            id: block.get_id(),
+                            name: block.get_name(),
+                            texture_path: block.get_texture_path(),
                             solid: block.is_solid(),
                             transparent: block.is_transparent(),
+                            render_layer: block.get_render_layer(),
+                            gravity: block.has_gravity(),
+                            animation: block.get_texture_animation(),
                         }) as Box<dyn Block>
                     } else {
                         Box::new(AirBlock) as Box<dyn Block>
@@ -0,0 +1,90 @@
+/// Items stack up to this count in one slot or one dropped item.
+pub const MAX_STACK_SIZE: u32 = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    /// Items are identified by the id of the block they place.
+    pub item_id: String,
+    pub count: u32,
+}
+
+impl ItemStack {
+    pub fn new(item_id: &str, count: u32) -> Self {
+        Self { item_id: item_id.to_string(), count }
+    }
+}
+
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self { slots: vec![None; size] }
+    }
+
+    pub fn get_slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    /// Adds as much of `stack` as fits, topping up existing stacks first.
+    /// Returns the number of items that did not fit.
+    pub fn add(&mut self, stack: &ItemStack) -> u32 {
+        let mut remaining = stack.count;
+        for slot in self.slots.iter_mut().flatten() {
+            if remaining == 0 {
+                break;
+            }
+            if slot.item_id == stack.item_id && slot.count < MAX_STACK_SIZE {
+                let moved = remaining.min(MAX_STACK_SIZE - slot.count);
+                slot.count += moved;
+                remaining -= moved;
+            }
+        }
+        for slot in self.slots.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let moved = remaining.min(MAX_STACK_SIZE);
+                *slot = Some(ItemStack::new(&stack.item_id, moved));
+                remaining -= moved;
+            }
+        }
+        remaining
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn counts(inventory: &Inventory) -> Vec<Option<u32>> {
+        inventory.get_slots().iter().map(|slot| slot.as_ref().map(|stack| stack.count)).collect()
+    }
+
+    #[test]
+    fn add_splits_into_full_stacks() {
+        let mut inventory = Inventory::new(4);
+        assert_eq!(inventory.add(&ItemStack::new("stone", 150)), 0);
+        assert_eq!(counts(&inventory), vec![Some(64), Some(64), Some(22), None]);
+    }
+
+    #[test]
+    fn add_tops_up_existing_stacks_first() {
+        let mut inventory = Inventory::new(3);
+        inventory.add(&ItemStack::new("stone", 60));
+        inventory.add(&ItemStack::new("dirt", 1));
+        assert_eq!(inventory.add(&ItemStack::new("stone", 10)), 0);
+        assert_eq!(counts(&inventory), vec![Some(64), Some(1), Some(6)]);
+        assert_eq!(inventory.get_slots()[2].as_ref().unwrap().item_id, "stone");
+    }
+
+    #[test]
+    fn add_returns_what_did_not_fit() {
+        let mut inventory = Inventory::new(2);
+        inventory.add(&ItemStack::new("dirt", 1));
+        assert_eq!(inventory.add(&ItemStack::new("stone", 100)), 36);
+        assert_eq!(counts(&inventory), vec![Some(1), Some(64)]);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod block;
-pub mod block_registry;
\ No newline at end of file
+pub mod block_registry;
+pub mod inventory;
\ No newline at end of file
@@ -8,4 +8,6 @@ pub trait GameApp {
     fn input_event(&mut self, engine: &mut Engine, event: &InputEvent);
     fn render(&mut self, engine: &mut Engine);
     fn resize(&mut self, engine: &mut Engine, new_size: winit::dpi::PhysicalSize<u32>);
+    /// Called once before the window closes.
+    fn exiting(&mut self, _engine: &mut Engine) {}
 }
\ No newline at end of file
@@ -6,7 +6,7 @@ use winit::window::{Window, WindowId};
 use glam::Vec2;
 
 use crate::engine::input::process_winit_event;
-use crate::engine::{Engine, GameApp};
+use crate::engine::{Engine, GameApp, RendererError};
 
 pub struct AppRunner<T: GameApp> {
     window: Arc<Window>,
@@ -16,14 +16,14 @@ pub struct AppRunner<T: GameApp> {
 }
 
 impl<T: GameApp> AppRunner<T> {
-    pub fn new(game_app: T, window: Arc<Window>) -> Self {
-        let engine = Engine::new(window.clone());
-        Self {
+    pub fn new(game_app: T, window: Arc<Window>) -> Result<Self, RendererError> {
+        let engine = Engine::new(window.clone())?;
+        Ok(Self {
             window: window,
             engine: Box::new(engine),
             game_app,
             initialized: false,
-        }
+        })
     }
 }
 
@@ -45,6 +45,7 @@ impl<T: GameApp> ApplicationHandler for AppRunner<T> {
 
         match event {
             WindowEvent::CloseRequested => {
+                self.game_app.exiting(self.engine.as_mut());
                 event_loop.exit();
             }
             WindowEvent::Resized(new_size) => {
@@ -61,7 +62,7 @@ impl<T: GameApp> ApplicationHandler for AppRunner<T> {
                     window_id: _window_id,
                     event,
                 };
-                if let Some(input_event) = process_winit_event(&winit_event) {
+                for input_event in process_winit_event(&winit_event) {
                     self.game_app.input_event(self.engine.as_mut(), &input_event);
                 }
             }
@@ -82,7 +83,7 @@ impl<T: GameApp> ApplicationHandler for AppRunner<T> {
             device_id,
             event,
         };
-        if let Some(input_event) = process_winit_event(&winit_event) {
+        for input_event in process_winit_event(&winit_event) {
             self.game_app.input_event(self.engine.as_mut(), &input_event);
         }
     }
@@ -0,0 +1,164 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crate::engine::logger::Logger;
+
+/// Folder holding resource packs. Each pack is a directory laid out like the game folder,
+/// e.g. `resourcepacks/hd/assets/textures/block/dirt.png` replaces `assets/textures/block/dirt.png`.
+pub const RESOURCE_PACK_DIR: &str = "resourcepacks";
+
+/// Handle shared by everything that loads assets, so changing the packs affects all of them.
+pub type SharedAssets = Arc<RwLock<Assets>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetSource {
+    Base,
+    ResourcePack(String),
+    Mod(String),
+}
+
+/// One folder asset paths are looked up in.
+#[derive(Debug, Clone)]
+pub struct AssetLayer {
+    pub source: AssetSource,
+    pub root: PathBuf,
+}
+
+/// Layered view over the asset folders. Asset paths stay relative strings like
+/// `assets/textures/block/dirt.png`; each layer is stacked on the previous one and wins over it:
+/// the base game files, then the enabled resource packs, then mod folders.
+pub struct Assets {
+    base: AssetLayer,
+    pack_dir: PathBuf,
+    /// Highest priority first, the order of the `resource_packs` setting.
+    packs: Vec<AssetLayer>,
+    mods: Vec<AssetLayer>,
+}
+
+impl Assets {
+    pub fn new(resource_packs: &[String]) -> Self {
+        Self::with_roots(".", RESOURCE_PACK_DIR, resource_packs)
+    }
+
+    /// Like `new`, with the game folder at `base` and the packs in `pack_dir`.
+    pub fn with_roots(base: impl Into<PathBuf>, pack_dir: impl Into<PathBuf>, resource_packs: &[String]) -> Self {
+        let mut assets = Self {
+            base: AssetLayer { source: AssetSource::Base, root: base.into() },
+            pack_dir: pack_dir.into(),
+            packs: Vec::new(),
+            mods: Vec::new(),
+        };
+        assets.set_resource_packs(resource_packs);
+        assets
+    }
+
+    pub fn shared(self) -> SharedAssets {
+        Arc::new(RwLock::new(self))
+    }
+
+    /// Enables `packs`, the first one with the highest priority. Packs that don't exist are skipped.
+    pub fn set_resource_packs(&mut self, packs: &[String]) {
+        self.packs.clear();
+        for name in packs {
+            let root = self.pack_dir.join(name);
+            if !root.is_dir() {
+                Logger::warn(&format!("Resource pack '{}' not found in {}", name, self.pack_dir.display()));
+                continue;
+            }
+            self.packs.push(AssetLayer { source: AssetSource::ResourcePack(name.clone()), root });
+        }
+    }
+
+    /// Names of the enabled packs, highest priority first.
+    pub fn resource_packs(&self) -> Vec<String> {
+        self.packs
+            .iter()
+            .filter_map(|layer| match &layer.source {
+                AssetSource::ResourcePack(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every pack in the pack folder, enabled or not, sorted by name.
+    pub fn available_resource_packs(&self) -> Vec<String> {
+        let mut packs: Vec<String> = std::fs::read_dir(&self.pack_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        packs.sort();
+        packs
+    }
+
+    /// Adds a mod folder on top of everything loaded so far.
+    pub fn add_mod(&mut self, name: &str, dir: impl Into<PathBuf>) {
+        self.mods.push(AssetLayer { source: AssetSource::Mod(name.to_string()), root: dir.into() });
+    }
+
+    /// All layers, highest priority first.
+    pub fn layers(&self) -> impl Iterator<Item = &AssetLayer> {
+        self.mods.iter().rev().chain(self.packs.iter()).chain(std::iter::once(&self.base))
+    }
+
+    /// The file `path` refers to in the highest layer that has it.
+    pub fn resolve(&self, path: &str) -> Option<PathBuf> {
+        self.layers().map(|layer| layer.root.join(path)).find(|file| file.is_file())
+    }
+
+    /// Which layer `path` resolves from, for debugging packs.
+    pub fn source_of(&self, path: &str) -> Option<&AssetSource> {
+        self.layers().find(|layer| layer.root.join(path).is_file()).map(|layer| &layer.source)
+    }
+
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let file = self.resolve(path).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("asset not found: {}", path)))?;
+        std::fs::read(file)
+    }
+
+    pub fn read_to_string(&self, path: &str) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let root = std::env::temp_dir().join(format!("voxel_assets_{}", std::process::id()));
+        let write = |path: &str, text: &str| {
+            let file = root.join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, text).unwrap();
+        };
+        write("game/assets/a.txt", "base");
+        write("game/assets/b.txt", "base");
+        write("game/assets/c.txt", "base");
+        write("packs/low/assets/a.txt", "low");
+        write("packs/low/assets/b.txt", "low");
+        write("packs/high/assets/a.txt", "high");
+        write("mod/assets/c.txt", "mod");
+
+        let packs = ["high".to_string(), "missing".to_string(), "low".to_string()];
+        let mut assets = Assets::with_roots(root.join("game"), root.join("packs"), &packs);
+        assets.add_mod("example", root.join("mod"));
+
+        assert_eq!(assets.resource_packs(), ["high", "low"]);
+        assert_eq!(assets.available_resource_packs(), ["high", "low"]);
+        assert_eq!(assets.read_to_string("assets/a.txt").unwrap(), "high");
+        assert_eq!(assets.read_to_string("assets/b.txt").unwrap(), "low");
+        assert_eq!(assets.read_to_string("assets/c.txt").unwrap(), "mod");
+        assert_eq!(assets.source_of("assets/b.txt"), Some(&AssetSource::ResourcePack("low".to_string())));
+
+        assets.set_resource_packs(&[]);
+        assert_eq!(assets.read_to_string("assets/a.txt").unwrap(), "base");
+        assert!(assets.resolve("assets/none.txt").is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
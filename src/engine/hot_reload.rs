@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often `FileWatcher::poll` scans the watched folders.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Finds files that were added, modified or removed by scanning folders every `POLL_INTERVAL`.
+/// Polling keeps it portable; the folders are small enough for a scan to be cheap.
+pub struct FileWatcher {
+    roots: Vec<PathBuf>,
+    extensions: Vec<&'static str>,
+    files: HashMap<PathBuf, SystemTime>,
+    interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+impl FileWatcher {
+    /// Watches files ending in one of `extensions`, e.g. `["png", "wgsl"]`.
+    pub fn new(extensions: &[&'static str]) -> Self {
+        Self {
+            roots: Vec::new(),
+            extensions: extensions.to_vec(),
+            files: HashMap::new(),
+            interval: POLL_INTERVAL,
+            last_poll: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the watched folders, searched recursively. Files found in new folders count as
+    /// already seen, so changing folders doesn't report everything in them.
+    pub fn set_roots(&mut self, roots: Vec<PathBuf>) {
+        if roots == self.roots {
+            return;
+        }
+        self.roots = roots;
+        self.files = self.collect();
+    }
+
+    /// Files changed since the last scan, or nothing if the last scan was less than the
+    /// interval ago.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        if self.last_poll.is_some_and(|last| last.elapsed() < self.interval) {
+            return Vec::new();
+        }
+        self.scan()
+    }
+
+    /// Scans now and returns the files that changed since the previous scan, sorted.
+    pub fn scan(&mut self) -> Vec<PathBuf> {
+        self.last_poll = Some(Instant::now());
+        let files = self.collect();
+        let mut changed: Vec<PathBuf> = files
+            .iter()
+            .filter(|(path, modified)| self.files.get(*path) != Some(modified))
+            .map(|(path, _)| path.clone())
+            .chain(self.files.keys().filter(|path| !files.contains_key(*path)).cloned())
+            .collect();
+        changed.sort();
+        self.files = files;
+        changed
+    }
+
+    fn collect(&self) -> HashMap<PathBuf, SystemTime> {
+        let mut files = HashMap::new();
+        for root in &self.roots {
+            self.collect_dir(root, &mut files);
+        }
+        files
+    }
+
+    fn collect_dir(&self, dir: &Path, files: &mut HashMap<PathBuf, SystemTime>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.collect_dir(&path, files);
+                continue;
+            }
+            let watched = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| self.extensions.contains(&ext));
+            if !watched {
+                continue;
+            }
+            if let Ok(modified) = entry.metadata().and_then(|meta| meta.modified()) {
+                files.insert(path, modified);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_added_modified_and_removed_files() {
+        let root = std::env::temp_dir().join(format!("voxel_watch_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("block")).unwrap();
+        let dirt = root.join("block/dirt.png");
+        let stone = root.join("block/stone.png");
+        std::fs::write(&dirt, "dirt").unwrap();
+        std::fs::write(root.join("notes.txt"), "ignored").unwrap();
+
+        let mut watcher = FileWatcher::new(&["png", "wgsl"]).with_interval(Duration::from_secs(3600));
+        watcher.set_roots(vec![root.clone()]);
+        assert!(watcher.scan().is_empty());
+
+        // Pushed into the future so the change shows up even with coarse timestamps
+        let file = std::fs::File::options().write(true).open(&dirt).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        std::fs::write(&stone, "stone").unwrap();
+        assert_eq!(watcher.scan(), [dirt.clone(), stone.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        std::fs::remove_file(&stone).unwrap();
+        assert_eq!(watcher.scan(), [stone]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
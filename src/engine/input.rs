@@ -5,39 +5,64 @@ pub enum InputEvent {
     MouseButton(winit::event::MouseButton, winit::event::ElementState),
     MouseMotion(f32, f32),
     CursorMoved(f32, f32),
+    /// Shift, Ctrl, Alt and Super currently held.
+    ModifiersChanged(winit::keyboard::ModifiersState),
+    /// Text typed by a key press, with the keyboard layout, Shift and dead keys applied.
+    /// Follows the `KeyPressed` of the same key; never contains control characters.
+    Text(String),
+    /// Text being composed in an input method, not part of the input yet. The range is the
+    /// IME cursor as byte offsets into the text.
+    ImePreedit(String, Option<(usize, usize)>),
+    /// Text confirmed by the input method.
+    ImeCommit(String),
 }
 
-pub fn process_winit_event(event: &winit::event::Event<()>) -> Option<InputEvent> {
+/// Turns a winit event into input events; a key press that types text gives both `KeyPressed`
+/// and `Text`.
+pub fn process_winit_event(event: &winit::event::Event<()>) -> Vec<InputEvent> {
+    let mut events = Vec::new();
     match event {
         winit::event::Event::WindowEvent { event, .. } => {
             match event {
                 winit::event::WindowEvent::KeyboardInput { event, .. } => {
                     if let winit::keyboard::PhysicalKey::Code(keycode) = event.physical_key {
                         match event.state {
-                            winit::event::ElementState::Pressed => Some(InputEvent::KeyPressed(keycode)),
-                            winit::event::ElementState::Released => Some(InputEvent::KeyReleased(keycode)),
+                            winit::event::ElementState::Pressed => events.push(InputEvent::KeyPressed(keycode)),
+                            winit::event::ElementState::Released => events.push(InputEvent::KeyReleased(keycode)),
                         }
-                    } else {
-                        None
                     }
+                    if event.state == winit::event::ElementState::Pressed {
+                        // Backspace, Enter, Escape and the like also carry text, as control characters
+                        if let Some(text) = event.text.as_ref().filter(|text| !text.chars().any(char::is_control)) {
+                            events.push(InputEvent::Text(text.to_string()));
+                        }
+                    }
+                }
+                winit::event::WindowEvent::ModifiersChanged(modifiers) => {
+                    events.push(InputEvent::ModifiersChanged(modifiers.state()));
                 }
+                winit::event::WindowEvent::Ime(ime) => match ime {
+                    winit::event::Ime::Preedit(text, cursor) => events.push(InputEvent::ImePreedit(text.clone(), *cursor)),
+                    winit::event::Ime::Commit(text) => events.push(InputEvent::ImeCommit(text.clone())),
+                    // Disabling drops whatever was being composed
+                    winit::event::Ime::Disabled => events.push(InputEvent::ImePreedit(String::new(), None)),
+                    winit::event::Ime::Enabled => {}
+                },
                 winit::event::WindowEvent::MouseInput { button, state, .. } => {
-                    Some(InputEvent::MouseButton(*button, *state))
+                    events.push(InputEvent::MouseButton(*button, *state));
                 }
                 winit::event::WindowEvent::CursorMoved { position, .. } => {
-                    Some(InputEvent::CursorMoved(position.x as f32, position.y as f32))
+                    events.push(InputEvent::CursorMoved(position.x as f32, position.y as f32));
                 }
-                _ => None
+                _ => {}
             }
         }
         winit::event::Event::DeviceEvent { event, .. } => {
-            match event {
-                winit::event::DeviceEvent::MouseMotion { delta } => {
-                    Some(InputEvent::MouseMotion(delta.0 as f32, delta.1 as f32))
-                }
-                _ => None
+            if let winit::event::DeviceEvent::MouseMotion { delta } = event {
+                events.push(InputEvent::MouseMotion(delta.0 as f32, delta.1 as f32));
             }
         }
-        _ => None
+        _ => {}
     }
+    events
 }
\ No newline at end of file
@@ -7,6 +7,9 @@ pub mod ui;
 pub mod app;
 pub mod app_runner;
 pub mod runner;
+pub mod settings;
+pub mod assets;
+pub mod hot_reload;
 
 pub use render::renderer::Renderer;
 pub use render::ui::UIRenderer;
@@ -16,30 +19,113 @@ pub use model::vertex::Vertex;
 pub use cursor::CursorManager;
 pub use app::GameApp;
 pub use runner::run_app;
+pub use settings::{CameraSettings, GraphicsBackend, GraphicsSettings, Settings};
+pub use render::error::{RendererError, TextureError};
+pub use render::animation::TextureAnimation;
+pub use assets::{Assets, SharedAssets};
 
 use logger::Logger;
+use settings::SETTINGS_PATH;
+use hot_reload::FileWatcher;
+use render::font::{Font, DEFAULT_FONT, FALLBACK_FONTS};
+use render::pipelines::SHADER_DIR;
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use winit::window::Window;
 use glam::{Vec2, Vec4};
 
+/// Files hot reload looks at: textures, their animations and shaders.
+const HOT_RELOAD_EXTENSIONS: &[&str] = &["png", "anim", "wgsl"];
+
 pub struct Engine<'window> {
     pub renderer: Renderer<'window>,
     pub image_renderer: ImageRenderer,
     pub cursor_manager: CursorManager,
-    window: Arc<Window>,
+    pub camera_settings: CameraSettings,
+    pub graphics_settings: GraphicsSettings,
+    /// Asset layers: base files, enabled resource packs and mod folders.
+    pub assets: SharedAssets,
+    /// Watches asset and shader files while hot reload is on, see `set_hot_reload`.
+    hot_reload: Option<FileWatcher>,
+    /// `None` for a headless engine.
+    window: Option<Arc<Window>>,
     ui_textures: std::collections::HashMap<String, u32>,
 }
 
 impl<'window> Engine<'window> {
    
-    pub fn new(window: Arc<Window>) -> Self {
+    pub fn new(window: Arc<Window>) -> Result<Self, RendererError> {
         Logger::info("Initializing engine with textures");
-        let renderer = Renderer::new(window.clone());
+        let settings = Settings::load(SETTINGS_PATH);
+        let renderer = Renderer::new(window.clone(), &Self::runtime_graphics(&settings))?;
+        Ok(Self::with_renderer(renderer, settings, Some(window)))
+    }
+}
+
+impl Engine<'static> {
+    /// An engine without a window rendering `width` x `height` frames offscreen, for tests and
+    /// tools. Frames are read back with `Renderer::read_frame`; cursor calls do nothing.
+    pub fn headless(width: u32, height: u32, settings: Settings) -> Result<Self, RendererError> {
+        Logger::info("Initializing headless engine");
+        let renderer = Renderer::new_headless(width, height, &Self::runtime_graphics(&settings))?;
+        let mut engine = Self::with_renderer(renderer, settings, None);
+        // Frames must only depend on what the caller loads
+        engine.set_hot_reload(false);
+        Ok(engine)
+    }
+}
+
+impl<'window> Engine<'window> {
+    fn with_renderer(mut renderer: Renderer<'window>, settings: Settings, window: Option<Arc<Window>>) -> Self {
         let image_renderer = ImageRenderer::new(renderer.get_device(), renderer.get_surface_format());
         let cursor_manager = CursorManager::new();
+        let assets = Assets::new(&settings.resource_packs).shared();
+        renderer.texture_manager.set_assets(assets.clone());
         
-        Self { renderer, image_renderer, cursor_manager, window, ui_textures: std::collections::HashMap::new() }
+        let mut engine = Self {
+            renderer,
+            image_renderer,
+            cursor_manager,
+            camera_settings: settings.camera,
+            graphics_settings: settings.graphics,
+            assets,
+            // On in debug builds, where art and shaders are iterated on
+            hot_reload: cfg!(debug_assertions).then(|| FileWatcher::new(HOT_RELOAD_EXTENSIONS)),
+            window,
+            ui_textures: std::collections::HashMap::new(),
+        };
+        engine.load_font();
+        engine
+    }
+
+    /// Loads the UI font through the asset layers, keeping the bitmap font if it can't be read.
+    fn load_font(&mut self) {
+        let assets = self.assets.read().unwrap();
+        let mut font = match Font::load(&assets, DEFAULT_FONT) {
+            Ok(font) => font,
+            Err(e) => {
+                Logger::warn(&format!("Failed to load UI font, using the bitmap font: {}", e));
+                return;
+            }
+        };
+        for path in FALLBACK_FONTS.iter().filter(|path| assets.resolve(path).is_some()) {
+            if let Err(e) = font.load_fallback(&assets, path) {
+                Logger::warn(&format!("Failed to load fallback font: {}", e));
+            }
+        }
+        drop(assets);
+        self.renderer.ui.set_font(Some(font));
+    }
+
+    /// Graphics settings with the backend environment override applied. The override only
+    /// applies to this run, it is never written back to the settings file.
+    fn runtime_graphics(settings: &Settings) -> GraphicsSettings {
+        let mut graphics = settings.graphics;
+        if let Some(backend) = GraphicsBackend::from_env() {
+            graphics.backend = backend;
+        }
+        graphics
     }
 
     pub fn clear_meshes(&mut self) {
@@ -47,7 +133,85 @@ impl<'window> Engine<'window> {
     }
 
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        self.renderer.render()
+        self.poll_hot_reload();
+        self.renderer.render(&self.camera_settings, &self.graphics_settings)
+    }
+
+    /// Saves the next rendered frame, world and UI, as a timestamped PNG in `screenshots/`,
+    /// supersampled by the `screenshot_scale` setting.
+    pub fn take_screenshot(&mut self) {
+        self.renderer.request_screenshot(self.graphics_settings.screenshot_scale);
+    }
+
+    /// Writes the current camera and graphics settings back to the settings file.
+    pub fn save_settings(&self) {
+        let settings = Settings {
+            camera: self.camera_settings,
+            graphics: self.graphics_settings,
+            resource_packs: self.assets.read().unwrap().resource_packs(),
+        };
+        if let Err(e) = settings.save(SETTINGS_PATH) {
+            Logger::error(&format!("Failed to save settings: {}", e));
+        }
+    }
+
+    /// Enables `packs`, the first one with the highest priority, reloads the textures and
+    /// saves the list.
+    pub fn set_resource_packs(&mut self, packs: Vec<String>) {
+        self.assets.write().unwrap().set_resource_packs(&packs);
+        Logger::info(&format!("Resource packs: {:?}", self.assets.read().unwrap().resource_packs()));
+        self.renderer.texture_manager.reload_textures();
+        self.load_font();
+        self.save_settings();
+    }
+
+    /// Re-reads the pack list from the settings file and reloads the textures, so packs can be
+    /// edited without restarting.
+    pub fn reload_resource_packs(&mut self) {
+        let packs = Settings::load(SETTINGS_PATH).resource_packs;
+        self.assets.write().unwrap().set_resource_packs(&packs);
+        Logger::info(&format!("Resource packs: {:?}", self.assets.read().unwrap().resource_packs()));
+        self.renderer.texture_manager.reload_textures();
+        self.load_font();
+    }
+
+    /// Reloads textures and shaders when their files change. Textures are watched in every asset
+    /// layer, shaders in `SHADER_DIR` when the source tree is there.
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        if enabled != self.hot_reload.is_some() {
+            self.hot_reload = enabled.then(|| FileWatcher::new(HOT_RELOAD_EXTENSIONS));
+        }
+    }
+
+    fn poll_hot_reload(&mut self) {
+        let Some(watcher) = &mut self.hot_reload else {
+            return;
+        };
+        let mut roots: Vec<PathBuf> = self.assets.read().unwrap().layers().map(|layer| layer.root.join("assets")).collect();
+        roots.push(PathBuf::from(SHADER_DIR));
+        watcher.set_roots(roots);
+        let changed = watcher.poll();
+        if changed.is_empty() {
+            return;
+        }
+        
+        for file in changed.iter().filter(|file| file.extension().is_some_and(|ext| ext == "wgsl")) {
+            let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let source = match std::fs::read_to_string(file) {
+                Ok(source) => source,
+                Err(e) => {
+                    Logger::error(&format!("Failed to read shader {}: {}", file.display(), e));
+                    continue;
+                }
+            };
+            match self.renderer.reload_shader(name, &source) {
+                Ok(()) => Logger::info(&format!("Reloaded shader {}", name)),
+                Err(e) => Logger::error(&format!("Shader {} failed to compile:\n{}", name, e.message)),
+            }
+        }
+        self.renderer.texture_manager.reload_changed(&changed);
     }
 
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
@@ -61,15 +225,28 @@ impl<'window> Engine<'window> {
     }
     
     pub fn lock_cursor(&mut self) {
-        self.cursor_manager.lock_cursor(&self.window);
+        if let Some(window) = &self.window {
+            self.cursor_manager.lock_cursor(window);
+        }
     }
     
     pub fn unlock_cursor(&mut self) {
-        self.cursor_manager.unlock_cursor(&self.window);
+        if let Some(window) = &self.window {
+            self.cursor_manager.unlock_cursor(window);
+        }
     }
     
     pub fn toggle_cursor(&mut self) {
-        self.cursor_manager.toggle_cursor(&self.window);
+        if let Some(window) = &self.window {
+            self.cursor_manager.toggle_cursor(window);
+        }
+    }
+    
+    /// Lets the OS input method (IME) compose text into the window, while a text field is focused.
+    pub fn set_ime_allowed(&mut self, allowed: bool) {
+        if let Some(window) = &self.window {
+            window.set_ime_allowed(allowed);
+        }
     }
     
     pub fn is_cursor_locked(&self) -> bool {
@@ -80,7 +257,17 @@ impl<'window> Engine<'window> {
     //     self.ui_renderer.clear();
     // }
     
-    pub fn add_texture(&mut self, path: &str, name: Option<&str>) -> Option<u32> {
+    pub fn add_texture(&mut self, path: &str, name: Option<&str>) -> Result<u32, TextureError> {
         self.renderer.add_texture(path, name)
     }
+
+    /// Adds a texture that is a vertical strip of frames played with `animation`.
+    pub fn add_animated_texture(&mut self, path: &str, name: Option<&str>, animation: TextureAnimation) -> Result<u32, TextureError> {
+        self.renderer.texture_manager.add_animated_texture(path, name, Some(animation))
+    }
+
+    /// Advances animated textures to the world tick `tick`.
+    pub fn tick_animations(&mut self, tick: u64) {
+        self.renderer.texture_manager.update_animations(tick);
+    }
 }
\ No newline at end of file
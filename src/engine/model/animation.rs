@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use glam::Vec3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    /// Seconds from the start of the animation.
+    pub time: f32,
+    /// Euler XYZ rotation of the part, radians.
+    pub rotation: Vec3,
+}
+
+/// Keyframes of one model part, sorted by time.
+#[derive(Debug, Clone)]
+pub struct AnimationTrack {
+    pub part: String,
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl AnimationTrack {
+    fn sample(&self, time: f32) -> Vec3 {
+        let Some(first) = self.keyframes.first() else { return Vec3::ZERO };
+        if time <= first.time {
+            return first.rotation;
+        }
+        for pair in self.keyframes.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if time <= b.time {
+                let t = (time - a.time) / (b.time - a.time).max(f32::EPSILON);
+                return a.rotation.lerp(b.rotation, t);
+            }
+        }
+        self.keyframes.last().unwrap().rotation
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    pub name: String,
+    /// Seconds.
+    pub length: f32,
+    pub looping: bool,
+    pub tracks: Vec<AnimationTrack>,
+}
+
+impl Animation {
+    pub fn new(name: &str, length: f32, looping: bool) -> Self {
+        Self { name: name.to_string(), length, looping, tracks: Vec::new() }
+    }
+
+    /// Adds a track from (time, rotation) pairs.
+    pub fn with_track(mut self, part: &str, keyframes: &[(f32, Vec3)]) -> Self {
+        let mut keyframes: Vec<Keyframe> = keyframes.iter()
+            .map(|(time, rotation)| Keyframe { time: *time, rotation: *rotation })
+            .collect();
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        self.tracks.push(AnimationTrack { part: part.to_string(), keyframes });
+        self
+    }
+
+    /// Part rotations at `time`, ready for `BoxModel::pose`.
+    pub fn sample(&self, time: f32) -> HashMap<String, Vec3> {
+        let time = if self.looping && self.length > 0.0 {
+            time.rem_euclid(self.length)
+        } else {
+            time.clamp(0.0, self.length)
+        };
+        self.tracks.iter().map(|track| (track.part.clone(), track.sample(time))).collect()
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use glam::{EulerRot, Mat4, Quat, Vec3};
+
+use crate::engine::render::texture_manager::TextureManager;
+use crate::engine::Vertex;
+
+/// Normal and corners of each face of a unit cube, counter-clockwise from outside.
+const CUBE_FACES: [([f32; 3], [[f32; 3]; 4]); 6] = [
+    ([0.0, 0.0, 1.0], [[0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [1.0, 1.0, 1.0], [0.0, 1.0, 1.0]]),
+    ([0.0, 0.0, -1.0], [[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]]),
+    ([1.0, 0.0, 0.0], [[1.0, 0.0, 1.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [1.0, 1.0, 1.0]]),
+    ([-1.0, 0.0, 0.0], [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 1.0], [0.0, 1.0, 0.0]]),
+    ([0.0, 1.0, 0.0], [[0.0, 1.0, 1.0], [1.0, 1.0, 1.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]),
+    ([0.0, -1.0, 0.0], [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 1.0], [0.0, 0.0, 1.0]]),
+];
+
+/// One textured box of a model part, in model space (blocks).
+#[derive(Debug, Clone)]
+pub struct ModelBox {
+    pub min: Vec3,
+    pub size: Vec3,
+    /// Name of the texture in the `TextureManager`.
+    pub texture: String,
+    /// Region of the texture used on every face, as fractions (u_min, v_min, u_max, v_max).
+    pub uv: [f32; 4],
+}
+
+impl ModelBox {
+    pub fn new(min: Vec3, size: Vec3, texture: &str) -> Self {
+        Self { min, size, texture: texture.to_string(), uv: [0.0, 0.0, 1.0, 1.0] }
+    }
+
+    pub fn with_uv(mut self, uv: [f32; 4]) -> Self {
+        self.uv = uv;
+        self
+    }
+}
+
+/// A rigid group of boxes that rotates around `pivot`, relative to its parent part.
+#[derive(Debug, Clone)]
+pub struct ModelPart {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub pivot: Vec3,
+    pub boxes: Vec<ModelBox>,
+}
+
+/// Hierarchical box model. Parts must be added after their parent.
+#[derive(Debug, Clone, Default)]
+pub struct BoxModel {
+    pub parts: Vec<ModelPart>,
+}
+
+impl BoxModel {
+    pub fn new() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    pub fn with_part(mut self, name: &str, parent: Option<&str>, pivot: Vec3, boxes: Vec<ModelBox>) -> Self {
+        let parent = parent.and_then(|parent| self.find_part(parent));
+        self.parts.push(ModelPart { name: name.to_string(), parent, pivot, boxes });
+        self
+    }
+
+    pub fn find_part(&self, name: &str) -> Option<usize> {
+        self.parts.iter().position(|part| part.name == name)
+    }
+
+    /// Model-space matrix of every part for the given part rotations (euler XYZ, radians);
+    /// parts without an entry keep their rest pose.
+    pub fn pose(&self, rotations: &HashMap<String, Vec3>) -> Vec<Mat4> {
+        let mut matrices: Vec<Mat4> = Vec::with_capacity(self.parts.len());
+        for part in &self.parts {
+            let parent = part.parent.map_or(Mat4::IDENTITY, |index| matrices[index]);
+            let local = match rotations.get(&part.name) {
+                Some(rotation) => {
+                    let rotation = Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z);
+                    Mat4::from_translation(part.pivot)
+                        * Mat4::from_quat(rotation)
+                        * Mat4::from_translation(-part.pivot)
+                }
+                None => Mat4::IDENTITY,
+            };
+            matrices.push(parent * local);
+        }
+        matrices
+    }
+
+    /// Geometry of one part in model space; textures that are not loaded are skipped.
+    pub fn build_part_mesh(&self, part: usize, texture_manager: &TextureManager) -> (Vec<Vertex>, Vec<u16>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for model_box in &self.parts[part].boxes {
+            let Some(texture_info) = texture_manager.get_texture_info(&model_box.texture) else {
+                println!("Model texture '{}' is not loaded", model_box.texture);
+                continue;
+            };
+            let (u_min, v_min, u_max, v_max) = texture_info.uvs;
+            let u = |t: f32| u_min + (u_max - u_min) * t;
+            let v = |t: f32| v_min + (v_max - v_min) * t;
+            let [bu0, bv0, bu1, bv1] = model_box.uv;
+            let uvs = [[u(bu0), v(bv1)], [u(bu1), v(bv1)], [u(bu1), v(bv0)], [u(bu0), v(bv0)]];
+            let layer = texture_info.atlas_position.2;
+
+            for (normal, corners) in CUBE_FACES.iter() {
+                let base_index = vertices.len() as u16;
+                for i in 0..4 {
+                    let corner = model_box.min + Vec3::from(corners[i]) * model_box.size;
+                    vertices.push(Vertex::new(corner.into(), *normal, uvs[i], layer, 1.0));
+                }
+                indices.extend_from_slice(&[
+                    base_index, base_index + 1, base_index + 2,
+                    base_index + 2, base_index + 3, base_index,
+                ]);
+            }
+        }
+        (vertices, indices)
+    }
+}
@@ -1 +1,5 @@
-pub mod vertex;
\ No newline at end of file
+pub mod vertex;
+pub mod instance;
+pub mod box_model;
+pub mod animation;
+pub mod aabb;
\ No newline at end of file
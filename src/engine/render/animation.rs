@@ -0,0 +1,127 @@
+use crate::engine::assets::Assets;
+
+/// Extension of the optional file next to a texture that describes its animation,
+/// e.g. `water.png` + `water.png.anim`.
+pub const ANIMATION_EXTENSION: &str = "anim";
+
+/// Timing of an animated texture. The image is a vertical strip of square frames, the first
+/// one at the top. The animation loops over `frames`, each shown for its number of ticks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureAnimation {
+    /// Ticks per frame for frames that don't set their own.
+    pub frame_time: u32,
+    /// Frame indices into the strip with their duration in ticks; empty plays the whole strip in order.
+    pub frames: Vec<(u32, Option<u32>)>,
+}
+
+impl Default for TextureAnimation {
+    fn default() -> Self {
+        Self { frame_time: 1, frames: Vec::new() }
+    }
+}
+
+impl TextureAnimation {
+    pub fn new(frame_time: u32) -> Self {
+        Self { frame_time, frames: Vec::new() }
+    }
+
+    pub fn with_frames(mut self, frames: Vec<(u32, Option<u32>)>) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Reads `<texture_path>.anim` from the asset layers if there is one.
+    pub fn load_sidecar(assets: &Assets, texture_path: &str) -> Option<Result<Self, String>> {
+        let path = format!("{}.{}", texture_path, ANIMATION_EXTENSION);
+        assets.resolve(&path)?;
+        Some(assets.read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| Self::parse(&text)))
+    }
+
+    /// Parses `key = value` lines: `frametime = 2` and `frames = 0 1 2 3:10 2`, where `:10`
+    /// shows that frame for 10 ticks. `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut animation = Self::default();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("expected `key = value`, got '{}'", line));
+            };
+            match key.trim() {
+                "frametime" => {
+                    animation.frame_time = value.trim().parse().map_err(|_| format!("invalid frametime '{}'", value.trim()))?;
+                }
+                "frames" => {
+                    animation.frames = value.split_whitespace().map(parse_frame).collect::<Result<_, _>>()?;
+                }
+                other => return Err(format!("unknown key '{}'", other)),
+            }
+        }
+        Ok(animation)
+    }
+
+    /// Checks the frames against a strip of `frame_count` frames.
+    pub fn validate(&self, frame_count: u32) -> Result<(), String> {
+        if self.frame_time == 0 || self.frames.iter().any(|(_, time)| *time == Some(0)) {
+            return Err("frame times must be at least one tick".to_string());
+        }
+        if let Some((index, _)) = self.frames.iter().find(|(index, _)| *index >= frame_count) {
+            return Err(format!("frame {} is outside the {} frames of the strip", index, frame_count));
+        }
+        Ok(())
+    }
+
+    /// Strip frame shown at `tick`, for a strip of `frame_count` frames.
+    pub fn frame_at(&self, tick: u64, frame_count: u32) -> u32 {
+        let frame_time = self.frame_time.max(1) as u64;
+        if self.frames.is_empty() {
+            return ((tick / frame_time) % frame_count.max(1) as u64) as u32;
+        }
+
+        let duration = |time: &Option<u32>| time.map_or(frame_time, |t| t.max(1) as u64);
+        let total: u64 = self.frames.iter().map(|(_, time)| duration(time)).sum();
+        let mut t = tick % total;
+        for (index, time) in &self.frames {
+            let ticks = duration(time);
+            if t < ticks {
+                return *index;
+            }
+            t -= ticks;
+        }
+        self.frames[0].0
+    }
+}
+
+fn parse_frame(frame: &str) -> Result<(u32, Option<u32>), String> {
+    let invalid = || format!("invalid frame '{}'", frame);
+    match frame.split_once(':') {
+        Some((index, time)) => Ok((index.parse().map_err(|_| invalid())?, Some(time.parse().map_err(|_| invalid())?))),
+        None => Ok((frame.parse().map_err(|_| invalid())?, None)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plays_the_whole_strip_by_default() {
+        let animation = TextureAnimation::new(2);
+        let frames: Vec<u32> = (0..8).map(|tick| animation.frame_at(tick, 3)).collect();
+        assert_eq!(frames, [0, 0, 1, 1, 2, 2, 0, 0]);
+    }
+
+    #[test]
+    fn parses_frame_list_with_timings() {
+        let animation = TextureAnimation::parse("# lava\nframetime = 3\nframes = 0 1:1 0\n").unwrap();
+        assert_eq!(animation, TextureAnimation::new(3).with_frames(vec![(0, None), (1, Some(1)), (0, None)]));
+        let frames: Vec<u32> = (0..8).map(|tick| animation.frame_at(tick, 2)).collect();
+        assert_eq!(frames, [0, 0, 0, 1, 0, 0, 0, 0]);
+
+        assert!(animation.validate(2).is_ok());
+        assert!(animation.validate(1).is_err());
+        assert!(TextureAnimation::parse("frames = 0 x").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+/// Why the renderer could not start.
+#[derive(Debug)]
+pub enum RendererError {
+    CreateSurface(wgpu::CreateSurfaceError),
+    /// No adapter on any of the requested backends can present to the window.
+    NoAdapter { backends: wgpu::Backends },
+    RequestDevice(wgpu::RequestDeviceError),
+    /// The adapter cannot present to this window.
+    UnsupportedSurface { adapter: String },
+    /// Frames can only be read back from a headless renderer.
+    NotOffscreen,
+    ReadBack(String),
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RendererError::CreateSurface(e) => write!(f, "failed to create window surface: {}", e),
+            RendererError::NoAdapter { backends } => write!(
+                f,
+                "no graphics adapter found for backends {:?}; try another one with the `backend` setting or {}",
+                backends,
+                crate::engine::settings::BACKEND_ENV_VAR
+            ),
+            RendererError::RequestDevice(e) => write!(f, "failed to create graphics device: {}", e),
+            RendererError::UnsupportedSurface { adapter } => write!(f, "adapter '{}' cannot present to the window", adapter),
+            RendererError::NotOffscreen => write!(f, "the renderer draws to a window, frames can't be read back"),
+            RendererError::ReadBack(e) => write!(f, "failed to read the frame back: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RendererError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RendererError::CreateSurface(e) => Some(e),
+            RendererError::RequestDevice(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for RendererError {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        RendererError::CreateSurface(e)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for RendererError {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        RendererError::RequestDevice(e)
+    }
+}
+
+/// Why a texture could not be added to the texture array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureError {
+    /// Every layer of the texture array is taken.
+    CapacityExceeded { path: String, layers: u32 },
+    /// The texture (with its gutter) is bigger than an atlas layer.
+    TooLarge { path: String, width: u32, height: u32, atlas_size: u32 },
+    /// The animation doesn't fit the image, or its `.anim` file can't be read.
+    BadAnimation { path: String, reason: String },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::CapacityExceeded { path, layers } => {
+                write!(f, "no room for texture {}: all {} texture layers are in use", path, layers)
+            }
+            TextureError::TooLarge { path, width, height, atlas_size } => write!(
+                f,
+                "texture {} needs {}x{} pixels including its gutter, more than the {}x{} atlas",
+                path, width, height, atlas_size, atlas_size
+            ),
+            TextureError::BadAnimation { path, reason } => write!(f, "invalid animation for texture {}: {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// A shader that failed to compile or to build its pipelines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderError {
+    /// File name in `pipelines::SHADER_DIR`, e.g. `shader.wgsl`.
+    pub file: String,
+    pub message: String,
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+impl std::error::Error for ShaderError {}
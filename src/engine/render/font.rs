@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font as _, FontArc, GlyphId, PxScale, ScaleFont};
+use glam::Vec2;
+
+use crate::engine::assets::Assets;
+use crate::engine::logger::Logger;
+
+/// Font the UI loads at startup. Without it text falls back to the built-in 8x8 bitmap font.
+pub const DEFAULT_FONT: &str = "assets/fonts/default.ttf";
+
+/// Fonts for characters `DEFAULT_FONT` lacks, tried in order. `default.ttf` only covers Latin;
+/// the shipped `fallback.ttf` (DejaVu Sans Mono) adds Cyrillic, Greek and more, and a resource
+/// pack can replace it with a font for other scripts.
+pub const FALLBACK_FONTS: &[&str] = &["assets/fonts/fallback.ttf"];
+
+/// Pixel height of text drawn at scale 1.
+pub const BASE_FONT_SIZE: f32 = 12.0;
+
+/// Width and height of the glyph atlas. When it fills up it is cleared and glyphs are
+/// rasterised again as they are drawn.
+pub const GLYPH_ATLAS_SIZE: u32 = 1024;
+
+/// Empty texels around each glyph so linear filtering doesn't pick up its neighbours.
+const GLYPH_PADDING: u32 = 1;
+
+/// A glyph to draw, in pixels relative to the top-left of the text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    pub pos: Vec2,
+    pub size: Vec2,
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+}
+
+/// Where a rasterised glyph sits in the atlas.
+#[derive(Debug, Clone, Copy)]
+struct AtlasGlyph {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// From the pen position on the baseline to the glyph's top-left.
+    offset: Vec2,
+}
+
+/// A TrueType/OpenType font with the glyphs drawn so far rasterised into a single-channel atlas.
+/// Glyphs are added on first use, for any size and any character the font or one of its
+/// fallbacks covers.
+pub struct Font {
+    /// The main font first, then the fallbacks. Line metrics come from the main font.
+    faces: Vec<FontArc>,
+    /// Face, glyph and pixel size -> place in the atlas, `None` for glyphs without an outline (space).
+    glyphs: HashMap<(usize, GlyphId, u32), Option<AtlasGlyph>>,
+    pixels: image::GrayImage,
+    cursor: (u32, u32),
+    row_height: u32,
+    /// Rows of `pixels` changed since the last upload.
+    dirty_rows: Option<(u32, u32)>,
+}
+
+impl Font {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        let font = FontArc::try_from_vec(bytes).map_err(|e| e.to_string())?;
+        Ok(Self {
+            faces: vec![font],
+            glyphs: HashMap::new(),
+            pixels: image::GrayImage::new(GLYPH_ATLAS_SIZE, GLYPH_ATLAS_SIZE),
+            cursor: (0, 0),
+            row_height: 0,
+            dirty_rows: None,
+        })
+    }
+
+    /// Reads the font at `path` through the asset layers, so resource packs can replace it.
+    pub fn load(assets: &Assets, path: &str) -> Result<Self, String> {
+        let bytes = assets.read(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::from_bytes(bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Adds a font used for characters the fonts before it don't have.
+    pub fn add_fallback(&mut self, bytes: Vec<u8>) -> Result<(), String> {
+        self.faces.push(FontArc::try_from_vec(bytes).map_err(|e| e.to_string())?);
+        Ok(())
+    }
+
+    /// Adds the fallback font at `path`, read through the asset layers.
+    pub fn load_fallback(&mut self, assets: &Assets, path: &str) -> Result<(), String> {
+        let bytes = assets.read(path).map_err(|e| format!("{}: {}", path, e))?;
+        self.add_fallback(bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Whether the font or a fallback has a glyph for `ch`.
+    pub fn covers(&self, ch: char) -> bool {
+        self.faces.iter().any(|face| face.glyph_id(ch).0 != 0)
+    }
+
+    /// The first face with a glyph for `ch`; the main font's missing-glyph box if none has one.
+    fn face_for(&self, ch: char) -> (usize, GlyphId) {
+        self.faces
+            .iter()
+            .enumerate()
+            .map(|(index, face)| (index, face.glyph_id(ch)))
+            .find(|(_, id)| id.0 != 0)
+            .unwrap_or((0, GlyphId(0)))
+    }
+
+    /// Kerning then advance of the glyph `id` of face `face`, following `previous` on the line.
+    fn advance(&self, face: usize, id: GlyphId, previous: Option<(usize, GlyphId)>, size: u32) -> (f32, f32) {
+        let font = self.faces[face].as_scaled(PxScale::from(size as f32));
+        // Kerning pairs only exist within one face
+        let kern = match previous {
+            Some((previous_face, previous_id)) if previous_face == face => font.kern(previous_id, id),
+            _ => 0.0,
+        };
+        (kern, font.h_advance(id))
+    }
+
+    /// Pixel size used for `scale`, rounded so glyphs are rasterised for whole pixel sizes.
+    pub fn pixel_size(scale: f32) -> u32 {
+        (BASE_FONT_SIZE * scale).round().max(1.0) as u32
+    }
+
+    /// Height of one line of text at `scale`.
+    pub fn line_height(&self, scale: f32) -> f32 {
+        let font = self.faces[0].as_scaled(PxScale::from(Self::pixel_size(scale) as f32));
+        (font.ascent() - font.descent() + font.line_gap()).ceil()
+    }
+
+    /// Size of `text` at `scale`, with kerning. `\n` starts a new line.
+    pub fn measure(&self, text: &str, scale: f32) -> Vec2 {
+        let size = Self::pixel_size(scale);
+        let mut width: f32 = 0.0;
+        let mut lines = 1;
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines += 1;
+            }
+            let mut pen = 0.0;
+            let mut previous = None;
+            for ch in line.chars() {
+                let (face, id) = self.face_for(ch);
+                let (kern, advance) = self.advance(face, id, previous, size);
+                pen += kern + advance;
+                previous = Some((face, id));
+            }
+            width = width.max(pen);
+        }
+        Vec2::new(width.ceil(), lines as f32 * self.line_height(scale))
+    }
+
+    /// Quads for `text` at `scale`, rasterising glyphs that are not in the atlas yet.
+    pub fn layout(&mut self, text: &str, scale: f32) -> Vec<GlyphQuad> {
+        let size = Self::pixel_size(scale);
+        let ascent = self.faces[0].as_scaled(PxScale::from(size as f32)).ascent();
+        let line_height = self.line_height(scale);
+        let atlas_size = GLYPH_ATLAS_SIZE as f32;
+        let mut quads = Vec::new();
+        for (line_index, line) in text.split('\n').enumerate() {
+            let baseline = line_index as f32 * line_height + ascent;
+            let mut pen = 0.0;
+            let mut previous = None;
+            for ch in line.chars() {
+                let (face, id) = self.face_for(ch);
+                let (kern, advance) = self.advance(face, id, previous, size);
+                pen += kern;
+                if let Some(glyph) = self.glyph(face, id, size) {
+                    quads.push(GlyphQuad {
+                        pos: Vec2::new((pen + glyph.offset.x).round(), (baseline + glyph.offset.y).round()),
+                        size: Vec2::new(glyph.width as f32, glyph.height as f32),
+                        uv_min: Vec2::new(glyph.x as f32 / atlas_size, glyph.y as f32 / atlas_size),
+                        uv_max: Vec2::new((glyph.x + glyph.width) as f32 / atlas_size, (glyph.y + glyph.height) as f32 / atlas_size),
+                    });
+                }
+                pen += advance;
+                previous = Some((face, id));
+            }
+        }
+        quads
+    }
+
+    /// The atlas and the rows that changed since the last call, if any.
+    pub fn take_dirty(&mut self) -> Option<(&image::GrayImage, std::ops::Range<u32>)> {
+        let (start, end) = self.dirty_rows.take()?;
+        Some((&self.pixels, start..end))
+    }
+
+    fn glyph(&mut self, face: usize, id: GlyphId, size: u32) -> Option<AtlasGlyph> {
+        if let Some(glyph) = self.glyphs.get(&(face, id, size)) {
+            return *glyph;
+        }
+        let glyph = self.rasterize(face, id, size);
+        self.glyphs.insert((face, id, size), glyph);
+        glyph
+    }
+
+    fn rasterize(&mut self, face: usize, id: GlyphId, size: u32) -> Option<AtlasGlyph> {
+        let outlined = self.faces[face].outline_glyph(id.with_scale(size as f32))?;
+        let bounds = outlined.px_bounds();
+        let width = bounds.width().ceil() as u32;
+        let height = bounds.height().ceil() as u32;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let (x, y) = match self.allocate(width, height) {
+            Some(position) => position,
+            None => {
+                // Full: start over, glyphs still in use are rasterised again as they are drawn
+                Logger::warn("Glyph atlas full, clearing it");
+                self.glyphs.clear();
+                self.pixels = image::GrayImage::new(GLYPH_ATLAS_SIZE, GLYPH_ATLAS_SIZE);
+                self.cursor = (0, 0);
+                self.row_height = 0;
+                self.mark_dirty(0, GLYPH_ATLAS_SIZE);
+                self.allocate(width, height)?
+            }
+        };
+        outlined.draw(|gx, gy, coverage| {
+            if gx < width && gy < height {
+                self.pixels.put_pixel(x + gx, y + gy, image::Luma([(coverage.clamp(0.0, 1.0) * 255.0).round() as u8]));
+            }
+        });
+        self.mark_dirty(y, y + height);
+        Some(AtlasGlyph { x, y, width, height, offset: Vec2::new(bounds.min.x, bounds.min.y) })
+    }
+
+    /// Shelf packing: glyphs go left to right in rows as tall as their tallest glyph.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (padded_width, padded_height) = (width + GLYPH_PADDING * 2, height + GLYPH_PADDING * 2);
+        if padded_width > GLYPH_ATLAS_SIZE {
+            return None;
+        }
+        if self.cursor.0 + padded_width > GLYPH_ATLAS_SIZE {
+            self.cursor = (0, self.cursor.1 + self.row_height);
+            self.row_height = 0;
+        }
+        if self.cursor.1 + padded_height > GLYPH_ATLAS_SIZE {
+            return None;
+        }
+        let position = (self.cursor.0 + GLYPH_PADDING, self.cursor.1 + GLYPH_PADDING);
+        self.cursor.0 += padded_width;
+        self.row_height = self.row_height.max(padded_height);
+        Some(position)
+    }
+
+    fn mark_dirty(&mut self, start: u32, end: u32) {
+        self.dirty_rows = Some(match self.dirty_rows {
+            Some((s, e)) => (s.min(start), e.max(end)),
+            None => (start, end),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn default_font() -> Font {
+        Font::load(&Assets::new(&[]), DEFAULT_FONT).expect("default font")
+    }
+
+    #[test]
+    fn measures_and_lays_out_unicode_text() {
+        let mut font = default_font();
+        let small = font.measure("Café €5", 1.0);
+        let large = font.measure("Café €5", 2.0);
+        assert!(font.covers('é') && font.covers('€'));
+        assert!(small.x > 0.0 && large.x > small.x * 1.5);
+        assert_eq!(font.measure("a\nb", 1.0).y, font.line_height(1.0) * 2.0);
+
+        let quads = font.layout("é€!", 1.0);
+        assert_eq!(quads.len(), 3);
+        assert!(quads.windows(2).all(|pair| pair[1].pos.x > pair[0].pos.x));
+        let width = font.measure("é€!", 1.0).x;
+        assert!(quads.iter().all(|quad| quad.pos.x + quad.size.x <= width + 1.0));
+
+        let (_, rows) = font.take_dirty().expect("glyphs were rasterised");
+        assert!(!rows.is_empty());
+        // Cached glyphs don't touch the atlas again
+        font.layout("é€!", 1.0);
+        assert!(font.take_dirty().is_none());
+    }
+
+    #[test]
+    fn missing_characters_come_from_fallbacks() {
+        let assets = Assets::new(&[]);
+        let mut font = default_font();
+        assert!(!font.covers('Ж'));
+        for path in FALLBACK_FONTS {
+            font.load_fallback(&assets, path).unwrap();
+        }
+        assert!(font.covers('Ж'));
+        assert_eq!(font.face_for('A').0, 0);
+        assert_eq!(font.face_for('Ж').0, 1);
+        assert_eq!(font.layout("Настройки", 1.0).len(), 9);
+    }
+}
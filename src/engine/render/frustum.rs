@@ -0,0 +1,80 @@
+use glam::{Mat4, Vec3, Vec4};
+
+use crate::engine::model::aabb::Aabb;
+
+/// The six clip planes of a camera, pointing inwards (left, right, bottom, top, near, far).
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes from a view-projection matrix with wgpu's 0..1 depth range.
+    pub fn from_view_proj(view_proj: Mat4) -> Self {
+        let row0 = view_proj.row(0);
+        let row1 = view_proj.row(1);
+        let row2 = view_proj.row(2);
+        let row3 = view_proj.row(3);
+        let planes = [
+            row3 + row0,
+            row3 - row0,
+            row3 + row1,
+            row3 - row1,
+            row2,
+            row3 - row2,
+        ]
+        .map(|plane| plane / plane.truncate().length());
+        Self { planes }
+    }
+
+    /// Conservative test: `false` only if the box is completely outside one of the planes.
+    pub fn intersects(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // Corner of the box furthest along the plane normal
+            let corner = Vec3::new(
+                if plane.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if plane.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if plane.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+            plane.truncate().dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Same matrix as glam's `Mat4::perspective_rh` (0..1 depth), looking down -z.
+    fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let h = 1.0 / (0.5 * fov_y).tan();
+        let w = h / aspect;
+        let r = far / (near - far);
+        Mat4::from_cols(
+            Vec4::new(w, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, h, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, r, -1.0),
+            Vec4::new(0.0, 0.0, r * near, 0.0),
+        )
+    }
+
+    fn cube(center: Vec3, half: f32) -> Aabb {
+        Aabb::new(center - Vec3::splat(half), center + Vec3::splat(half))
+    }
+
+    #[test]
+    fn culls_boxes_outside_a_perspective_frustum() {
+        // 90° so the side planes run at 45° through the camera at the origin
+        let frustum = Frustum::from_view_proj(perspective(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 100.0));
+
+        assert!(frustum.intersects(&cube(Vec3::new(0.0, 0.0, -10.0), 1.0)));
+        assert!(!frustum.intersects(&cube(Vec3::new(0.0, 0.0, 5.0), 1.0)));
+        assert!(!frustum.intersects(&cube(Vec3::new(0.0, 0.0, -120.0), 1.0)));
+        assert!(!frustum.intersects(&cube(Vec3::new(-13.0, 0.0, -10.0), 1.0)));
+        assert!(!frustum.intersects(&cube(Vec3::new(0.0, 13.0, -10.0), 1.0)));
+
+        // Boxes straddling the left, far and near planes stay
+        assert!(frustum.intersects(&cube(Vec3::new(-10.0, 0.0, -10.0), 1.0)));
+        assert!(frustum.intersects(&cube(Vec3::new(0.0, 0.0, -100.0), 1.0)));
+        assert!(frustum.intersects(&cube(Vec3::new(0.0, 0.0, 0.0), 0.5)));
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+/// Reference images for the rendering tests.
+pub const GOLDEN_DIR: &str = "tests/golden";
+/// Set to rewrite the reference images from the current output instead of comparing.
+pub const UPDATE_ENV_VAR: &str = "UPDATE_GOLDEN";
+/// Frames that don't match are written here for inspection.
+const FAILURE_DIR: &str = "target/golden-failures";
+
+/// Per-channel difference up to which two pixels count as equal. Software rasterizers
+/// round a little differently between versions.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Share of pixels allowed to differ beyond `CHANNEL_TOLERANCE`.
+const MAX_DIFFERING: f32 = 0.005;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    pub differing_pixels: u32,
+    pub total_pixels: u32,
+    pub max_channel_diff: u8,
+}
+
+impl ImageDiff {
+    pub fn differing_fraction(&self) -> f32 {
+        self.differing_pixels as f32 / self.total_pixels.max(1) as f32
+    }
+}
+
+/// Compares two images of the same size, `None` if their sizes differ.
+pub fn compare_images(actual: &image::RgbaImage, expected: &image::RgbaImage, tolerance: u8) -> Option<ImageDiff> {
+    if actual.dimensions() != expected.dimensions() {
+        return None;
+    }
+    let mut diff = ImageDiff { differing_pixels: 0, total_pixels: actual.width() * actual.height(), max_channel_diff: 0 };
+    for (a, e) in actual.pixels().zip(expected.pixels()) {
+        let channel_diff = a.0.iter().zip(e.0.iter()).map(|(a, e)| a.abs_diff(*e)).max().unwrap_or(0);
+        diff.max_channel_diff = diff.max_channel_diff.max(channel_diff);
+        if channel_diff > tolerance {
+            diff.differing_pixels += 1;
+        }
+    }
+    Some(diff)
+}
+
+/// Checks `actual` against `GOLDEN_DIR/<name>.png`. With `UPDATE_GOLDEN=1` the current frame
+/// is written as the new reference instead; a missing reference is an error.
+pub fn check_golden(name: &str, actual: &image::RgbaImage) -> Result<(), String> {
+    let path = PathBuf::from(GOLDEN_DIR).join(format!("{}.png", name));
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).map_err(|e| e.to_string())?;
+        actual.save(&path).map_err(|e| e.to_string())?;
+        println!("Wrote golden image {}", path.display());
+        return Ok(());
+    }
+    if !path.exists() {
+        return Err(format!("{}: no reference image, run with {}=1 to create it", path.display(), UPDATE_ENV_VAR));
+    }
+
+    let expected = image::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?.to_rgba8();
+    let failure = match compare_images(actual, &expected, CHANNEL_TOLERANCE) {
+        Some(diff) if diff.differing_fraction() <= MAX_DIFFERING => return Ok(()),
+        Some(diff) => format!(
+            "{}: {} of {} pixels differ (max channel difference {})",
+            name, diff.differing_pixels, diff.total_pixels, diff.max_channel_diff
+        ),
+        None => format!("{}: size {:?} does not match the reference {:?}", name, actual.dimensions(), expected.dimensions()),
+    };
+
+    let failure_path = PathBuf::from(FAILURE_DIR).join(format!("{}.png", name));
+    if std::fs::create_dir_all(FAILURE_DIR).is_ok() && actual.save(&failure_path).is_ok() {
+        return Err(format!("{}, actual frame saved to {}", failure, failure_path.display()));
+    }
+    Err(failure)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use glam::{Vec2, Vec3};
+    use crate::engine::ui::*;
+    use crate::engine::{Engine, Settings};
+    use crate::engine::settings::ShadowQuality;
+
+    /// Headless engine for rendering tests. These need a GPU or software adapter (llvmpipe,
+    /// WARP), so they are `#[ignore]`d and run with `cargo test -- --ignored`; without an
+    /// adapter this panics instead of letting the test pass without rendering anything.
+    pub fn test_engine(width: u32, height: u32) -> Engine<'static> {
+        let mut settings = Settings::default();
+        settings.graphics.shadows = ShadowQuality::Off;
+        Engine::headless(width, height, settings).unwrap_or_else(|e| panic!("rendering test without an adapter: {}", e))
+    }
+
+    #[test]
+    fn compare_counts_pixels_beyond_tolerance() {
+        let expected = image::RgbaImage::from_pixel(4, 4, image::Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, image::Rgba([104, 100, 100, 255]));
+        actual.put_pixel(1, 0, image::Rgba([100, 160, 100, 255]));
+
+        let diff = compare_images(&actual, &expected, 8).unwrap();
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.max_channel_diff, 60);
+        assert!(compare_images(&actual, &image::RgbaImage::new(2, 2), 8).is_none());
+    }
+
+    #[test]
+    #[ignore = "needs a GPU or software adapter"]
+    fn ui_matches_golden() {
+        let mut engine = test_engine(320, 240);
+        engine.update_camera(Vec3::new(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+        let ui = UI::new()
+            .add_widget(Text::new("Golden 123").with_style(|s| {
+                s.position = Vec2::new(16.0, 16.0);
+                s.color = WHITE;
+            }))
+            .add_widget(
+                Container::new(LayoutType::Vertical { spacing: 8.0 })
+                    .with_style(|s| {
+                        s.anchor = Anchor::Center;
+                        s.size = Vec2::new(160.0, 100.0);
+                        s.color = DARK_GRAY;
+                        s.padding = Vec2::new(10.0, 10.0);
+                    })
+                    .add_button(Button::new("Button").with_style(|s| {
+                        s.size = Vec2::new(100.0, 30.0);
+                        s.color = RED;
+                    })),
+            );
+        engine.renderer.ui.set_ui(ui);
+        engine.render().expect("offscreen render");
+
+        let frame = engine.renderer.read_frame().expect("read back");
+        check_golden("ui_widgets", &frame).unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs a GPU or software adapter"]
+    fn later_widgets_cover_earlier_text() {
+        let mut engine = test_engine(160, 80);
+        let ui = UI::new()
+            .add_widget(Text::new("Hidden").with_scale(2.0).with_style(|s| {
+                s.position = Vec2::new(8.0, 8.0);
+                s.color = WHITE;
+            }))
+            .add_widget(Panel::new().with_style(|s| {
+                s.size = Vec2::new(160.0, 40.0);
+                s.color = RED;
+            }));
+        engine.renderer.ui.set_ui(ui);
+        engine.render().expect("offscreen render");
+
+        let frame = engine.renderer.read_frame().expect("read back");
+        for y in 0..40 {
+            for x in 0..160 {
+                let pixel = frame.get_pixel(x, y).0;
+                assert!(pixel[0] > 240 && pixel[1] < 16 && pixel[2] < 16, "text shows through at {}, {}: {:?}", x, y, pixel);
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use glam::Vec3;
+
+/// The sun (or moon) lighting the world, written to the light uniform every frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    /// Direction the light travels in, i.e. pointing away from the sun.
+    pub direction: Vec3,
+    pub color: Vec3,
+    /// Strength of the diffuse term.
+    pub intensity: f32,
+    /// Light every surface gets regardless of its orientation.
+    pub ambient: f32,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::new(0.0, -1.0, -0.5),
+            color: Vec3::ONE,
+            intensity: 1.0,
+            ambient: 0.3,
+        }
+    }
+}
@@ -2,4 +2,16 @@ pub mod renderer;
 pub mod texture_manager;
 pub mod ui;
 pub mod bitmap_font;
-pub mod image;
\ No newline at end of file
+pub mod image;
+pub mod frustum;
+pub mod sky;
+pub mod light;
+pub mod shadow;
+pub mod error;
+pub mod readback;
+#[cfg(test)]
+pub mod golden;
+pub mod screenshot;
+pub mod animation;
+pub mod pipelines;
+pub mod font;
@@ -0,0 +1,86 @@
+use std::sync::mpsc;
+
+use crate::engine::render::error::RendererError;
+
+/// Copies a 2D RGBA8/BGRA8 texture back to the CPU and returns it as RGBA. The texture needs
+/// `COPY_SRC` usage; the call blocks until the GPU is done with everything submitted before it.
+pub fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Result<image::RgbaImage, RendererError> {
+    let buffer = copy_to_buffer(device, queue, texture);
+    let slice = buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    receiver
+        .recv()
+        .map_err(|_| RendererError::ReadBack("map callback was dropped".to_string()))?
+        .map_err(|e| RendererError::ReadBack(e.to_string()))?;
+
+    let image = unpad_rows(&slice.get_mapped_range(), texture.width(), texture.height(), texture.format());
+    buffer.unmap();
+    Ok(image)
+}
+
+/// Copies `texture` into a new mappable buffer, rows padded to wgpu's copy alignment.
+pub fn copy_to_buffer(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> wgpu::Buffer {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+    let buffer = record_copy(device, &mut encoder, texture);
+    queue.submit(std::iter::once(encoder.finish()));
+    buffer
+}
+
+/// Like `copy_to_buffer`, but records the copy into `encoder` so it runs with the rest of a frame.
+pub fn record_copy(device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) -> wgpu::Buffer {
+    let padded_row = padded_bytes_per_row(texture.width());
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size: padded_row as u64 * texture.height() as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(texture.height()),
+            },
+        },
+        texture.size(),
+    );
+    buffer
+}
+
+/// Strips the row padding of a mapped readback buffer and swizzles BGRA to RGBA.
+pub fn unpad_rows(data: &[u8], width: u32, height: u32, format: wgpu::TextureFormat) -> image::RgbaImage {
+    let padded_row = padded_bytes_per_row(width) as usize;
+    let row = width as usize * 4;
+    let bgra = matches!(format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb);
+
+    let mut pixels = Vec::with_capacity(row * height as usize);
+    for y in 0..height as usize {
+        pixels.extend_from_slice(&data[y * padded_row..y * padded_row + row]);
+    }
+    if bgra {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    image::RgbaImage::from_raw(width, height, pixels).expect("readback buffer has the image size")
+}
+
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (width * 4).div_ceil(align) * align
+}
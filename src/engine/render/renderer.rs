@@ -1,9 +1,23 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use winit::window::Window;
 use glam::{Mat4, Vec2, Vec3};
 use wgpu::util::DeviceExt;
 
 use crate::engine::{UIRenderer, Vertex, render::texture_manager::TextureManager};
+use crate::engine::model::aabb::Aabb;
+use crate::engine::model::box_model::BoxModel;
+use crate::engine::render::frustum::Frustum;
+use crate::engine::render::sky::Sky;
+use crate::engine::render::light::DirectionalLight;
+use crate::engine::logger::Logger;
+use crate::engine::render::error::{RendererError, ShaderError, TextureError};
+use crate::engine::render::pipelines::{self, WorldPipelines, UI_SHADER, UI_TEXTURE_SHADER, UI_TEXT_SHADER, WORLD_SHADER};
+use crate::engine::render::readback;
+use crate::engine::render::screenshot::ScreenshotQueue;
+use crate::engine::render::shadow::{self, ShadowMap, SHADOW_DISTANCE};
+use crate::engine::settings::{CameraSettings, GraphicsSettings, ShadowQuality, TextureFilter};
+use crate::engine::model::instance::InstanceRaw;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -18,6 +32,31 @@ pub struct Mesh {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub index_count: u32,
+    /// World-space bounds, used for frustum culling.
+    pub bounds: Aabb,
+}
+
+/// GPU copy of one chunk's geometry, kept until the chunk is remeshed or unloaded.
+struct ChunkMesh {
+    opaque: Option<Mesh>,
+    translucent: Option<Mesh>,
+}
+
+/// Mesh counts of the last rendered frame, for the debug HUD.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    pub meshes_drawn: u32,
+    pub meshes_culled: u32,
+}
+
+/// Index of a model registered with `Renderer::register_model`.
+pub type ModelId = usize;
+
+/// GPU copy of a `BoxModel`: one mesh per part, each drawn once per instance.
+struct GpuModel {
+    parts: Vec<Mesh>,
+    /// Instance matrices queued this frame, per part.
+    instances: Vec<Vec<InstanceRaw>>,
 }
 
 #[repr(C)]
@@ -29,32 +68,74 @@ struct LightUniform {
     view_proj: [[f32; 4]; 4],
 }
 
+impl LightUniform {
+    /// `shadow_map_size` is `None` while shadows are off.
+    fn new(light: &DirectionalLight, view_proj: Mat4, shadow_map_size: Option<u32>) -> Self {
+        let (shadows, texel) = match shadow_map_size {
+            Some(size) => (1.0, 1.0 / size as f32),
+            None => (0.0, 0.0),
+        };
+        Self {
+            direction: light.direction.extend(0.0).to_array(),
+            color: light.color.extend(1.0).to_array(),
+            params: [light.intensity, light.ambient, shadows, texel],
+            view_proj: view_proj.to_cols_array_2d(),
+        }
+    }
+}
+
+/// Where frames end up: the window, or a texture that can be read back.
+enum RenderTarget<'window> {
+    Surface(wgpu::Surface<'window>),
+    Offscreen(wgpu::Texture),
+}
+
 pub struct Renderer<'window> {
     pub ui: UIRenderer,
     pub texture_manager: TextureManager,
-    surface: wgpu::Surface<'window>,
+    pub sky: Sky,
+    pub light: DirectionalLight,
+    target: RenderTarget<'window>,
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
     config: wgpu::SurfaceConfiguration,
     camera_position: Vec3,
     camera_target: Vec3,
     camera_up: Vec3,
+    /// Per-frame geometry (entities), dropped by `clear_meshes`.
     meshes: Vec<Mesh>,
-    render_pipeline: wgpu::RenderPipeline,
+    translucent_meshes: Vec<Mesh>,
+    chunk_meshes: HashMap<(i32, i32), ChunkMesh>,
+    models: Vec<GpuModel>,
+    stats: RenderStats,
+    pipelines: WorldPipelines,
+    pipeline_layout: wgpu::PipelineLayout,
+    shadow_pipeline_layout: wgpu::PipelineLayout,
+    /// WGSL errors of shaders that failed to reload, shown on screen until they compile.
+    shader_errors: Vec<ShaderError>,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
+    sky_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
     light_buffer: wgpu::Buffer,
     light_bind_group: wgpu::BindGroup,
+    shadow_quality: ShadowQuality,
+    shadow_map: ShadowMap,
+    shadow_sampler: wgpu::Sampler,
+    shadow_bind_group_layout: wgpu::BindGroupLayout,
     shadow_bind_group: wgpu::BindGroup,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
     texture_bind_group: wgpu::BindGroup,
+    texture_filter: TextureFilter,
+    anisotropy: u16,
     depth_texture: wgpu::Texture,
     depth_view: wgpu::TextureView,
+    screenshots: ScreenshotQueue,
 }
 
 impl<'window> Renderer<'window> {
-    pub fn new(window: Arc<Window>) -> Self {
-        pollster::block_on(Self::new_async(window))
+    pub fn new(window: Arc<Window>, graphics: &GraphicsSettings) -> Result<Self, RendererError> {
+        pollster::block_on(Self::new_async(window, graphics))
     }
 
     pub fn get_device(&self) -> &wgpu::Device {
@@ -69,39 +150,184 @@ impl<'window> Renderer<'window> {
         self.config.format
     }
     
-    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+    pub fn render(&mut self, camera: &CameraSettings, graphics: &GraphicsSettings) -> Result<(), wgpu::SurfaceError> {
+        if graphics.shadows != self.shadow_quality {
+            self.set_shadow_quality(graphics.shadows);
+        }
+        if graphics.texture_filter != self.texture_filter || graphics.anisotropy != self.anisotropy {
+            self.set_texture_filtering(graphics.texture_filter, graphics.anisotropy);
+        }
+        
         // Update camera matrix
         let view = Mat4::look_at_rh(self.camera_position, self.camera_target, self.camera_up);
-        let proj = Mat4::perspective_rh(45.0_f32.to_radians(), self.config.width as f32 / self.config.height as f32, 0.1, 100.0);
-        self.camera_uniform.view_proj = (proj * view).to_cols_array_2d();
+        let aspect = self.config.width as f32 / self.config.height.max(1) as f32;
+        let proj = Mat4::perspective_rh(camera.fov.to_radians(), aspect, camera.near, camera.far);
+        let view_proj = proj * view;
+        self.camera_uniform.view_proj = view_proj.to_cols_array_2d();
         self.camera_uniform.model = Mat4::IDENTITY.to_cols_array_2d();
         self.camera_uniform.view_position = self.camera_position.to_array();
         
         self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+        let sky_uniform = self.sky.to_uniform(view_proj.inverse(), camera.far);
+        self.queue.write_buffer(&self.sky_buffer, 0, bytemuck::cast_slice(&[sky_uniform]));
+        
+        let shadows_enabled = self.shadow_quality != ShadowQuality::Off;
+        let light_view_proj = if shadows_enabled {
+            shadow::fit_light_view_proj(
+                self.light.direction,
+                view,
+                camera.fov.to_radians(),
+                aspect,
+                camera.near,
+                SHADOW_DISTANCE.min(camera.far),
+                self.shadow_map.size,
+            )
+        } else {
+            Mat4::IDENTITY
+        };
+        let light_uniform = LightUniform::new(&self.light, light_view_proj, shadows_enabled.then_some(self.shadow_map.size));
+        self.queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&[light_uniform]));
         
         // Update UI buffers
         self.ui.update_buffers(&self.device);
         
-        let output = self.surface.get_current_texture()?;
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let frustum = Frustum::from_view_proj(view_proj);
+        
+        // All model instances of the frame go into one buffer, each part draws its own range
+        let mut instance_data: Vec<InstanceRaw> = Vec::new();
+        let mut model_draws = Vec::new();
+        for (model_id, model) in self.models.iter().enumerate() {
+            for (part, instances) in model.instances.iter().enumerate() {
+                if instances.is_empty() || model.parts[part].index_count == 0 {
+                    continue;
+                }
+                let start = instance_data.len() as u32;
+                instance_data.extend_from_slice(instances);
+                model_draws.push((model_id, part, start..instance_data.len() as u32));
+            }
+        }
+        let instance_buffer = if model_draws.is_empty() {
+            None
+        } else {
+            Some(self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Instance Buffer"),
+                contents: bytemuck::cast_slice(&instance_data),
+                usage: wgpu::BufferUsages::VERTEX,
+            }))
+        };
+        
+        // Surface frames are presented at the end, offscreen ones stay in the texture for `read_frame`
+        let (output, view) = match &self.target {
+            RenderTarget::Surface(surface) => {
+                let output = surface.get_current_texture()?;
+                let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+                (Some(output), view)
+            }
+            RenderTarget::Offscreen(texture) => (None, texture.create_view(&wgpu::TextureViewDescriptor::default())),
+        };
         
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
         
+        // Depth from the sun's point of view, sampled by the main pass
+        if shadows_enabled {
+            let light_frustum = Frustum::from_view_proj(light_view_proj);
+            let mut shadow_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: self.shadow_map.view(),
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            shadow_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            shadow_pass.set_bind_group(1, &self.light_bind_group, &[]);
+            
+            shadow_pass.set_pipeline(&self.pipelines.shadow);
+            for mesh in self.opaque_meshes() {
+                if !light_frustum.intersects(&mesh.bounds) {
+                    continue;
+                }
+                shadow_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                shadow_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                shadow_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            }
+            
+            if let Some(instance_buffer) = &instance_buffer {
+                shadow_pass.set_pipeline(&self.pipelines.shadow_entity);
+                shadow_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+                for (model_id, part, range) in &model_draws {
+                    let mesh = &self.models[*model_id].parts[*part];
+                    shadow_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                    shadow_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    shadow_pass.draw_indexed(0..mesh.index_count, 0, range.clone());
+                }
+            }
+        }
+        
+        let depth_view = self.depth_view.clone();
+        self.stats = self.draw_scene(&mut encoder, &view, &depth_view, &frustum, instance_buffer.as_ref(), &model_draws);
+        
+        if let Some(scale) = self.screenshots.take_request() {
+            let frame_texture = match (&self.target, &output) {
+                (RenderTarget::Offscreen(texture), _) => Some(texture.clone()),
+                (RenderTarget::Surface(_), Some(output)) => Some(output.texture.clone()),
+                _ => None,
+            };
+            let capture_texture = match frame_texture {
+                Some(texture) if scale == 1 && texture.usage().contains(wgpu::TextureUsages::COPY_SRC) => texture,
+                // Supersampled, or the surface can't be copied from: draw the frame once more offscreen
+                _ => self.draw_capture(&mut encoder, scale, &frustum, instance_buffer.as_ref(), &model_draws),
+            };
+            let buffer = readback::record_copy(&self.device, &mut encoder, &capture_texture);
+            self.queue.submit(std::iter::once(encoder.finish()));
+            self.screenshots.push(buffer, capture_texture.width(), capture_texture.height(), capture_texture.format());
+        } else {
+            self.queue.submit(std::iter::once(encoder.finish()));
+        }
+        if let Some(output) = output {
+            output.present();
+        }
+        self.screenshots.poll(&self.device);
+        
+        Ok(())
+    }
+
+    /// Draws the sky, the world and the UI into `view`; shared by the frame itself and by
+    /// supersampled screenshots, which use the same uniforms at a larger size.
+    fn draw_scene(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        frustum: &Frustum,
+        instance_buffer: Option<&wgpu::Buffer>,
+        model_draws: &[(ModelId, usize, std::ops::Range<u32>)],
+    ) -> RenderStats {
+        let mut stats = RenderStats::default();
+        let sky_color = self.sky.clear_color();
+        let clear_color = wgpu::Color { r: sky_color.x as f64, g: sky_color.y as f64, b: sky_color.z as f64, a: 1.0 };
+        
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: wgpu::LoadOp::Clear(clear_color),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_view,
+                    view: depth_view,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(1.0),
                         store: wgpu::StoreOp::Store,
@@ -112,20 +338,63 @@ impl<'window> Renderer<'window> {
                 occlusion_query_set: None,
             });
             
-            // Render 3D world
-            render_pass.set_pipeline(&self.render_pipeline);
             render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
             render_pass.set_bind_group(1, &self.light_bind_group, &[]);
             render_pass.set_bind_group(2, &self.shadow_bind_group, &[]);
             render_pass.set_bind_group(3, &self.texture_bind_group, &[]);
             
-            for mesh in &self.meshes {
+            // Sky first, everything else draws over it
+            render_pass.set_pipeline(&self.pipelines.sky);
+            render_pass.draw(0..3, 0..1);
+            
+            // Render 3D world
+            render_pass.set_pipeline(&self.pipelines.render);
+            
+            for mesh in self.opaque_meshes() {
+                if !frustum.intersects(&mesh.bounds) {
+                    stats.meshes_culled += 1;
+                    continue;
+                }
+                stats.meshes_drawn += 1;
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            }
+            
+            if let Some(instance_buffer) = &instance_buffer {
+                render_pass.set_pipeline(&self.pipelines.entity);
+                render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+                for (model_id, part, range) in model_draws {
+                    let mesh = &self.models[*model_id].parts[*part];
+                    render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                    render_pass.draw_indexed(0..mesh.index_count, 0, range.clone());
+                }
+            }
+            
+            // Translucent geometry after opaques, farthest mesh first
+            let camera_position = self.camera_position;
+            let mut translucent: Vec<&Mesh> = self.chunk_meshes.values()
+                .filter_map(|chunk| chunk.translucent.as_ref())
+                .chain(&self.translucent_meshes)
+                .collect();
+            translucent.sort_by(|a, b| {
+                let a = a.bounds.center().distance_squared(camera_position);
+                let b = b.bounds.center().distance_squared(camera_position);
+                b.total_cmp(&a)
+            });
+            render_pass.set_pipeline(&self.pipelines.translucent);
+            for mesh in translucent {
+                if !frustum.intersects(&mesh.bounds) {
+                    stats.meshes_culled += 1;
+                    continue;
+                }
+                stats.meshes_drawn += 1;
                 render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
                 render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
                 render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
             }
             
-            // Render UI on top (separate pass without depth)
         }
         
         // Render UI in separate pass without depth testing
@@ -133,7 +402,7 @@ impl<'window> Renderer<'window> {
             let mut ui_render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("UI Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Load,
@@ -147,21 +416,238 @@ impl<'window> Renderer<'window> {
             self.ui.render(&mut ui_render_pass, &self.device);
         }
         
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        stats
+    }
+
+    /// Draws the frame again into a new texture `scale` times the window size, for a supersampled
+    /// screenshot. The UI is laid out for the window and stretched along with everything else.
+    fn draw_capture(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        scale: u32,
+        frustum: &Frustum,
+        instance_buffer: Option<&wgpu::Buffer>,
+        model_draws: &[(ModelId, usize, std::ops::Range<u32>)],
+    ) -> wgpu::Texture {
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        let scale = scale.min(max_size / self.config.width.max(self.config.height)).max(1);
+        let size = wgpu::Extent3d {
+            width: self.config.width * scale,
+            height: self.config.height * scale,
+            depth_or_array_layers: 1,
+        };
         
-        Ok(())
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Screenshot Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let depth_texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Screenshot Depth Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.draw_scene(encoder, &view, &depth_view, frustum, instance_buffer, model_draws);
+        texture
+    }
+
+    /// Saves the next frame as a PNG in `SCREENSHOT_DIR`, at `scale` times the window resolution.
+    /// The file is written a few frames later, without waiting on the GPU.
+    pub fn request_screenshot(&mut self, scale: u32) {
+        self.screenshots.request(scale);
+    }
+
+    /// Rebuilds the pipelines of the shader `file` (see `pipelines::SHADER_DIR`) from `source`.
+    /// A shader that doesn't compile keeps its old pipelines and its error stays on screen until
+    /// it compiles again.
+    pub fn reload_shader(&mut self, file: &str, source: &str) -> Result<(), ShaderError> {
+        let result = match file {
+            WORLD_SHADER => WorldPipelines::from_source(&self.device, source, &self.pipeline_layout, &self.shadow_pipeline_layout, self.config.format)
+                .map(|pipelines| self.pipelines = pipelines),
+            UI_SHADER | UI_TEXTURE_SHADER | UI_TEXT_SHADER => self.ui.reload_shader(&self.device, file, source),
+            _ => return Ok(()),
+        };
+        
+        self.shader_errors.retain(|error| error.file != file);
+        if let Err(error) = &result {
+            self.shader_errors.push(error.clone());
+        }
+        let overlay = self
+            .shader_errors
+            .iter()
+            .flat_map(|error| std::iter::once(format!("Shader error in {}:", error.file)).chain(error.message.lines().map(str::to_string)))
+            .collect();
+        self.ui.set_error_overlay(overlay);
+        result
     }
 
     pub fn clear_meshes(&mut self) {
         self.meshes.clear();
+        self.translucent_meshes.clear();
+        for model in &mut self.models {
+            for instances in &mut model.instances {
+                instances.clear();
+            }
+        }
     }
 
-    pub fn add_texture(&mut self, path: &str, name: Option<&str>) -> Option<u32> {
+    pub fn add_texture(&mut self, path: &str, name: Option<&str>) -> Result<u32, TextureError> {
         self.texture_manager.add_texture(path, name)
     }
 
-    pub fn add_mesh(&mut self, vertices: &[u8], indices: &[u16]) {
+    /// Replaces the geometry of the chunk at `chunk_pos`. Unlike `add_mesh` it stays
+    /// across frames until replaced again or dropped with `remove_chunk_mesh`.
+    pub fn set_chunk_mesh(
+        &mut self,
+        chunk_pos: (i32, i32),
+        vertices: &[u8],
+        indices: &[u16],
+        translucent_vertices: &[u8],
+        translucent_indices: &[u16],
+        bounds: Aabb,
+    ) {
+        let opaque = (!indices.is_empty()).then(|| self.create_mesh(vertices, indices, bounds));
+        let translucent = (!translucent_indices.is_empty())
+            .then(|| self.create_mesh(translucent_vertices, translucent_indices, bounds));
+        self.chunk_meshes.insert(chunk_pos, ChunkMesh { opaque, translucent });
+    }
+
+    pub fn remove_chunk_mesh(&mut self, chunk_pos: (i32, i32)) {
+        self.chunk_meshes.remove(&chunk_pos);
+    }
+
+    /// Chunk geometry followed by this frame's opaque meshes.
+    fn opaque_meshes(&self) -> impl Iterator<Item = &Mesh> {
+        self.chunk_meshes.values().filter_map(|chunk| chunk.opaque.as_ref()).chain(&self.meshes)
+    }
+
+    /// Adds opaque geometry for this frame; `bounds` is its world-space box, meshes outside the view are skipped.
+    pub fn add_mesh(&mut self, vertices: &[u8], indices: &[u16], bounds: Aabb) {
+        let mesh = self.create_mesh(vertices, indices, bounds);
+        self.meshes.push(mesh);
+    }
+
+    /// Adds alpha-blended geometry for this frame. The center of `bounds` is used to sort meshes back to front.
+    pub fn add_translucent_mesh(&mut self, vertices: &[u8], indices: &[u16], bounds: Aabb) {
+        let mesh = self.create_mesh(vertices, indices, bounds);
+        self.translucent_meshes.push(mesh);
+    }
+
+    /// The last rendered frame of a headless renderer (see `new_headless`) as RGBA pixels.
+    pub fn read_frame(&self) -> Result<image::RgbaImage, RendererError> {
+        match &self.target {
+            RenderTarget::Offscreen(texture) => readback::read_texture(&self.device, &self.queue, texture),
+            RenderTarget::Surface(_) => Err(RendererError::NotOffscreen),
+        }
+    }
+
+    pub fn get_stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    /// Rebuilds the atlas sampler for the given filter mode and anisotropy.
+    pub fn set_texture_filtering(&mut self, filter: TextureFilter, anisotropy: u16) {
+        self.texture_filter = filter;
+        self.anisotropy = anisotropy;
+        self.texture_bind_group = Self::create_texture_bind_group(
+            &self.device,
+            &self.texture_bind_group_layout,
+            &self.texture_manager,
+            filter,
+            anisotropy,
+        );
+    }
+
+    fn create_texture_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        texture_manager: &TextureManager,
+        filter: TextureFilter,
+        anisotropy: u16,
+    ) -> wgpu::BindGroup {
+        let texture_view = texture_manager.get_texture_view();
+        let texture_sampler = texture_manager.get_sampler(filter, anisotropy);
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&texture_sampler),
+                },
+            ],
+            label: Some("texture_bind_group"),
+        })
+    }
+
+    /// Recreates the shadow map at the resolution of `quality`.
+    pub fn set_shadow_quality(&mut self, quality: ShadowQuality) {
+        self.shadow_quality = quality;
+        self.shadow_map = ShadowMap::new(&self.device, quality.map_size());
+        self.shadow_bind_group = Self::create_shadow_bind_group(&self.device, &self.shadow_bind_group_layout, &self.shadow_map, &self.shadow_sampler);
+    }
+
+    fn create_shadow_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        shadow_map: &ShadowMap,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(shadow_map.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("shadow_bind_group"),
+        })
+    }
+
+    /// Uploads the part meshes of a model; textures it uses must already be loaded.
+    pub fn register_model(&mut self, model: &BoxModel) -> ModelId {
+        let mut parts = Vec::with_capacity(model.parts.len());
+        for part in 0..model.parts.len() {
+            let (vertices, indices) = model.build_part_mesh(part, &self.texture_manager);
+            // Parts are placed per instance, so their bounds are never used for culling
+            parts.push(self.create_mesh(bytemuck::cast_slice(&vertices), &indices, Aabb::new(Vec3::ZERO, Vec3::ZERO)));
+        }
+        let instances = vec![Vec::new(); parts.len()];
+        self.models.push(GpuModel { parts, instances });
+        self.models.len() - 1
+    }
+
+    /// Queues one instance of a model for this frame. `pose` holds the model-space
+    /// matrix of every part (see `BoxModel::pose`), `transform` places the model in the world.
+    pub fn draw_model(&mut self, model_id: ModelId, transform: Mat4, pose: &[Mat4]) {
+        let Some(model) = self.models.get_mut(model_id) else { return };
+        for (instances, part) in model.instances.iter_mut().zip(pose) {
+            instances.push(InstanceRaw::new(transform * *part));
+        }
+    }
+
+    fn create_mesh(&self, vertices: &[u8], indices: &[u16], bounds: Aabb) -> Mesh {
         let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
             contents: vertices,
@@ -174,53 +660,149 @@ impl<'window> Renderer<'window> {
             usage: wgpu::BufferUsages::INDEX,
         });
         
-        self.meshes.push(Mesh {
+        Mesh {
             vertex_buffer,
             index_buffer,
             index_count: indices.len() as u32,
-        });
+            bounds,
+        }
     }
 
-    async fn new_async(window: Arc<Window>) -> Self {
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::DX12,
-            flags: wgpu::InstanceFlags::all(),
-            ..Default::default()
-        });
+    async fn new_async(window: Arc<Window>, graphics: &GraphicsSettings) -> Result<Self, RendererError> {
+        let instance = Self::create_instance(graphics);
+        let surface = instance.create_surface(Arc::clone(&window))?;
+        let adapter = Self::request_adapter(&instance, Some(&surface), graphics, false).await?;
+        let (device, queue) = Self::request_device(&adapter).await?;
+
+        let size = window.inner_size();
+        let mut config = surface
+            .get_default_config(&adapter, size.width.max(1), size.height.max(1))
+            .ok_or_else(|| RendererError::UnsupportedSurface { adapter: adapter.get_info().name })?;
+        // Lets screenshots copy the frame directly instead of drawing it a second time
+        if surface.get_capabilities(&adapter).usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            config.usage |= wgpu::TextureUsages::COPY_SRC;
+        }
+        surface.configure(&device, &config);
         
-        let surface = instance.create_surface(Arc::clone(&window)).unwrap();
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
-            })
-            .await
-            .expect("Failed to find an appropriate adapter");
+        Ok(Self::from_device(device, queue, RenderTarget::Surface(surface), config, graphics))
+    }
+
+    /// A renderer without a window that draws into an offscreen texture, preferring a software
+    /// adapter so it also runs on machines without a GPU. Frames are fetched with `read_frame`.
+    pub fn new_headless(width: u32, height: u32, graphics: &GraphicsSettings) -> Result<Renderer<'static>, RendererError> {
+        pollster::block_on(async {
+            let instance = Self::create_instance(graphics);
+            let adapter = Self::request_adapter(&instance, None, graphics, true).await?;
+            let (device, queue) = Self::request_device(&adapter).await?;
             
-        let (device, queue) = adapter
+            let config = wgpu::SurfaceConfiguration {
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                width: width.max(1),
+                height: height.max(1),
+                present_mode: wgpu::PresentMode::Fifo,
+                desired_maximum_frame_latency: 2,
+                alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+                view_formats: vec![],
+            };
+            let texture = Self::create_offscreen_texture(&device, &config);
+            Ok(Renderer::from_device(device, queue, RenderTarget::Offscreen(texture), config, graphics))
+        })
+    }
+
+    fn create_instance(graphics: &GraphicsSettings) -> wgpu::Instance {
+        wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: graphics.backend.to_wgpu(),
+            flags: wgpu::InstanceFlags::all(),
+            ..Default::default()
+        })
+    }
+
+    /// Finds an adapter on the configured backends. With `prefer_software` the fallback
+    /// adapter is tried first, otherwise only when no hardware one is found (and allowed).
+    async fn request_adapter(
+        instance: &wgpu::Instance,
+        surface: Option<&wgpu::Surface<'_>>,
+        graphics: &GraphicsSettings,
+        prefer_software: bool,
+    ) -> Result<wgpu::Adapter, RendererError> {
+        let attempts: &[bool] = if prefer_software {
+            &[true, false]
+        } else if graphics.allow_software_fallback {
+            &[false, true]
+        } else {
+            &[false]
+        };
+        for (i, &force_fallback_adapter) in attempts.iter().enumerate() {
+            if i > 0 {
+                Logger::warn("No matching graphics adapter found, trying another kind");
+            }
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    force_fallback_adapter,
+                    compatible_surface: surface,
+                })
+                .await;
+            if let Some(adapter) = adapter {
+                let info = adapter.get_info();
+                Logger::info(&format!("Using adapter '{}' ({:?}, {:?})", info.name, info.backend, info.device_type));
+                return Ok(adapter);
+            }
+        }
+        Err(RendererError::NoAdapter { backends: graphics.backend.to_wgpu() })
+    }
+
+    async fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue), RendererError> {
+        // GL and older GPUs can't meet the default limits, ask only for what they have
+        let required_limits = if wgpu::Limits::default().check_limits(&adapter.limits()) {
+            wgpu::Limits::default()
+        } else {
+            wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits())
+        };
+        let device = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
                     required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
+                    required_limits,
                     memory_hints: wgpu::MemoryHints::Performance,
                 },
                 None,
             )
-            .await
-            .expect("Failed to create device");
+            .await?;
+        Ok(device)
+    }
+
+    fn create_offscreen_texture(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d { width: config.width, height: config.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        })
+    }
+
+    /// Creates every pipeline and GPU resource for drawing into `target`.
+    fn from_device(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        target: RenderTarget<'window>,
+        config: wgpu::SurfaceConfiguration,
+        graphics: &GraphicsSettings,
+    ) -> Self {
         let arc_device = Arc::new(device);
         let arc_queue = Arc::new(queue);
-
-        let size = window.inner_size();
-        let config = surface.get_default_config(&adapter, size.width, size.height).unwrap();
-        surface.configure(&arc_device, &config);
+        let size = winit::dpi::PhysicalSize::new(config.width, config.height);
         
         // Create simple render pipeline
         let shader = arc_device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/shader.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(pipelines::embedded_source(WORLD_SHADER).unwrap().into()),
         });
         
         let camera_uniform = CameraUniform {
@@ -236,35 +818,56 @@ impl<'window> Renderer<'window> {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
         
+        let sky = Sky::default();
+        let sky_buffer = arc_device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Sky Buffer"),
+            contents: bytemuck::cast_slice(&[sky.to_uniform(Mat4::IDENTITY, 100.0)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        
         let camera_bind_group_layout = arc_device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
             label: Some("camera_bind_group_layout"),
         });
         
         let camera_bind_group = arc_device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: sky_buffer.as_entire_binding(),
+                },
+            ],
             label: Some("camera_bind_group"),
         });
         
-        let light_uniform = LightUniform {
-            direction: [0.0, -1.0, -0.5, 0.0],
-            color: [1.0, 1.0, 1.0, 1.0],
-            params: [1.0, 0.3, 0.0, 0.0],
-            view_proj: Mat4::IDENTITY.to_cols_array_2d(),
-        };
+        let light = DirectionalLight::default();
+        let light_uniform = LightUniform::new(&light, Mat4::IDENTITY, None);
         
         let light_buffer = arc_device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Light Buffer"),
@@ -295,19 +898,9 @@ impl<'window> Renderer<'window> {
             label: Some("light_bind_group"),
         });
         
-        // Create dummy shadow texture
-        let shadow_texture = arc_device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Shadow Texture"),
-            size: wgpu::Extent3d { width: 1024, height: 1024, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-            view_formats: &[],
-        });
-        
-        let shadow_view = shadow_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Shadows start off, the engine switches them to the configured quality on the first frame
+        let shadow_quality = ShadowQuality::Off;
+        let shadow_map = ShadowMap::new(&arc_device, shadow_quality.map_size());
         let shadow_sampler = arc_device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("Shadow Sampler"),
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -342,25 +935,12 @@ impl<'window> Renderer<'window> {
             label: Some("shadow_bind_group_layout"),
         });
         
-        let shadow_bind_group = arc_device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &shadow_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&shadow_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&shadow_sampler),
-                },
-            ],
-            label: Some("shadow_bind_group"),
-        });
+        let shadow_bind_group = Self::create_shadow_bind_group(&arc_device, &shadow_bind_group_layout, &shadow_map, &shadow_sampler);
         
         // Use TextureManager to load textures
-        let texture_manager = TextureManager::new(arc_device.clone(), arc_queue.clone(), 512);
-        let texture_view = texture_manager.get_texture_view();
-        let texture_sampler = texture_manager.get_sampler();
+        let texture_manager = TextureManager::new(arc_device.clone(), arc_queue.clone(), graphics.texture_layout);
+        let texture_filter = TextureFilter::PixelArt;
+        let anisotropy = 1;
         
         let texture_bind_group_layout = arc_device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
@@ -384,20 +964,7 @@ impl<'window> Renderer<'window> {
             label: Some("texture_bind_group_layout"),
         });
         
-        let texture_bind_group = arc_device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &texture_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&texture_sampler),
-                },
-            ],
-            label: Some("texture_bind_group"),
-        });
+        let texture_bind_group = Self::create_texture_bind_group(&arc_device, &texture_bind_group_layout, &texture_manager, texture_filter, anisotropy);
         
         let render_pipeline_layout = arc_device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
@@ -419,54 +986,21 @@ impl<'window> Renderer<'window> {
         
         let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
         
-        let render_pipeline = arc_device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
+        // The shadow passes only read the camera and light uniforms
+        let shadow_pipeline_layout = arc_device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &light_bind_group_layout],
+            push_constant_ranges: &[],
         });
-        let ui_renderer = UIRenderer::new(&arc_device, config.format);
+        let pipelines = WorldPipelines::new(&arc_device, &shader, &render_pipeline_layout, &shadow_pipeline_layout, config.format);
+        let mut ui_renderer = UIRenderer::new(&arc_device, arc_queue.clone(), config.format);
+        ui_renderer.resize(size);
         Self {
             ui: ui_renderer,
             texture_manager,
-            surface,
+            sky,
+            light,
+            target,
             device: arc_device,
             queue: arc_queue,
             config,
@@ -474,16 +1008,32 @@ impl<'window> Renderer<'window> {
             camera_target: Vec3::new(0.0, 0.0, -1.0),
             camera_up: Vec3::Y,
             meshes: Vec::new(),
-            render_pipeline,
+            translucent_meshes: Vec::new(),
+            chunk_meshes: HashMap::new(),
+            models: Vec::new(),
+            stats: RenderStats::default(),
+            pipelines,
+            pipeline_layout: render_pipeline_layout,
+            shadow_pipeline_layout,
+            shader_errors: Vec::new(),
             camera_uniform,
             camera_buffer,
+            sky_buffer,
             camera_bind_group,
             light_buffer,
             light_bind_group,
+            shadow_quality,
+            shadow_map,
+            shadow_sampler,
+            shadow_bind_group_layout,
             shadow_bind_group,
+            texture_bind_group_layout,
             texture_bind_group,
+            texture_filter,
+            anisotropy,
             depth_texture,
             depth_view,
+            screenshots: ScreenshotQueue::default(),
         }
     }
 
@@ -515,7 +1065,10 @@ impl<'window> Renderer<'window> {
         if new_size.width > 0 && new_size.height > 0 {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            match &mut self.target {
+                RenderTarget::Surface(surface) => surface.configure(&self.device, &self.config),
+                RenderTarget::Offscreen(texture) => *texture = Self::create_offscreen_texture(&self.device, &self.config),
+            }
             self.ui.resize(new_size);
             // Recreate depth texture
             self.depth_texture = self.device.create_texture(&wgpu::TextureDescriptor {
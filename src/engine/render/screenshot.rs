@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::engine::logger::Logger;
+use crate::engine::render::readback;
+
+/// Folder screenshots are written to, relative to the working directory.
+pub const SCREENSHOT_DIR: &str = "screenshots";
+/// Largest supersampling factor; the capture is also limited by the GPU's texture size.
+pub const MAX_SCREENSHOT_SCALE: u32 = 4;
+
+type MapResult = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+/// A frame copied into a staging buffer, waiting for the GPU to finish and the map to complete.
+struct PendingCapture {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    mapped: MapResult,
+}
+
+/// Screenshot requests and captures in flight. The renderer copies the frame into a staging
+/// buffer as part of the frame's own submission; the buffer is mapped asynchronously and the
+/// PNG is encoded on a separate thread, so the frame loop never waits on either.
+#[derive(Default)]
+pub struct ScreenshotQueue {
+    requested: Option<u32>,
+    pending: Vec<PendingCapture>,
+}
+
+impl ScreenshotQueue {
+    /// Captures the next frame at `scale` times the window resolution.
+    pub fn request(&mut self, scale: u32) {
+        self.requested = Some(scale.clamp(1, MAX_SCREENSHOT_SCALE));
+    }
+
+    pub(crate) fn take_request(&mut self) -> Option<u32> {
+        self.requested.take()
+    }
+
+    /// Starts mapping a staging buffer filled by a copy that has already been submitted.
+    pub(crate) fn push(&mut self, buffer: wgpu::Buffer, width: u32, height: u32, format: wgpu::TextureFormat) {
+        let mapped: MapResult = Arc::new(Mutex::new(None));
+        let callback_result = Arc::clone(&mapped);
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            *callback_result.lock().unwrap() = Some(result);
+        });
+        self.pending.push(PendingCapture { buffer, width, height, format, mapped });
+    }
+
+    /// Writes out every capture whose buffer has been mapped. Never blocks on the GPU.
+    pub(crate) fn poll(&mut self, device: &wgpu::Device) {
+        if self.pending.is_empty() {
+            return;
+        }
+        device.poll(wgpu::Maintain::Poll);
+
+        self.pending.retain(|capture| {
+            let Some(result) = capture.mapped.lock().unwrap().take() else {
+                return true;
+            };
+            match result {
+                Ok(()) => {
+                    let image = readback::unpad_rows(
+                        &capture.buffer.slice(..).get_mapped_range(),
+                        capture.width,
+                        capture.height,
+                        capture.format,
+                    );
+                    capture.buffer.unmap();
+                    // Encoding a large PNG takes longer than a frame
+                    std::thread::spawn(move || save_screenshot(&image, Path::new(SCREENSHOT_DIR)));
+                }
+                Err(e) => Logger::error(&format!("Failed to read back screenshot: {}", e)),
+            }
+            false
+        });
+    }
+}
+
+fn save_screenshot(image: &image::RgbaImage, dir: &Path) {
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        Logger::error(&format!("Failed to create {}: {}", dir.display(), e));
+        return;
+    }
+    let path = screenshot_path(dir, SystemTime::now());
+    match image.save(&path) {
+        Ok(()) => Logger::info(&format!("Saved screenshot {} ({}x{})", path.display(), image.width(), image.height())),
+        Err(e) => Logger::error(&format!("Failed to save screenshot {}: {}", path.display(), e)),
+    }
+}
+
+/// `dir/YYYY-MM-DD_HH.MM.SS.png` in UTC, with a counter appended when several are taken in one second.
+fn screenshot_path(dir: &Path, time: SystemTime) -> PathBuf {
+    let stamp = format_timestamp(time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+    let mut path = dir.join(format!("{}.png", stamp));
+    let mut counter = 2;
+    while path.exists() {
+        path = dir.join(format!("{}_{}.png", stamp, counter));
+        counter += 1;
+    }
+    path
+}
+
+fn format_timestamp(unix_seconds: u64) -> String {
+    let days = (unix_seconds / 86400) as i64;
+    let seconds = unix_seconds % 86400;
+
+    // Дни с эпохи в григорианскую дату (алгоритм Howard Hinnant, civil_from_days)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}.{:02}.{:02}",
+        year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timestamps_are_utc_calendar_dates() {
+        assert_eq!(format_timestamp(0), "1970-01-01_00.00.00");
+        assert_eq!(format_timestamp(951_782_400 + 3661), "2000-02-29_01.01.01");
+        assert_eq!(format_timestamp(1_735_689_599), "2024-12-31_23.59.59");
+    }
+}
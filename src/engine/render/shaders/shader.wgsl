@@ -7,11 +7,21 @@ struct CameraUniform {
 struct LightUniform {
     direction: vec4<f32>,
     color: vec4<f32>,
-    params: vec4<f32>,
+    params: vec4<f32>, // x: diffuse intensity, y: ambient, z: 1 when shadows are on, w: shadow map texel size
     view_proj: mat4x4<f32>,
 }
 
+struct SkyUniform {
+    inv_view_proj: mat4x4<f32>,
+    zenith_color: vec4<f32>,
+    horizon_color: vec4<f32>,
+    sun_direction: vec4<f32>,
+    fog_color: vec4<f32>,
+    fog_params: vec4<f32>, // x: fog start, y: fog end, z: 1 when underwater
+}
+
 @group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(0) @binding(1) var<uniform> sky: SkyUniform;
 @group(1) @binding(0) var<uniform> light: LightUniform;
 @group(2) @binding(0) var shadow_texture: texture_depth_2d;
 @group(2) @binding(1) var shadow_sampler: sampler_comparison;
@@ -37,12 +47,17 @@ struct VertexOutput {
     @location(5) occulusion: f32,
 }
 
-@vertex
-fn vs_main(model: VertexInput) -> VertexOutput {
+struct InstanceInput {
+    @location(5) model_0: vec4<f32>,
+    @location(6) model_1: vec4<f32>,
+    @location(7) model_2: vec4<f32>,
+    @location(8) model_3: vec4<f32>,
+}
+
+fn transform_vertex(model: VertexInput, model_matrix: mat4x4<f32>) -> VertexOutput {
     var out: VertexOutput;
     
     // Transform position
-    let model_matrix = camera.model;
     let world_position = (model_matrix * vec4<f32>(model.position, 1.0)).xyz;
     out.clip_position = camera.view_proj * vec4<f32>(world_position, 1.0);
     out.world_position = world_position;
@@ -62,33 +77,74 @@ fn vs_main(model: VertexInput) -> VertexOutput {
     return out;
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let view_dir = normalize(camera.view_position.xyz - in.world_position);
-    let normal = normalize(in.world_normal);
-    let light_dir = normalize(light.direction.xyz);
-        let base_color = textureSample(
-        texture_array, 
-        texture_sampler, 
-        in.uv, 
-        in.tex_index
-    ).rgb;
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    return transform_vertex(model, camera.model);
+}
 
-    // Calculate shadow factor
+// Entity model parts, one model matrix per instance
+@vertex
+fn vs_entity(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(
+        instance.model_0,
+        instance.model_1,
+        instance.model_2,
+        instance.model_3,
+    );
+    return transform_vertex(model, model_matrix);
+}
+
+// Depth-only passes from the sun's point of view
+@vertex
+fn vs_shadow(model: VertexInput) -> @builtin(position) vec4<f32> {
+    return light.view_proj * camera.model * vec4<f32>(model.position, 1.0);
+}
+
+@vertex
+fn vs_shadow_entity(model: VertexInput, instance: InstanceInput) -> @builtin(position) vec4<f32> {
+    let model_matrix = mat4x4<f32>(
+        instance.model_0,
+        instance.model_1,
+        instance.model_2,
+        instance.model_3,
+    );
+    return light.view_proj * model_matrix * vec4<f32>(model.position, 1.0);
+}
+
+// Texels below this alpha are discarded in the opaque pass (leaves, grates)
+const ALPHA_CUTOFF: f32 = 0.5;
+
+// 1 where the sun reaches the fragment, 0 in full shadow
+fn shadow_factor(coords: vec3<f32>) -> f32 {
+    if (light.params.z < 0.5) {
+        return 1.0;
+    }
+    // Outside the shadow map (beyond the shadow distance) everything is lit
+    if (any(coords.xy < vec2<f32>(0.0)) || any(coords.xy > vec2<f32>(1.0)) || coords.z > 1.0) {
+        return 1.0;
+    }
     var shadow: f32 = 0.0;
-    let size = f32(1.0) / 2048.0; // shadow map size
+    let size = light.params.w;
     for (var y: i32 = -1; y <= 1; y++) {
         for (var x: i32 = -1; x <= 1; x++) {
             let offset = vec2<f32>(f32(x) * size, f32(y) * size);
-            shadow += textureSampleCompare(
+            shadow += textureSampleCompareLevel(
                 shadow_texture,
                 shadow_sampler,
-                in.shadow_coords.xy + offset,
-                in.shadow_coords.z - 0.001 // bias to reduce shadow acne
+                coords.xy + offset,
+                coords.z - 0.001 // bias to reduce shadow acne
             );
         }
     }
-    shadow /= 9.0; // Average the samples
+    return shadow / 9.0; // Average the samples
+}
+
+fn shade(in: VertexOutput, base_color: vec3<f32>) -> vec3<f32> {
+    let view_dir = normalize(camera.view_position.xyz - in.world_position);
+    let normal = normalize(in.world_normal);
+    let light_dir = normalize(light.direction.xyz);
+
+    let shadow = shadow_factor(in.shadow_coords);
     
     // Calculate lighting
     let ambient_strength = light.params.y;
@@ -97,8 +153,6 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let diff = max(dot(normal, -light_dir), 0.0);
     let diffuse = light.color.xyz * diff * diffuse_intensity; // intensity is params.x
 
-    // Base color (you can modify this or add texture sampling)
-    // let base_color = vec3<f32>(0.8, 0.8, 0.8);
     //rim lighting
     var rim = 1.0 - max(dot(normal, view_dir), 0.0);
     rim = smoothstep(0.0, 1.0, rim); // Сглаживание эффекта
@@ -106,6 +160,89 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
 
     // Combine lighting with shadows
     let lighting = ambient + diffuse * shadow;
-    let final_color = base_color * lighting * in.occulusion + rim_light;
-    return vec4<f32>(final_color, 1.0);
+    return base_color * lighting * in.occulusion + rim_light;
+}
+
+fn is_underwater() -> bool {
+    return sky.fog_params.z > 0.5;
+}
+
+// Sky colour in a view direction, without the sun and moon
+fn sky_gradient(dir: vec3<f32>) -> vec3<f32> {
+    if (is_underwater()) {
+        return sky.fog_color.rgb;
+    }
+    let height = clamp(dir.y, 0.0, 1.0);
+    return mix(sky.horizon_color.rgb, sky.zenith_color.rgb, sqrt(height));
+}
+
+// Fades distant geometry into the sky behind it
+fn apply_fog(color: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+    let to_fragment = world_position - camera.view_position;
+    let fog = smoothstep(sky.fog_params.x, sky.fog_params.y, length(to_fragment));
+    return mix(color, sky_gradient(normalize(to_fragment)), fog);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(
+        texture_array, 
+        texture_sampler, 
+        in.uv, 
+        in.tex_index
+    );
+    if (texel.a < ALPHA_CUTOFF) {
+        discard;
+    }
+    return vec4<f32>(apply_fog(shade(in, texel.rgb), in.world_position), 1.0);
+}
+
+@fragment
+fn fs_translucent(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(
+        texture_array, 
+        texture_sampler, 
+        in.uv, 
+        in.tex_index
+    );
+    return vec4<f32>(apply_fog(shade(in, texel.rgb), in.world_position), texel.a);
+}
+
+struct SkyOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+// Full-screen triangle behind everything, no vertex buffer
+@vertex
+fn vs_sky(@builtin(vertex_index) index: u32) -> SkyOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    let ndc = uv * 2.0 - 1.0;
+    var out: SkyOutput;
+    out.clip_position = vec4<f32>(ndc, 1.0, 1.0);
+    out.ndc = ndc;
+    return out;
+}
+
+const SUN_COLOR: vec3<f32> = vec3<f32>(1.0, 0.95, 0.8);
+const MOON_COLOR: vec3<f32> = vec3<f32>(0.8, 0.85, 0.95);
+
+@fragment
+fn fs_sky(in: SkyOutput) -> @location(0) vec4<f32> {
+    let far_point = sky.inv_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+    let dir = normalize(far_point.xyz / far_point.w - camera.view_position);
+    var color = sky_gradient(dir);
+    if (is_underwater()) {
+        return vec4<f32>(color, 1.0);
+    }
+
+    let sun_dir = normalize(sky.sun_direction.xyz);
+    let sun = dot(dir, sun_dir);
+    let sun_disk = smoothstep(0.9990, 0.9995, sun);
+    let sun_glow = pow(max(sun, 0.0), 64.0) * 0.35;
+    color += SUN_COLOR * (sun_disk + sun_glow);
+
+    let moon_disk = smoothstep(0.9994, 0.9997, dot(dir, -sun_dir));
+    color = mix(color, MOON_COLOR, moon_disk);
+    return vec4<f32>(color, 1.0);
 }
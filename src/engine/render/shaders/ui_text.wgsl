@@ -0,0 +1,38 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    // Same normalized screen coordinates as ui.wgsl
+    out.clip_position = vec4<f32>(
+        model.position.x * 2.0 - 1.0,
+        1.0 - model.position.y * 2.0,
+        0.0,
+        1.0
+    );
+    out.uv = model.uv;
+    out.color = model.color;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_glyphs: texture_2d<f32>;
+@group(0) @binding(1)
+var s_glyphs: sampler;
+
+// The glyph atlas only stores coverage, the colour comes from the vertex
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(t_glyphs, s_glyphs, in.uv).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
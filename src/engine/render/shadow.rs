@@ -0,0 +1,108 @@
+use glam::{Mat4, Vec3, Vec4, Vec4Swizzles};
+
+/// Shadows are drawn up to this distance from the camera, anything farther is fully lit.
+pub const SHADOW_DISTANCE: f32 = 64.0;
+/// Extra depth towards the sun, so blocks outside the view still cast shadows into it.
+const CASTER_MARGIN: f32 = 64.0;
+
+/// Depth texture the sun's view is rendered into.
+pub struct ShadowMap {
+    pub size: u32,
+    view: wgpu::TextureView,
+}
+
+impl ShadowMap {
+    pub fn new(device: &wgpu::Device, size: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Texture"),
+            size: wgpu::Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { size, view }
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+}
+
+/// Light-space view-projection covering the part of the camera frustum between `near` and `far`.
+/// The box is sized from the slice's bounding sphere and snapped to whole shadow map texels,
+/// so shadow edges stay put while the camera moves and turns.
+pub fn fit_light_view_proj(
+    light_direction: Vec3,
+    camera_view: Mat4,
+    fov: f32,
+    aspect: f32,
+    near: f32,
+    far: f32,
+    map_size: u32,
+) -> Mat4 {
+    let inv_view_proj = (Mat4::perspective_rh(fov, aspect, near, far) * camera_view).inverse();
+    let mut corners = [Vec3::ZERO; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        let ndc = Vec4::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { 0.0 } else { 1.0 },
+            1.0,
+        );
+        let point = inv_view_proj * ndc;
+        *corner = point.xyz() / point.w;
+    }
+
+    let center = corners.iter().fold(Vec3::ZERO, |sum, c| sum + *c) / corners.len() as f32;
+    // Rounded up with a unit of slack, so snapping the center below never pushes a corner out
+    let radius = corners.iter().map(|c| c.distance(center)).fold(0.0, f32::max).ceil() + 1.0;
+
+    let direction = light_direction.normalize();
+    let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+    let light_view = Mat4::look_at_rh(Vec3::ZERO, direction, up);
+
+    let texel = 2.0 * radius / map_size as f32;
+    let mut light_center = light_view.transform_point3(center);
+    light_center.x = (light_center.x / texel).floor() * texel;
+    light_center.y = (light_center.y / texel).floor() * texel;
+
+    // Looking down -Z: the side facing the sun has the larger z
+    let proj = Mat4::orthographic_rh(
+        light_center.x - radius,
+        light_center.x + radius,
+        light_center.y - radius,
+        light_center.y + radius,
+        -(light_center.z + radius + CASTER_MARGIN),
+        -(light_center.z - radius),
+    );
+    proj * light_view
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fitted_projection_contains_the_camera_slice() {
+        let camera_view = Mat4::look_at_rh(Vec3::new(10.0, 40.0, -5.0), Vec3::new(30.0, 35.0, 20.0), Vec3::Y);
+        let light_direction = Vec3::new(-0.4, -1.0, -0.25);
+        let light = fit_light_view_proj(light_direction, camera_view, 70f32.to_radians(), 16.0 / 9.0, 0.1, 48.0, 1024);
+
+        // Points the camera sees within the slice must land inside the shadow map
+        let inv_camera = (Mat4::perspective_rh(70f32.to_radians(), 16.0 / 9.0, 0.1, 48.0) * camera_view).inverse();
+        for x in [-1.0, 0.0, 1.0] {
+            for y in [-1.0, 0.0, 1.0] {
+                for z in [0.0, 0.5, 1.0] {
+                    let world = inv_camera.project_point3(Vec3::new(x, y, z));
+                    let clip = light.project_point3(world);
+                    assert!(clip.x.abs() <= 1.0 && clip.y.abs() <= 1.0, "{:?} outside the map", world);
+                    assert!((0.0..=1.0).contains(&clip.z), "{:?} outside the depth range", world);
+                }
+            }
+        }
+    }
+}
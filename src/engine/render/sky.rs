@@ -0,0 +1,80 @@
+use glam::{Mat4, Vec3};
+
+/// Fog starts at this fraction of the view distance and reaches full strength at its end.
+const FOG_START: f32 = 0.7;
+
+/// Fog used while the camera is inside a fluid; replaces both the distance fog and the sky.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnderwaterFog {
+    pub color: Vec3,
+    pub distance: f32,
+}
+
+/// Sky gradient, sun/moon placement and distance fog, drawn by the sky pass and
+/// applied to world geometry in `shader.wgsl`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    pub zenith_color: Vec3,
+    pub horizon_color: Vec3,
+    /// Direction towards the sun; the moon is always on the opposite side.
+    pub sun_direction: Vec3,
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub underwater: Option<UnderwaterFog>,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self {
+            zenith_color: Vec3::new(0.32, 0.55, 0.95),
+            horizon_color: Vec3::new(0.72, 0.84, 0.98),
+            sun_direction: Vec3::new(0.0, 1.0, 0.5).normalize(),
+            fog_start: 64.0,
+            fog_end: 96.0,
+            underwater: None,
+        }
+    }
+}
+
+impl Sky {
+    /// Places the fog so geometry fades into the sky right where the loaded world ends.
+    pub fn set_view_distance(&mut self, distance: f32) {
+        self.fog_start = distance * FOG_START;
+        self.fog_end = distance;
+    }
+
+    /// Colour the frame is cleared to, matching the fog at the horizon.
+    pub fn clear_color(&self) -> Vec3 {
+        match self.underwater {
+            Some(fog) => fog.color,
+            None => self.horizon_color,
+        }
+    }
+
+    pub(crate) fn to_uniform(&self, inv_view_proj: Mat4, far: f32) -> SkyUniform {
+        let (fog_color, fog_start, fog_end, underwater) = match self.underwater {
+            Some(fog) => (fog.color, 0.0, fog.distance, 1.0),
+            None => (self.horizon_color, self.fog_start.min(far), self.fog_end.min(far), 0.0),
+        };
+        SkyUniform {
+            inv_view_proj: inv_view_proj.to_cols_array_2d(),
+            zenith_color: self.zenith_color.extend(1.0).to_array(),
+            horizon_color: self.horizon_color.extend(1.0).to_array(),
+            sun_direction: self.sun_direction.normalize_or_zero().extend(0.0).to_array(),
+            fog_color: fog_color.extend(1.0).to_array(),
+            fog_params: [fog_start, fog_end.max(fog_start + 0.01), underwater, 0.0],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct SkyUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    zenith_color: [f32; 4],
+    horizon_color: [f32; 4],
+    sun_direction: [f32; 4],
+    fog_color: [f32; 4],
+    /// x: fog start, y: fog end, z: 1 when underwater.
+    fog_params: [f32; 4],
+}
@@ -1,5 +1,62 @@
 use std::{collections::HashMap, sync::Arc};
 use wgpu::{Sampler, TextureView};
+
+use crate::engine::assets::{Assets, SharedAssets};
+use crate::engine::logger::Logger;
+use crate::engine::render::animation::{TextureAnimation, ANIMATION_EXTENSION};
+use crate::engine::render::error::TextureError;
+use crate::engine::settings::TextureFilter;
+
+/// Mip levels of every atlas layer; a 16x16 texture goes down to 2x2.
+pub const MIP_LEVELS: u32 = 4;
+/// Border around each texture filled with copies of its edge pixels, wide enough that neither
+/// filtering nor the smallest mip level pulls in the neighbouring texture.
+pub const GUTTER: u32 = 1 << (MIP_LEVELS - 1);
+pub const ATLAS_SIZE: u32 = 512;
+/// Size of block textures in `TextureLayout::Layers`.
+pub const TILE_SIZE: u32 = 16;
+const MAX_LAYERS: u32 = 256;
+
+/// How textures are placed in the texture array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureLayout {
+    /// Textures of any size packed into `size`² layers, each surrounded by a gutter.
+    Atlas { size: u32 },
+    /// Every texture gets a whole `tile_size`² layer and is resized to fit. UVs cover the full
+    /// layer, so faces can repeat a texture (greedy meshing) and the mip chain goes down to 1x1.
+    Layers { tile_size: u32 },
+}
+
+impl TextureLayout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextureLayout::Atlas { .. } => "atlas",
+            TextureLayout::Layers { .. } => "layers",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "atlas" => Some(TextureLayout::Atlas { size: ATLAS_SIZE }),
+            "layers" => Some(TextureLayout::Layers { tile_size: TILE_SIZE }),
+            _ => None,
+        }
+    }
+
+    fn layer_size(&self) -> u32 {
+        match self {
+            TextureLayout::Atlas { size } => *size,
+            TextureLayout::Layers { tile_size } => *tile_size,
+        }
+    }
+
+    fn mip_levels(&self) -> u32 {
+        match self {
+            TextureLayout::Atlas { .. } => MIP_LEVELS,
+            TextureLayout::Layers { tile_size } => tile_size.max(&1).ilog2() + 1,
+        }
+    }
+}
 #[derive(Debug, Clone)]
 pub struct TextureInfo {
     pub id: u32,
@@ -9,22 +66,39 @@ pub struct TextureInfo {
     pub uvs: (f32, f32, f32, f32), // 
 }
 
+/// Frames of an animated texture, already prepared for its place in the array.
+struct AnimatedTexture {
+    /// Name of the texture in `textures`.
+    texture: String,
+    animation: TextureAnimation,
+    frames: Vec<image::RgbaImage>,
+    /// Where `frames` are written, the top-left of the gutter in atlas mode.
+    origin: (u32, u32, u32),
+    current: u32,
+}
+
 pub struct TextureManager {
     pub texture_array: wgpu::Texture,
+    layout: TextureLayout,
     atlas_size: u32,
     max_layers: u32,
+    mip_levels: u32,
     next_position: (u32, u32, u32), // x, y, z
     current_row_height: u32,
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
     textures: HashMap<String, TextureInfo>, // path -> info
     texture_by_id: HashMap<u32, String>,    // id -> path
+    animations: Vec<AnimatedTexture>,
+    assets: SharedAssets,
     next_id: u32,
 }
 
 impl TextureManager {
-    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, atlas_size: u32) -> Self {
-        let max_layers = 256;
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, layout: TextureLayout) -> Self {
+        let max_layers = MAX_LAYERS.min(device.limits().max_texture_array_layers);
+        let atlas_size = layout.layer_size();
+        let mip_levels = layout.mip_levels();
         
         let texture_array = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Texture Atlas"),
@@ -33,7 +107,7 @@ impl TextureManager {
                 height: atlas_size,
                 depth_or_array_layers: max_layers,
             },
-            mip_level_count: 1,
+            mip_level_count: mip_levels,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -43,14 +117,18 @@ impl TextureManager {
         
         Self {
             texture_array,
+            layout,
             atlas_size,
             max_layers,
+            mip_levels,
             next_position: (0, 0, 0),
             current_row_height: 0,
             device,
             queue,
             textures: HashMap::new(),
             texture_by_id: HashMap::new(),
+            animations: Vec::new(),
+            assets: Assets::new(&[]).shared(),
             next_id: 0,
         }
     }
@@ -59,7 +137,25 @@ impl TextureManager {
         self.atlas_size
     }
 
-    pub fn add_texture(&mut self, path: &str, name: Option<&str>) -> Option<u32> {
+    pub fn get_layout(&self) -> TextureLayout {
+        self.layout
+    }
+
+    /// Asset layers textures are looked up in; shared with the engine so pack changes apply here.
+    pub fn set_assets(&mut self, assets: SharedAssets) {
+        self.assets = assets;
+    }
+
+    /// Loads a texture; it is animated if a `.anim` file sits next to it (see `TextureAnimation`).
+    /// `path` is resolved through the asset layers, so resource packs and mods can replace it.
+    pub fn add_texture(&mut self, path: &str, name: Option<&str>) -> Result<u32, TextureError> {
+        let animation = self.sidecar_animation(path)?;
+        self.add_animated_texture(path, name, animation)
+    }
+
+    /// Loads a texture whose image is a vertical strip of square frames, played by
+    /// `update_animations`. With `None` the whole image is one still texture.
+    pub fn add_animated_texture(&mut self, path: &str, name: Option<&str>, animation: Option<TextureAnimation>) -> Result<u32, TextureError> {
         let texture_name = name.unwrap_or_else(|| {
             std::path::Path::new(path)
                 .file_stem()
@@ -68,27 +164,35 @@ impl TextureManager {
         });
         // Return existing texture if already loaded
         if let Some(info) = self.textures.get(texture_name) {
-            return Some(info.id);
+            return Ok(info.id);
         }
         
-        let (texture_data, width, height) = if let Ok(img) = image::open(path) {
-            let rgba = img.to_rgba8();
-            let dimensions = rgba.dimensions();
-            (rgba.into_raw(), dimensions.0, dimensions.1)
-        } else {
-            println!("Failed to load texture: {}, using magenta fallback", path);
-            // Magenta fallback 16x16
-            let mut data = Vec::new();
-            for _ in 0..(16 * 16) {
-                data.extend_from_slice(&[255, 0, 255, 255]);
+        let frames = self.load_frames(path, animation.as_ref())?;
+        let (width, height) = frames[0].dimensions();
+        let frames: Vec<image::RgbaImage> = frames.iter().map(|frame| self.prepare_image(path, frame)).collect();
+        
+        // `origin` is where the stored image goes, `(x, y)` where the texture itself starts
+        let (origin, x, y, width, height) = match self.layout {
+            TextureLayout::Atlas { .. } => {
+                // Find position for texture, the gutter goes around it
+                let (cell_x, cell_y, z) = self.find_position(path, frames[0].width(), frames[0].height())?;
+                ((cell_x, cell_y, z), cell_x + GUTTER, cell_y + GUTTER, width, height)
+            }
+            TextureLayout::Layers { tile_size } => {
+                let z = self.next_position.2;
+                if z >= self.max_layers {
+                    return Err(TextureError::CapacityExceeded { path: path.to_string(), layers: self.max_layers });
+                }
+                self.next_position.2 += 1;
+                ((0, 0, z), 0, 0, tile_size, tile_size)
             }
-            (data, 16, 16)
         };
-        
-        // Find position for texture
-        let (x, y, z) = self.find_position(width, height)?;
-        
-        self.write_texture_data(&texture_data, x, y, z, width, height);
+        let z = origin.2;
+        self.write_mip_chain(frames[0].clone(), origin.0, origin.1, origin.2);
+        if let Some(animation) = animation {
+            Logger::info(&format!("Texture {} is animated, {} frames", texture_name, frames.len()));
+            self.animations.push(AnimatedTexture { texture: texture_name.to_string(), animation, frames, origin, current: 0 });
+        }
         
         let u_min = x as f32 / self.atlas_size as f32;
         let v_min = y as f32 / self.atlas_size as f32;
@@ -108,14 +212,151 @@ impl TextureManager {
         
         let id: u32 = self.next_id;
         self.next_id += 1;
-        Some(id)
+        Ok(id)
+    }
+    
+    /// Shows the frame each animated texture has at `tick`, rewriting only the ones that changed.
+    pub fn update_animations(&mut self, tick: u64) {
+        let mut changed = Vec::new();
+        for animated in &mut self.animations {
+            let frame = animated.animation.frame_at(tick, animated.frames.len() as u32);
+            if frame != animated.current {
+                animated.current = frame;
+                changed.push((animated.frames[frame as usize].clone(), animated.origin));
+            }
+        }
+        for (image, (x, y, z)) in changed {
+            self.write_mip_chain(image, x, y, z);
+        }
+    }
+
+    /// Reads every texture again through the asset layers, after the resource packs changed.
+    /// Each texture keeps its place; atlas textures are resized to the size they were loaded with.
+    pub fn reload_textures(&mut self) {
+        let mut names: Vec<String> = self.textures.keys().cloned().collect();
+        names.sort_by_key(|name| self.textures[name].id);
+        for name in names {
+            if let Err(e) = self.reload_texture(&name) {
+                Logger::error(&format!("Failed to reload texture {}: {}", name, e));
+            }
+        }
+        Logger::info(&format!("Reloaded {} textures", self.textures.len()));
+    }
+
+    /// Reloads the textures whose image or `.anim` file is one of `changed`, in any asset layer,
+    /// so adding or removing an override in a pack counts too. Returns how many were reloaded.
+    pub fn reload_changed(&mut self, changed: &[std::path::PathBuf]) -> usize {
+        let mut names: Vec<String> = self
+            .textures
+            .iter()
+            .filter(|(_, info)| {
+                let sidecar = format!("{}.{}", info.path, ANIMATION_EXTENSION);
+                changed.iter().any(|file| file.ends_with(&info.path) || file.ends_with(&sidecar))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort_by_key(|name| self.textures[name].id);
+        for name in &names {
+            match self.reload_texture(name) {
+                Ok(()) => Logger::info(&format!("Reloaded texture {}", name)),
+                Err(e) => Logger::error(&format!("Failed to reload texture {}: {}", name, e)),
+            }
+        }
+        names.len()
+    }
+
+    fn reload_texture(&mut self, name: &str) -> Result<(), TextureError> {
+        let info = self.textures[name].clone();
+        let previous = self.animations.iter().position(|animated| animated.texture == name);
+        let animation = match self.sidecar_animation(&info.path)? {
+            Some(animation) => Some(animation),
+            None => previous.map(|i| self.animations[i].animation.clone()),
+        };
+        
+        let frames = self.load_frames(&info.path, animation.as_ref())?;
+        let frames: Vec<image::RgbaImage> = frames
+            .iter()
+            .map(|frame| {
+                let fits = matches!(self.layout, TextureLayout::Layers { .. }) || frame.dimensions() == info.dimensions;
+                if fits {
+                    self.prepare_image(&info.path, frame)
+                } else {
+                    let (width, height) = info.dimensions;
+                    let resized = image::imageops::resize(frame, width, height, image::imageops::FilterType::Nearest);
+                    self.prepare_image(&info.path, &resized)
+                }
+            })
+            .collect();
+        
+        let (x, y, z) = info.atlas_position;
+        let origin = match self.layout {
+            TextureLayout::Atlas { .. } => (x - GUTTER, y - GUTTER, z),
+            TextureLayout::Layers { .. } => (0, 0, z),
+        };
+        self.write_mip_chain(frames[0].clone(), origin.0, origin.1, origin.2);
+        
+        if let Some(i) = previous {
+            self.animations.remove(i);
+        }
+        if let Some(animation) = animation {
+            self.animations.push(AnimatedTexture { texture: name.to_string(), animation, frames, origin, current: 0 });
+        }
+        Ok(())
+    }
+
+    /// The animation from the `.anim` file next to `path`, if any layer has one.
+    fn sidecar_animation(&self, path: &str) -> Result<Option<TextureAnimation>, TextureError> {
+        let assets = self.assets.read().unwrap();
+        match TextureAnimation::load_sidecar(&assets, path) {
+            Some(Ok(animation)) => Ok(Some(animation)),
+            Some(Err(reason)) => Err(TextureError::BadAnimation { path: path.to_string(), reason }),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads `path` through the asset layers and cuts it into frames. A missing or broken image
+    /// becomes a magenta placeholder so the game still runs.
+    fn load_frames(&self, path: &str, animation: Option<&TextureAnimation>) -> Result<Vec<image::RgbaImage>, TextureError> {
+        let file = self.assets.read().unwrap().resolve(path);
+        let image = match file.map(image::open) {
+            Some(Ok(img)) => img.to_rgba8(),
+            _ => {
+                println!("Failed to load texture: {}, using magenta fallback", path);
+                // Magenta fallback 16x16
+                image::RgbaImage::from_pixel(16, 16, image::Rgba([255, 0, 255, 255]))
+            }
+        };
+        match animation {
+            Some(animation) => split_frames(&image, animation).map_err(|reason| TextureError::BadAnimation { path: path.to_string(), reason }),
+            None => Ok(vec![image]),
+        }
+    }
+
+    /// The image as it is stored: with its gutter in the atlas, or resized to the tile.
+    fn prepare_image(&self, path: &str, image: &image::RgbaImage) -> image::RgbaImage {
+        match self.layout {
+            TextureLayout::Atlas { .. } => pad_with_gutter(image.as_raw(), image.width(), image.height(), GUTTER),
+            TextureLayout::Layers { tile_size } => {
+                if image.dimensions() == (tile_size, tile_size) {
+                    return image.clone();
+                }
+                Logger::warn(&format!("Resizing texture {} from {}x{} to {}x{}", path, image.width(), image.height(), tile_size, tile_size));
+                image::imageops::resize(image, tile_size, tile_size, image::imageops::FilterType::Nearest)
+            }
+        }
     }
     
     pub fn get_texture_id_by_name(&self, name: &str) -> Option<u32> {
         self.textures.get(name).map(|info| info.id)
     }
     
-    fn find_position(&mut self, width: u32, height: u32) -> Option<(u32, u32, u32)> {
+    fn find_position(&mut self, path: &str, width: u32, height: u32) -> Result<(u32, u32, u32), TextureError> {
+        // Cells start on multiples of the smallest mip's texel, so mip texels never straddle two textures
+        let width = width.next_multiple_of(GUTTER);
+        let height = height.next_multiple_of(GUTTER);
+        if width > self.atlas_size || height > self.atlas_size {
+            return Err(TextureError::TooLarge { path: path.to_string(), width, height, atlas_size: self.atlas_size });
+        }
         let (mut x, mut y, mut z) = self.next_position;
         
         // Check if texture fits in current row
@@ -137,22 +378,31 @@ impl TextureManager {
         
         // Check if we have space
         if z >= self.max_layers {
-            println!("Texture atlas full! Cannot add more textures.");
-            return None;
+            return Err(TextureError::CapacityExceeded { path: path.to_string(), layers: self.max_layers });
         }
         
         // Update position for next texture
         self.next_position = (x + width, y, z);
         self.current_row_height = self.current_row_height.max(height);
         
-        Some((x, y, z))
+        Ok((x, y, z))
     }
     
-    fn write_texture_data(&self, data: &[u8], x: u32, y: u32, z: u32, width: u32, height: u32) {
+    /// Writes `image` at `(x, y)` of layer `z` and its downsampled copies into every mip level.
+    fn write_mip_chain(&self, mut image: image::RgbaImage, x: u32, y: u32, z: u32) {
+        for level in 0..self.mip_levels {
+            if level > 0 {
+                image = downsample(&image);
+            }
+            self.write_texture_data(image.as_raw(), level, x >> level, y >> level, z, image.width(), image.height());
+        }
+    }
+
+    fn write_texture_data(&self, data: &[u8], mip_level: u32, x: u32, y: u32, z: u32, width: u32, height: u32) {
         self.queue.write_texture(
             wgpu::TexelCopyTextureInfo {
                 texture: &self.texture_array,
-                mip_level: 0,
+                mip_level,
                 origin: wgpu::Origin3d { x, y, z },
                 aspect: wgpu::TextureAspect::All,
             },
@@ -189,17 +439,102 @@ impl TextureManager {
         })
     }
 
-    pub fn get_sampler(&self) -> Sampler {
+    /// Sampler for the atlas. `anisotropy` only takes effect with `TextureFilter::Trilinear`,
+    /// wgpu requires every filter to be linear for it.
+    pub fn get_sampler(&self, filter: TextureFilter, anisotropy: u16) -> Sampler {
+        let (filter_mode, mipmap_filter, anisotropy_clamp) = match filter {
+            TextureFilter::PixelArt => (wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest, 1),
+            TextureFilter::Bilinear => (wgpu::FilterMode::Linear, wgpu::FilterMode::Nearest, 1),
+            TextureFilter::Trilinear => (wgpu::FilterMode::Linear, wgpu::FilterMode::Linear, anisotropy.max(1)),
+        };
         self.device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("Texture Sampler"),
             address_mode_u: wgpu::AddressMode::Repeat,
             address_mode_v: wgpu::AddressMode::Repeat,
             address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
+            mag_filter: filter_mode,
+            min_filter: filter_mode,
+            mipmap_filter,
+            anisotropy_clamp,
             ..Default::default()
         })
     }
 
-}
\ No newline at end of file
+}
+
+/// Cuts a vertical strip into its square frames and checks the animation against them.
+fn split_frames(image: &image::RgbaImage, animation: &TextureAnimation) -> Result<Vec<image::RgbaImage>, String> {
+    let (width, height) = image.dimensions();
+    if height % width != 0 {
+        return Err(format!("a {}x{} image is not a strip of square frames", width, height));
+    }
+    let frame_count = height / width;
+    animation.validate(frame_count)?;
+    Ok((0..frame_count)
+        .map(|i| image::imageops::crop_imm(image, 0, i * width, width, width).to_image())
+        .collect())
+}
+
+/// Copies the texture into the middle of a larger image and extends its edge pixels outwards.
+fn pad_with_gutter(data: &[u8], width: u32, height: u32, gutter: u32) -> image::RgbaImage {
+    image::RgbaImage::from_fn(width + 2 * gutter, height + 2 * gutter, |x, y| {
+        let src_x = x.saturating_sub(gutter).min(width - 1);
+        let src_y = y.saturating_sub(gutter).min(height - 1);
+        let i = ((src_y * width + src_x) * 4) as usize;
+        image::Rgba([data[i], data[i + 1], data[i + 2], data[i + 3]])
+    })
+}
+
+/// Halves the image with a 2x2 box filter. Colours are averaged in linear space (the atlas is sRGB)
+/// and weighted by alpha, so transparent texels don't darken the edges of cutout textures.
+fn downsample(image: &image::RgbaImage) -> image::RgbaImage {
+    let (width, height) = ((image.width() / 2).max(1), (image.height() / 2).max(1));
+    image::RgbaImage::from_fn(width, height, |x, y| {
+        let mut color = [0.0f32; 3];
+        let mut alpha = 0.0f32;
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let texel = image.get_pixel((x * 2 + dx).min(image.width() - 1), (y * 2 + dy).min(image.height() - 1));
+            let weight = texel[3] as f32 / 255.0;
+            for c in 0..3 {
+                color[c] += srgb_to_linear(texel[c]) * weight;
+            }
+            alpha += weight;
+        }
+        let rgb = color.map(|c| if alpha > 0.0 { linear_to_srgb(c / alpha) } else { 0 });
+        image::Rgba([rgb[0], rgb[1], rgb[2], (alpha / 4.0 * 255.0).round() as u8])
+    })
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let c = if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 };
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gutter_repeats_edge_pixels() {
+        // 2x1: red, blue
+        let padded = pad_with_gutter(&[255, 0, 0, 255, 0, 0, 255, 255], 2, 1, 2);
+        assert_eq!(padded.dimensions(), (6, 5));
+        assert_eq!(padded.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(padded.get_pixel(5, 4).0, [0, 0, 255, 255]);
+        assert_eq!(padded.get_pixel(3, 2).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn downsample_ignores_transparent_colour() {
+        let mut image = image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 0, 0]));
+        image.put_pixel(0, 0, image::Rgba([200, 100, 50, 255]));
+        let half = downsample(&image);
+        assert_eq!(half.dimensions(), (1, 1));
+        assert_eq!(half.get_pixel(0, 0).0, [200, 100, 50, 64]);
+    }
+}
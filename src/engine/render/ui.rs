@@ -1,7 +1,14 @@
+use std::ops::Range;
+use std::sync::Arc;
 use wgpu::util::DeviceExt;
 use glam::{Vec2, Vec4};
 
-use crate::engine::{render::bitmap_font::FONT_DATA, ui::UI};
+use winit::keyboard::ModifiersState;
+
+use crate::engine::{InputEvent, render::bitmap_font::FONT_DATA, ui::{UI, Widget}};
+use crate::engine::render::error::ShaderError;
+use crate::engine::render::font::{Font, GLYPH_ATLAS_SIZE};
+use crate::engine::render::pipelines::{self, UI_SHADER, UI_TEXTURE_SHADER, UI_TEXT_SHADER};
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct UIVertex {
@@ -40,10 +47,41 @@ impl UIVertex {
     }
 }
 
+/// Pipeline that draws a run of UI quads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BatchKind {
+    Color,
+    Texture,
+    Text,
+}
+
+/// Consecutive quads of one kind, as a range of that kind's index buffer.
+#[derive(Debug, Clone)]
+struct UIBatch {
+    kind: BatchKind,
+    indices: Range<u32>,
+}
+
 pub struct UIRenderer {
     pub screen_size: Vec2,
+    surface_format: wgpu::TextureFormat,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    texture_pipeline_layout: wgpu::PipelineLayout,
+    text_pipeline_layout: wgpu::PipelineLayout,
     render_pipeline: wgpu::RenderPipeline,
     texture_pipeline: wgpu::RenderPipeline,
+    text_pipeline: wgpu::RenderPipeline,
+    queue: Arc<wgpu::Queue>,
+    /// TrueType font for text; `None` draws the built-in bitmap font.
+    font: Option<Font>,
+    font_texture: wgpu::Texture,
+    font_bind_group: wgpu::BindGroup,
+    text_vertices: Vec<UIVertex>,
+    text_indices: Vec<u16>,
+    text_vertex_buffer: Option<wgpu::Buffer>,
+    text_index_buffer: Option<wgpu::Buffer>,
+    /// Lines drawn over everything else, see `set_error_overlay`.
+    error_overlay: Vec<String>,
     vertex_buffer: Option<wgpu::Buffer>,
     index_buffer: Option<wgpu::Buffer>,
     vertices: Vec<UIVertex>,
@@ -53,14 +91,19 @@ pub struct UIRenderer {
     texture_vertex_buffer: Option<wgpu::Buffer>,
     texture_index_buffer: Option<wgpu::Buffer>,
     current_texture_bind_group: Option<wgpu::BindGroup>,
+    /// Draws in the order they were submitted, so a panel drawn after some text covers it
+    /// even though text and panels use different pipelines.
+    batches: Vec<UIBatch>,
     pub ui: Option<UI>,
+    /// Modifier keys held, for widget shortcuts like Ctrl+A.
+    modifiers: ModifiersState,
 }
 
 impl UIRenderer {
-    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+    pub fn new(device: &wgpu::Device, queue: Arc<wgpu::Queue>, surface_format: wgpu::TextureFormat) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("UI Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/ui.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(pipelines::embedded_source(UI_SHADER).unwrap().into()),
         });
 
         let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -69,43 +112,7 @@ impl UIRenderer {
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("UI Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[UIVertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
+        let render_pipeline = Self::create_color_pipeline(device, &shader, &render_pipeline_layout, surface_format);
 
         let texture_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
@@ -137,20 +144,157 @@ impl UIRenderer {
 
         let texture_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("UI Texture Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/ui_texture.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(pipelines::embedded_source(UI_TEXTURE_SHADER).unwrap().into()),
         });
 
-        let texture_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let texture_pipeline = Self::create_texture_pipeline(device, &texture_shader, &texture_pipeline_layout, surface_format);
+
+        // Glyph coverage for TrueType text, filled by `Font` as glyphs are first drawn
+        let font_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("UI Glyph Atlas"),
+            size: wgpu::Extent3d { width: GLYPH_ATLAS_SIZE, height: GLYPH_ATLAS_SIZE, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let font_view = font_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let font_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("UI Glyph Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let font_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&font_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&font_sampler),
+                },
+            ],
+            label: Some("UI Glyph Bind Group"),
+        });
+
+        let text_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("UI Text Pipeline Layout"),
+            bind_group_layouts: &[&texture_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let text_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("UI Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(pipelines::embedded_source(UI_TEXT_SHADER).unwrap().into()),
+        });
+
+        // Same vertices and blending as plain UI quads, only the fragment shader differs
+        let text_pipeline = Self::create_color_pipeline(device, &text_shader, &text_pipeline_layout, surface_format);
+
+        Self {
+            screen_size: Vec2::new(800.0, 600.0),
+            surface_format,
+            render_pipeline_layout,
+            texture_pipeline_layout,
+            text_pipeline_layout,
+            render_pipeline,
+            texture_pipeline,
+            text_pipeline,
+            queue,
+            font: None,
+            font_texture,
+            font_bind_group,
+            text_vertices: Vec::new(),
+            text_indices: Vec::new(),
+            text_vertex_buffer: None,
+            text_index_buffer: None,
+            error_overlay: Vec::new(),
+            vertex_buffer: None,
+            index_buffer: None,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            texture_vertices: Vec::new(),
+            texture_indices: Vec::new(),
+            texture_vertex_buffer: None,
+            texture_index_buffer: None,
+            current_texture_bind_group: None,
+            batches: Vec::new(),
+            ui: None,
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    fn create_color_pipeline(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        layout: &wgpu::PipelineLayout,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("UI Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[UIVertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_texture_pipeline(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        layout: &wgpu::PipelineLayout,
+        surface_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("UI Texture Pipeline"),
-            layout: Some(&texture_pipeline_layout),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
-                module: &texture_shader,
+                module: shader,
                 entry_point: Some("vs_main"),
                 buffers: &[UIVertex::desc()],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &texture_shader,
+                module: shader,
                 entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_format,
@@ -176,26 +320,90 @@ impl UIRenderer {
             },
             multiview: None,
             cache: None,
-        });
+        })
+    }
 
-        Self {
-            screen_size: Vec2::new(800.0, 600.0),
-            render_pipeline,
-            texture_pipeline,
-            vertex_buffer: None,
-            index_buffer: None,
-            vertices: Vec::new(),
-            indices: Vec::new(),
-            texture_vertices: Vec::new(),
-            texture_indices: Vec::new(),
-            texture_vertex_buffer: None,
-            texture_index_buffer: None,
-            current_texture_bind_group: None,
-            ui: None,
+    /// Rebuilds the pipeline of the UI shader `file` (`ui.wgsl`, `ui_texture.wgsl` or `ui_text.wgsl`) from
+    /// `source`. On a WGSL error the old pipeline is kept.
+    pub fn reload_shader(&mut self, device: &wgpu::Device, file: &str, source: &str) -> Result<(), ShaderError> {
+        let surface_format = self.surface_format;
+        let create = |layout: &wgpu::PipelineLayout, textured: bool| {
+            pipelines::create_checked(device, file, || {
+                let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(file),
+                    source: wgpu::ShaderSource::Wgsl(source.into()),
+                });
+                if textured {
+                    Self::create_texture_pipeline(device, &shader, layout, surface_format)
+                } else {
+                    Self::create_color_pipeline(device, &shader, layout, surface_format)
+                }
+            })
+        };
+        match file {
+            UI_SHADER => self.render_pipeline = create(&self.render_pipeline_layout, false)?,
+            UI_TEXTURE_SHADER => self.texture_pipeline = create(&self.texture_pipeline_layout, true)?,
+            UI_TEXT_SHADER => self.text_pipeline = create(&self.text_pipeline_layout, false)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Shows `lines` in a red box at the top of the screen, over the game UI. Empty hides it.
+    pub fn set_error_overlay(&mut self, lines: Vec<String>) {
+        self.error_overlay = lines;
+    }
+
+    fn render_error_overlay(&mut self) {
+        if self.error_overlay.is_empty() {
+            return;
         }
+        let scale = 1.5;
+        let line_height = self.measure_text("M", scale).y + 2.0;
+        let max_chars = ((self.screen_size.x - 20.0) / self.measure_text("M", scale).x.max(1.0)).max(1.0) as usize;
+        let max_lines = ((self.screen_size.y * 0.5) / line_height) as usize;
+        let lines: Vec<String> = self
+            .error_overlay
+            .iter()
+            .flat_map(|line| {
+                let chars: Vec<char> = line.chars().collect();
+                if chars.is_empty() {
+                    return vec![String::new()];
+                }
+                chars.chunks(max_chars).map(|chunk| chunk.iter().collect()).collect::<Vec<String>>()
+            })
+            .take(max_lines.max(1))
+            .collect();
+
+        let height = lines.len() as f32 * line_height + 20.0;
+        self.render_rect(Vec2::ZERO, Vec2::new(self.screen_size.x, height), Vec4::new(0.4, 0.0, 0.0, 0.85));
+        for (i, line) in lines.iter().enumerate() {
+            self.render_text(line, Vec2::new(10.0, 10.0 + i as f32 * line_height), scale, Vec4::new(1.0, 0.9, 0.9, 1.0));
+        }
+    }
+
+    /// Draws text with `font` from now on, or with the built-in bitmap font for `None`.
+    pub fn set_font(&mut self, font: Option<Font>) {
+        self.font = font;
     }
 
+    pub fn has_font(&self) -> bool {
+        self.font.is_some()
+    }
+
+    /// Size of `text` drawn with `render_text` at `scale`.
+    pub fn measure_text(&self, text: &str, scale: f32) -> Vec2 {
+        match &self.font {
+            Some(font) => font.measure(text, scale),
+            None => Vec2::new(text.chars().count() as f32 * 8.0 * scale, 8.0 * scale),
+        }
+    }
+
+    /// Replaces the widget tree; widgets with an id keep their state from the old one.
     pub fn set_ui(&mut self, ui: UI) {
+        if let Some(previous) = &self.ui {
+            ui.retain_state(previous);
+        }
         self.ui = Some(ui);
     }
 
@@ -208,7 +416,22 @@ impl UIRenderer {
         self.indices.clear();
         self.texture_vertices.clear();
         self.texture_indices.clear();
+        self.text_vertices.clear();
+        self.text_indices.clear();
         self.current_texture_bind_group = None;
+        self.batches.clear();
+    }
+
+    /// Records that `indices` of the `kind` index buffer draw next, joining the previous
+    /// batch when it is of the same kind.
+    fn push_batch(&mut self, kind: BatchKind, indices: Range<u32>) {
+        if indices.is_empty() {
+            return;
+        }
+        match self.batches.last_mut() {
+            Some(last) if last.kind == kind && last.indices.end == indices.start => last.indices.end = indices.end,
+            _ => self.batches.push(UIBatch { kind, indices }),
+        }
     }
 
     pub fn render_textured_rect(&mut self, pos: Vec2, size: Vec2, _texture_view: &wgpu::TextureView, bind_group: &wgpu::BindGroup) {
@@ -219,10 +442,12 @@ impl UIRenderer {
         self.texture_vertices.push(UIVertex::new([pos.x + size.x, pos.y + size.y], [1.0, 1.0], [1.0, 1.0, 1.0, 1.0]));
         self.texture_vertices.push(UIVertex::new([pos.x, pos.y + size.y], [0.0, 1.0], [1.0, 1.0, 1.0, 1.0]));
 
+        let start = self.texture_indices.len() as u32;
         self.texture_indices.extend_from_slice(&[
             base_index, base_index + 1, base_index + 2,
             base_index + 2, base_index + 3, base_index,
         ]);
+        self.push_batch(BatchKind::Texture, start..self.texture_indices.len() as u32);
         
         self.current_texture_bind_group = Some(bind_group.clone());
     }
@@ -238,16 +463,37 @@ impl UIRenderer {
         self.vertices.push(UIVertex::new([norm_pos.x + norm_size.x, norm_pos.y + norm_size.y], [1.0, 1.0], color.to_array()));
         self.vertices.push(UIVertex::new([norm_pos.x, norm_pos.y + norm_size.y], [0.0, 1.0], color.to_array()));
 
+        let start = self.indices.len() as u32;
         self.indices.extend_from_slice(&[
             base_index, base_index + 1, base_index + 2,
             base_index + 2, base_index + 3, base_index,
         ]);
+        self.push_batch(BatchKind::Color, start..self.indices.len() as u32);
     }
 
     pub fn render_text(&mut self, text: &str, pos: Vec2, scale: f32, color: Vec4) {
         // Не рендерим текст если он невидимый
         if color.w <= 0.0 { return; }
         
+        if let Some(font) = &mut self.font {
+            let start = self.text_indices.len() as u32;
+            for quad in font.layout(text, scale) {
+                let min = (pos + quad.pos) / self.screen_size;
+                let max = (pos + quad.pos + quad.size) / self.screen_size;
+                let base_index = self.text_vertices.len() as u16;
+                self.text_vertices.push(UIVertex::new([min.x, min.y], [quad.uv_min.x, quad.uv_min.y], color.to_array()));
+                self.text_vertices.push(UIVertex::new([max.x, min.y], [quad.uv_max.x, quad.uv_min.y], color.to_array()));
+                self.text_vertices.push(UIVertex::new([max.x, max.y], [quad.uv_max.x, quad.uv_max.y], color.to_array()));
+                self.text_vertices.push(UIVertex::new([min.x, max.y], [quad.uv_min.x, quad.uv_max.y], color.to_array()));
+                self.text_indices.extend_from_slice(&[
+                    base_index, base_index + 1, base_index + 2,
+                    base_index + 2, base_index + 3, base_index,
+                ]);
+            }
+            self.push_batch(BatchKind::Text, start..self.text_indices.len() as u32);
+            return;
+        }
+        
         let char_width = 8.0 * scale;
         let pixel_size = scale;
         
@@ -279,6 +525,27 @@ impl UIRenderer {
     }
 
     pub fn update_buffers(&mut self, device: &wgpu::Device) {
+        if let Some((pixels, rows)) = self.font.as_mut().and_then(|font| font.take_dirty()) {
+            let start = (rows.start * GLYPH_ATLAS_SIZE) as usize;
+            let end = (rows.end * GLYPH_ATLAS_SIZE) as usize;
+            self.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &self.font_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: 0, y: rows.start, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &pixels.as_raw()[start..end],
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(GLYPH_ATLAS_SIZE),
+                    rows_per_image: Some(rows.end - rows.start),
+                },
+                wgpu::Extent3d { width: GLYPH_ATLAS_SIZE, height: rows.end - rows.start, depth_or_array_layers: 1 },
+            );
+        }
+        
+
         if !self.vertices.is_empty() {
             self.vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("UI Vertex Buffer"),
@@ -306,6 +573,20 @@ impl UIRenderer {
                 usage: wgpu::BufferUsages::INDEX,
             }));
         }
+        
+        if !self.text_vertices.is_empty() {
+            self.text_vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("UI Text Vertex Buffer"),
+                contents: bytemuck::cast_slice(&self.text_vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            }));
+
+            self.text_index_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("UI Text Index Buffer"),
+                contents: bytemuck::cast_slice(&self.text_indices),
+                usage: wgpu::BufferUsages::INDEX,
+            }));
+        }
     }
 
     pub fn render<'a>(&'a mut self, render_pass: &mut wgpu::RenderPass<'a>, device: &wgpu::Device) {
@@ -315,23 +596,35 @@ impl UIRenderer {
             ui.render(self);
             self.ui = Some(ui);
         }
+        self.render_error_overlay();
         
         self.update_buffers(device);
         
-        if let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer) {
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.indices.len() as u32, 0, 0..1);
-        }
-        
-        if let (Some(texture_vertex_buffer), Some(texture_index_buffer), Some(bind_group)) = 
-            (&self.texture_vertex_buffer, &self.texture_index_buffer, &self.current_texture_bind_group) {
-            render_pass.set_pipeline(&self.texture_pipeline);
-            render_pass.set_bind_group(0, bind_group, &[]);
-            render_pass.set_vertex_buffer(0, texture_vertex_buffer.slice(..));
-            render_pass.set_index_buffer(texture_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.texture_indices.len() as u32, 0, 0..1);
+        for batch in &self.batches {
+            match batch.kind {
+                BatchKind::Color => {
+                    let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer) else { continue };
+                    render_pass.set_pipeline(&self.render_pipeline);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                }
+                BatchKind::Texture => {
+                    let (Some(vertex_buffer), Some(index_buffer), Some(bind_group)) =
+                        (&self.texture_vertex_buffer, &self.texture_index_buffer, &self.current_texture_bind_group) else { continue };
+                    render_pass.set_pipeline(&self.texture_pipeline);
+                    render_pass.set_bind_group(0, bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                }
+                BatchKind::Text => {
+                    let (Some(vertex_buffer), Some(index_buffer)) = (&self.text_vertex_buffer, &self.text_index_buffer) else { continue };
+                    render_pass.set_pipeline(&self.text_pipeline);
+                    render_pass.set_bind_group(0, &self.font_bind_group, &[]);
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                }
+            }
+            render_pass.draw_indexed(batch.indices.clone(), 0, 0..1);
         }
     }
 
@@ -343,6 +636,33 @@ impl UIRenderer {
         }
     }
     
+    /// Keyboard and text input for the focused widget; returns whether the UI used it.
+    pub fn handle_input(&mut self, event: &InputEvent) -> bool {
+        if let InputEvent::ModifiersChanged(modifiers) = event {
+            self.modifiers = *modifiers;
+        }
+        match &self.ui {
+            Some(ui) => ui.handle_input(event, self.modifiers),
+            None => false,
+        }
+    }
+
+    pub fn handle_cursor_moved(&mut self, point: Vec2) {
+        if let Some(ui) = &self.ui {
+            ui.handle_cursor_moved(point);
+        }
+    }
+
+    /// Changes the widget with the given id in the current tree, see `UI::update`.
+    pub fn update_widget<W: Widget, R>(&self, id: &str, f: impl FnOnce(&mut W) -> R) -> Option<R> {
+        self.ui.as_ref()?.update(id, f)
+    }
+
+    /// Whether a widget such as a text field has keyboard focus.
+    pub fn has_focus(&self) -> bool {
+        self.ui.as_ref().is_some_and(|ui| ui.has_focus())
+    }
+    
     pub fn render_image(&mut self, texture_name: &str, pos: Vec2, size: Vec2) {
         // TODO: Get texture from TextureManager by name and render it
         // For now, render a placeholder colored rect
@@ -0,0 +1,360 @@
+use std::fs;
+use std::path::Path;
+
+use crate::engine::logger::Logger;
+use crate::engine::render::screenshot::MAX_SCREENSHOT_SCALE;
+use crate::engine::render::texture_manager::{TextureLayout, ATLAS_SIZE};
+
+pub const SETTINGS_PATH: &str = "settings.cfg";
+/// Overrides the `backend` setting, e.g. `VOXEL_BACKEND=gl`.
+pub const BACKEND_ENV_VAR: &str = "VOXEL_BACKEND";
+
+pub const MIN_FOV: f32 = 30.0;
+pub const MAX_FOV: f32 = 110.0;
+pub const MIN_RENDER_DISTANCE: u32 = 2;
+pub const MAX_RENDER_DISTANCE: u32 = 32;
+pub const MAX_ANISOTROPY: u16 = 16;
+/// Blocks along a chunk side, same as `world::chunk::CHUNK_SIZE`.
+const CHUNK_BLOCKS: f32 = 16.0;
+
+/// Projection and view range of the main camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraSettings {
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32,
+    /// Radius in chunks around the player that the world keeps loaded.
+    pub render_distance: u32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            fov: 70.0,
+            near: 0.1,
+            far: 256.0,
+            render_distance: 4,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Applies one `key = value` entry; `None` if the key belongs to another section.
+    fn apply(&mut self, key: &str, value: &str) -> Option<bool> {
+        let parsed = match key {
+            "fov" => value.parse().map(|v| self.fov = v).is_ok(),
+            "near" => value.parse().map(|v| self.near = v).is_ok(),
+            "far" => value.parse().map(|v| self.far = v).is_ok(),
+            "render_distance" => value.parse().map(|v| self.render_distance = v).is_ok(),
+            _ => return None,
+        };
+        Some(parsed)
+    }
+
+    /// Keeps every value in a range the renderer can work with.
+    pub fn clamped(mut self) -> Self {
+        self.fov = self.fov.clamp(MIN_FOV, MAX_FOV);
+        self.near = self.near.clamp(0.01, 1.0);
+        self.render_distance = self.render_distance.clamp(MIN_RENDER_DISTANCE, MAX_RENDER_DISTANCE);
+        self.far = self.far.clamp(self.min_far(), 4096.0);
+        self
+    }
+
+    /// Shortest far plane that still reaches the corners of the loaded area, one chunk past
+    /// the render distance, so the far plane never cuts off streamed chunks.
+    pub fn min_far(&self) -> f32 {
+        (self.render_distance + 1) as f32 * CHUNK_BLOCKS * std::f32::consts::SQRT_2
+    }
+
+    pub fn to_config(&self) -> String {
+        format!(
+            "fov = {}\nnear = {}\nfar = {}\nrender_distance = {}\n",
+            self.fov, self.near, self.far, self.render_distance
+        )
+    }
+}
+
+/// Resolution of the sun's shadow map, `Off` skips the shadow pass entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    High,
+}
+
+impl ShadowQuality {
+    pub fn map_size(&self) -> u32 {
+        match self {
+            ShadowQuality::Off => 1,
+            ShadowQuality::Low => 1024,
+            ShadowQuality::High => 2048,
+        }
+    }
+
+    /// The next quality, wrapping around; used by the options screen.
+    pub fn next(&self) -> Self {
+        match self {
+            ShadowQuality::Off => ShadowQuality::Low,
+            ShadowQuality::Low => ShadowQuality::High,
+            ShadowQuality::High => ShadowQuality::Off,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShadowQuality::Off => "off",
+            ShadowQuality::Low => "low",
+            ShadowQuality::High => "high",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(ShadowQuality::Off),
+            "low" => Some(ShadowQuality::Low),
+            "high" => Some(ShadowQuality::High),
+            _ => None,
+        }
+    }
+}
+
+/// How block textures are sampled. Every mode uses the mipmaps, so distant faces don't shimmer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Nearest texel and nearest mip level: crisp pixels at any distance.
+    PixelArt,
+    Bilinear,
+    /// Bilinear plus blending between mip levels; the only mode anisotropy applies to.
+    Trilinear,
+}
+
+impl TextureFilter {
+    /// The next mode, wrapping around; used by the options screen.
+    pub fn next(&self) -> Self {
+        match self {
+            TextureFilter::PixelArt => TextureFilter::Bilinear,
+            TextureFilter::Bilinear => TextureFilter::Trilinear,
+            TextureFilter::Trilinear => TextureFilter::PixelArt,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextureFilter::PixelArt => "pixel_art",
+            TextureFilter::Bilinear => "bilinear",
+            TextureFilter::Trilinear => "trilinear",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pixel_art" => Some(TextureFilter::PixelArt),
+            "bilinear" => Some(TextureFilter::Bilinear),
+            "trilinear" => Some(TextureFilter::Trilinear),
+            _ => None,
+        }
+    }
+}
+
+/// Graphics API the renderer runs on. `Auto` lets wgpu pick the best one available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsBackend {
+    Auto,
+    Vulkan,
+    Gl,
+    Metal,
+    Dx12,
+}
+
+impl GraphicsBackend {
+    pub fn to_wgpu(&self) -> wgpu::Backends {
+        match self {
+            GraphicsBackend::Auto => wgpu::Backends::PRIMARY | wgpu::Backends::GL,
+            GraphicsBackend::Vulkan => wgpu::Backends::VULKAN,
+            GraphicsBackend::Gl => wgpu::Backends::GL,
+            GraphicsBackend::Metal => wgpu::Backends::METAL,
+            GraphicsBackend::Dx12 => wgpu::Backends::DX12,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GraphicsBackend::Auto => "auto",
+            GraphicsBackend::Vulkan => "vulkan",
+            GraphicsBackend::Gl => "gl",
+            GraphicsBackend::Metal => "metal",
+            GraphicsBackend::Dx12 => "dx12",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(GraphicsBackend::Auto),
+            "vulkan" | "vk" => Some(GraphicsBackend::Vulkan),
+            "gl" | "opengl" | "gles" => Some(GraphicsBackend::Gl),
+            "metal" => Some(GraphicsBackend::Metal),
+            "dx12" | "d3d12" => Some(GraphicsBackend::Dx12),
+            _ => None,
+        }
+    }
+
+    /// Backend requested through `BACKEND_ENV_VAR`, if it is set to a known name.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(BACKEND_ENV_VAR).ok()?;
+        let backend = Self::from_name(value.trim());
+        if backend.is_none() {
+            Logger::warn(&format!("Unknown {} '{}', using the settings file", BACKEND_ENV_VAR, value));
+        }
+        backend
+    }
+}
+
+/// Rendering quality options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphicsSettings {
+    pub shadows: ShadowQuality,
+    pub texture_filter: TextureFilter,
+    /// Maximum anisotropic filtering samples, 1 (off) to `MAX_ANISOTROPY`.
+    pub anisotropy: u16,
+    /// Packing of block textures; only read when the renderer starts.
+    pub texture_layout: TextureLayout,
+    pub backend: GraphicsBackend,
+    /// Falls back to a software adapter when no GPU adapter is found.
+    pub allow_software_fallback: bool,
+    /// Screenshots are rendered at this multiple of the window resolution.
+    pub screenshot_scale: u32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            shadows: ShadowQuality::Low,
+            texture_filter: TextureFilter::PixelArt,
+            anisotropy: 1,
+            texture_layout: TextureLayout::Atlas { size: ATLAS_SIZE },
+            backend: GraphicsBackend::Auto,
+            allow_software_fallback: true,
+            screenshot_scale: 1,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    fn apply(&mut self, key: &str, value: &str) -> Option<bool> {
+        let parsed = match key {
+            "shadows" => ShadowQuality::from_name(value).map(|v| self.shadows = v).is_some(),
+            "texture_filter" => TextureFilter::from_name(value).map(|v| self.texture_filter = v).is_some(),
+            "anisotropy" => value.parse().map(|v: u16| self.anisotropy = v.clamp(1, MAX_ANISOTROPY)).is_ok(),
+            "texture_layout" => TextureLayout::from_name(value).map(|v| self.texture_layout = v).is_some(),
+            "backend" => GraphicsBackend::from_name(value).map(|v| self.backend = v).is_some(),
+            "software_fallback" => value.parse().map(|v| self.allow_software_fallback = v).is_ok(),
+            "screenshot_scale" => value.parse().map(|v: u32| self.screenshot_scale = v.clamp(1, MAX_SCREENSHOT_SCALE)).is_ok(),
+            _ => return None,
+        };
+        Some(parsed)
+    }
+
+    pub fn to_config(&self) -> String {
+        format!(
+            "shadows = {}\ntexture_filter = {}\nanisotropy = {}\ntexture_layout = {}\nbackend = {}\nsoftware_fallback = {}\nscreenshot_scale = {}\n",
+            self.shadows.name(), self.texture_filter.name(), self.anisotropy, self.texture_layout.name(),
+            self.backend.name(), self.allow_software_fallback, self.screenshot_scale
+        )
+    }
+}
+
+/// Everything stored in the settings file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Settings {
+    pub camera: CameraSettings,
+    pub graphics: GraphicsSettings,
+    /// Enabled resource packs, the first one wins (see `Assets`).
+    pub resource_packs: Vec<String>,
+}
+
+impl Settings {
+    /// Reads `path`, falling back to the defaults for a missing file and for unknown or broken entries.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        match fs::read_to_string(path.as_ref()) {
+            Ok(text) => Self::parse(&text),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        fs::write(path, self.to_config())
+    }
+
+    /// Parses `key = value` lines; `#` starts a comment.
+    pub fn parse(text: &str) -> Self {
+        let mut settings = Self::default();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                Logger::warn(&format!("Ignoring settings line '{}'", line));
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "resource_packs" {
+                settings.resource_packs = value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect();
+                continue;
+            }
+            let parsed = settings.camera.apply(key, value)
+                .or_else(|| settings.graphics.apply(key, value));
+            match parsed {
+                Some(true) => {}
+                Some(false) => Logger::warn(&format!("Invalid value '{}' for setting '{}'", value, key)),
+                None => Logger::warn(&format!("Unknown setting '{}'", key)),
+            }
+        }
+        settings.camera = settings.camera.clamped();
+        settings
+    }
+
+    pub fn to_config(&self) -> String {
+        format!(
+            "{}{}resource_packs = {}\n",
+            self.camera.to_config(),
+            self.graphics.to_config(),
+            self.resource_packs.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::render::texture_manager::TILE_SIZE;
+
+    #[test]
+    fn parse_round_trips() {
+        let settings = Settings {
+            camera: CameraSettings { fov: 90.0, near: 0.05, far: 512.0, render_distance: 12 },
+            graphics: GraphicsSettings {
+                shadows: ShadowQuality::High,
+                texture_filter: TextureFilter::Trilinear,
+                anisotropy: 8,
+                texture_layout: TextureLayout::Layers { tile_size: TILE_SIZE },
+                backend: GraphicsBackend::Vulkan,
+                allow_software_fallback: false,
+                screenshot_scale: 2,
+            },
+            resource_packs: vec!["hd".to_string(), "dark ui".to_string()],
+        };
+        assert_eq!(Settings::parse(&settings.to_config()), settings);
+    }
+
+    #[test]
+    fn parse_keeps_defaults_for_bad_entries_and_clamps() {
+        let settings = Settings::parse("# comment\nfov = wide\nrender_distance = 100\nfar=64\nshadows = ultra\nbogus line\n");
+        assert_eq!(settings.camera.fov, CameraSettings::default().fov);
+        assert_eq!(settings.camera.render_distance, MAX_RENDER_DISTANCE);
+        assert_eq!(settings.camera.far, settings.camera.min_far());
+        assert!(settings.camera.far >= (MAX_RENDER_DISTANCE as f32 * CHUNK_BLOCKS));
+        assert_eq!(settings.graphics.shadows, GraphicsSettings::default().shadows);
+    }
+}
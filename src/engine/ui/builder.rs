@@ -1,8 +1,14 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use glam::{Vec2, Vec4};
+use winit::keyboard::{KeyCode, ModifiersState};
 
-use crate::engine::{UIRenderer, ui::{Container, LayoutType, Rect, SizeMode, Widget}};
+use crate::engine::{InputEvent, UIRenderer, ui::{Container, LayoutType, Rect, SizeMode, Widget}};
+/// Widget tree drawn by `UIRenderer`. The tree is retained between frames: build it when a
+/// screen opens or its contents change, and change widgets with an id in place through
+/// `update` for values that change every frame. A rebuilt tree set with
+/// `UIRenderer::set_ui` takes over the widget state (hover, typed text, focus) by id.
 #[derive(Clone)]
 pub struct UI {
     pub root: Container,
@@ -30,8 +36,131 @@ impl UI {
         self.root.render(renderer, screen_rect);
     }
 
+    /// Clicks move the keyboard focus to the focusable widget under the point, or clear it.
     pub fn handle_click(&self, point: glam::Vec2) -> bool {
-        self.root.handle_click(point)
+        let mut clicked_focusable = false;
+        for widget in self.focusable() {
+            let mut widget = widget.lock().unwrap();
+            let hit = !clicked_focusable && widget.bounds().is_some_and(|rect| rect.contains(point));
+            clicked_focusable |= hit;
+            widget.set_focused(hit);
+        }
+        self.root.handle_click(point) || clicked_focusable
+    }
+
+    /// Whether a widget has keyboard focus, so keys go to it instead of the game.
+    pub fn has_focus(&self) -> bool {
+        self.focused().is_some()
+    }
+
+    pub fn clear_focus(&self) {
+        if let Some(widget) = self.focused() {
+            widget.lock().unwrap().set_focused(false);
+        }
+    }
+
+    /// Moves the focus to the next (or with `backwards` the previous) focusable widget, wrapping around.
+    pub fn focus_next(&self, backwards: bool) {
+        let widgets = self.focusable();
+        if widgets.is_empty() {
+            return;
+        }
+        let current = widgets.iter().position(|widget| widget.lock().unwrap().is_focused());
+        let next = match (current, backwards) {
+            (None, false) => 0,
+            (None, true) => widgets.len() - 1,
+            (Some(i), false) => (i + 1) % widgets.len(),
+            (Some(i), true) => (i + widgets.len() - 1) % widgets.len(),
+        };
+        for (i, widget) in widgets.iter().enumerate() {
+            widget.lock().unwrap().set_focused(i == next);
+        }
+    }
+
+    /// Passes keyboard and text events to the focused widget; Tab cycles the focus.
+    /// Returns whether the UI used the event.
+    pub fn handle_input(&self, event: &InputEvent, modifiers: ModifiersState) -> bool {
+        let Some(widget) = self.focused() else { return false };
+        if let InputEvent::KeyPressed(KeyCode::Tab) = event {
+            self.focus_next(modifiers.shift_key());
+            return true;
+        }
+        let handled = widget.lock().unwrap().handle_input(event, modifiers);
+        handled
+    }
+
+    /// Updates hover state of the widgets for the pointer at `point`.
+    pub fn handle_cursor_moved(&self, point: Vec2) {
+        for widget in self.widgets() {
+            let mut widget = widget.lock().unwrap();
+            let hovered = widget.style().visible && widget.bounds().is_some_and(|rect| rect.contains(point));
+            widget.set_hovered(hovered);
+        }
+    }
+
+    /// Widget with the given id.
+    pub fn find(&self, id: &str) -> Option<Arc<Mutex<dyn Widget>>> {
+        self.widgets().into_iter().find(|widget| {
+            let found = widget.lock().unwrap().id() == Some(id);
+            found
+        })
+    }
+
+    /// Runs `f` on the widget with the given id if it is a `W`, e.g. to change a label
+    /// without rebuilding the tree.
+    pub fn update<W: Widget, R>(&self, id: &str, f: impl FnOnce(&mut W) -> R) -> Option<R> {
+        let widget = self.find(id)?;
+        let mut widget = widget.lock().unwrap();
+        let widget: &mut dyn std::any::Any = &mut *widget;
+        widget.downcast_mut::<W>().map(f)
+    }
+
+    /// Carries widget state over from the tree this one replaces, matching widgets by id.
+    pub fn retain_state(&self, previous: &UI) {
+        let previous: HashMap<String, Arc<Mutex<dyn Widget>>> = previous.widgets()
+            .into_iter()
+            .filter_map(|widget| {
+                let id = widget.lock().unwrap().id()?.to_string();
+                Some((id, widget))
+            })
+            .collect();
+        for widget in self.widgets() {
+            let id = widget.lock().unwrap().id().map(str::to_string);
+            let Some(old) = id.and_then(|id| previous.get(&id)) else { continue };
+            // Both trees may share a widget, it already has its state
+            if Arc::ptr_eq(old, &widget) {
+                continue;
+            }
+            widget.lock().unwrap().retain_state(&*old.lock().unwrap());
+        }
+    }
+
+    fn focused(&self) -> Option<Arc<Mutex<dyn Widget>>> {
+        self.focusable().into_iter().find(|widget| {
+            let focused = widget.lock().unwrap().is_focused();
+            focused
+        })
+    }
+
+    /// Focusable widgets in tree order, which is also the Tab order.
+    fn focusable(&self) -> Vec<Arc<Mutex<dyn Widget>>> {
+        self.widgets().into_iter().filter(|widget| {
+            let focusable = widget.lock().unwrap().is_focusable();
+            focusable
+        }).collect()
+    }
+
+    /// Every widget below the root, in tree order.
+    fn widgets(&self) -> Vec<Arc<Mutex<dyn Widget>>> {
+        fn collect(children: &[Arc<Mutex<dyn Widget>>], out: &mut Vec<Arc<Mutex<dyn Widget>>>) {
+            for child in children {
+                out.push(child.clone());
+                collect(child.lock().unwrap().children(), out);
+            }
+        }
+        let mut widgets = Vec::new();
+        collect(self.root.children(), &mut widgets);
+        widgets
     }
 }
 
@@ -41,4 +170,35 @@ impl UIBuilder {
     pub fn new() -> UI {
         UI::new()
     }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::ui::{Button, Text, TextInput};
+
+    #[test]
+    fn rebuilt_tree_keeps_state_by_id() {
+        let build = |label: &str| UI::new()
+            .add_widget(Text::new(label).with_id("label"))
+            .add_widget(TextInput::new().with_id("name"))
+            .add_widget(TextInput::new());
+
+        let ui = build("first");
+        ui.update("name", |input: &mut TextInput| input.insert("Alex"));
+        ui.focus_next(false);
+        assert_eq!(ui.update("label", |button: &mut Button| button.text.clone()), None);
+
+        let rebuilt = build("second");
+        rebuilt.retain_state(&ui);
+        assert!(rebuilt.has_focus());
+        assert_eq!(rebuilt.update("name", |input: &mut TextInput| input.text.clone()).as_deref(), Some("Alex"));
+        assert_eq!(rebuilt.update("label", |text: &mut Text| text.text.clone()).as_deref(), Some("second"));
+
+        // Widgets without an id start over
+        rebuilt.focus_next(false);
+        let third = build("third");
+        third.retain_state(&rebuilt);
+        assert!(!third.has_focus());
+    }
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::sync::{Arc, Mutex};
 
 use glam::Vec2;
 
-use crate::engine::{UIRenderer, ui::{Button, Panel, Rect, Style, Text, Widget, calculate_layout}};
+use crate::engine::{UIRenderer, ui::{Button, Panel, Rect, Style, Text, TextInput, Widget, calculate_layout}};
 
 #[derive(Debug, Clone, Copy)]
 pub enum LayoutType {
@@ -53,6 +53,10 @@ impl Container {
     pub fn add_container(self, container: Container) -> Self {
         self.add_child(Arc::new(Mutex::new(container)))
     }
+
+    pub fn add_text_input(self, input: TextInput) -> Self {
+        self.add_child(Arc::new(Mutex::new(input)))
+    }
 }
 
 impl Widget for Container {
@@ -77,6 +81,8 @@ impl Widget for Container {
         self.render_children(renderer, content_rect);
     }
 
+    fn children(&self) -> &[Arc<Mutex<dyn Widget>>] { &self.children }
+
     fn handle_click(&self, point: Vec2) -> bool {
         if !self.style.visible { return false; }
 
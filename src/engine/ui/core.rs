@@ -31,6 +31,8 @@ pub struct Style {
     pub padding: Vec2,
     pub color: Vec4,
     pub visible: bool,
+    /// Stable name of the widget, the same in every rebuild of the UI; see `Widget::with_id`.
+    pub id: Option<String>,
 }
 
 impl Default for Style {
@@ -44,6 +46,7 @@ impl Default for Style {
             padding: Vec2::ZERO,
             color: Vec4::new(1.0, 1.0, 1.0, 1.0),
             visible: true,
+            id: None,
         }
     }
 }
@@ -1,11 +1,13 @@
 pub mod core;
 pub mod widgets;
+pub mod text_input;
 pub mod container;
 pub mod builder;
 pub mod colors;
 
 pub use core::*;
 pub use widgets::*;
+pub use text_input::*;
 pub use container::*;
 pub use builder::*;
 pub use colors::*;
\ No newline at end of file
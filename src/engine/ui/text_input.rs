@@ -0,0 +1,466 @@
+use std::any::Any;
+use std::ops::Range;
+
+use glam::{Vec2, Vec4};
+use winit::keyboard::{KeyCode, ModifiersState};
+
+use crate::engine::{InputEvent, UIRenderer, ui::{Rect, Style, Widget, calculate_layout}};
+
+/// Space between the box edge and the text.
+const TEXT_PADDING: f32 = 5.0;
+
+/// Single-line text field. Click or Tab to focus it, then type; text comes from
+/// `InputEvent::Text` and the IME events, so any keyboard layout and input method works.
+/// Editing keys: arrows (Ctrl for words, Shift to select), Home/End, Backspace/Delete
+/// (Ctrl for words), Ctrl+A, Enter to submit and Escape to leave the field.
+pub struct TextInput {
+    pub style: Style,
+    pub text: String,
+    pub placeholder: String,
+    pub text_color: Vec4,
+    pub placeholder_color: Vec4,
+    pub selection_color: Vec4,
+    pub focus_color: Vec4,
+    pub scale: f32,
+    /// Most characters the field accepts.
+    pub max_length: Option<usize>,
+    pub on_submit: Option<Box<dyn Fn(&str)>>,
+    /// Caret position in characters.
+    caret: usize,
+    /// Other end of the selection, in characters; the selection runs between it and the caret.
+    anchor: Option<usize>,
+    /// Text the IME is composing, drawn at the caret until it is committed.
+    preedit: String,
+    focused: bool,
+    /// First visible character, so the caret stays in view when the text is wider than the box.
+    scroll: usize,
+    rect: Rect,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self {
+            style: Style {
+                size: Vec2::new(200.0, 30.0),
+                color: Vec4::new(0.1, 0.1, 0.1, 0.9),
+                ..Style::default()
+            },
+            text: String::new(),
+            placeholder: String::new(),
+            text_color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+            placeholder_color: Vec4::new(0.6, 0.6, 0.6, 1.0),
+            selection_color: Vec4::new(0.2, 0.4, 0.9, 0.6),
+            focus_color: Vec4::new(0.9, 0.9, 0.9, 1.0),
+            scale: 1.0,
+            max_length: None,
+            on_submit: None,
+            caret: 0,
+            anchor: None,
+            preedit: String::new(),
+            focused: false,
+            scroll: 0,
+            rect: Rect::new(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn with_style<F>(mut self, f: F) -> Self
+    where F: FnOnce(&mut Style) {
+        f(&mut self.style);
+        self
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.set_text(text);
+        self
+    }
+
+    pub fn with_placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+
+    pub fn with_text_color(mut self, color: Vec4) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Called with the text when Enter is pressed in the field.
+    pub fn on_submit<F>(mut self, callback: F) -> Self
+    where F: Fn(&str) + 'static {
+        self.on_submit = Some(Box::new(callback));
+        self
+    }
+
+    /// Replaces the text, with the caret at its end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = match self.max_length {
+            Some(max) => text.chars().take(max).collect(),
+            None => text.to_string(),
+        };
+        self.caret = self.len();
+        self.anchor = None;
+        self.scroll = 0;
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// Selected characters, if any.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        (anchor != self.caret).then(|| anchor.min(self.caret)..anchor.max(self.caret))
+    }
+
+    pub fn selected_text(&self) -> &str {
+        match self.selection() {
+            Some(range) => &self.text[self.byte_index(range.start)..self.byte_index(range.end)],
+            None => "",
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.caret = self.len();
+    }
+
+    /// Types `text` at the caret, replacing the selection. Stops at `max_length`.
+    pub fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        let room = self.max_length.map_or(usize::MAX, |max| max.saturating_sub(self.len()));
+        let text: String = text.chars().filter(|ch| !ch.is_control()).take(room).collect();
+        let at = self.byte_index(self.caret);
+        self.text.insert_str(at, &text);
+        self.caret += text.chars().count();
+    }
+
+    /// Deletes the selection, or the character (or word) before the caret.
+    pub fn backspace(&mut self, word: bool) {
+        if self.delete_selection() {
+            return;
+        }
+        let start = if word { self.word_start(self.caret) } else { self.caret.saturating_sub(1) };
+        self.delete_range(start..self.caret);
+    }
+
+    /// Deletes the selection, or the character (or word) after the caret.
+    pub fn delete(&mut self, word: bool) {
+        if self.delete_selection() {
+            return;
+        }
+        let end = if word { self.word_end(self.caret) } else { (self.caret + 1).min(self.len()) };
+        self.delete_range(self.caret..end);
+    }
+
+    /// Moves the caret one character or word left or right, extending the selection with `select`.
+    pub fn move_caret(&mut self, right: bool, word: bool, select: bool) {
+        let target = match (right, word) {
+            (false, false) => self.caret.saturating_sub(1),
+            (true, false) => (self.caret + 1).min(self.len()),
+            (false, true) => self.word_start(self.caret),
+            (true, true) => self.word_end(self.caret),
+        };
+        // Without Shift an arrow first collapses the selection to its side
+        let target = match self.selection() {
+            Some(range) if !select && !word => if right { range.end } else { range.start },
+            _ => target,
+        };
+        self.move_to(target, select);
+    }
+
+    /// Moves the caret to character `position`, extending the selection with `select`.
+    pub fn move_to(&mut self, position: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+        self.caret = position.min(self.len());
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self, chars: usize) -> usize {
+        self.text.char_indices().nth(chars).map_or(self.text.len(), |(i, _)| i)
+    }
+
+    fn delete_range(&mut self, range: Range<usize>) {
+        let (start, end) = (self.byte_index(range.start), self.byte_index(range.end));
+        self.text.replace_range(start..end, "");
+        self.caret = range.start;
+        self.anchor = None;
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some(range) => {
+                self.delete_range(range);
+                true
+            }
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    /// Start of the word before `from`, skipping the spaces in between.
+    fn word_start(&self, from: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = from;
+        while i > 0 && chars[i - 1].is_whitespace() {
+            i -= 1;
+        }
+        while i > 0 && !chars[i - 1].is_whitespace() {
+            i -= 1;
+        }
+        i
+    }
+
+    /// End of the word after `from`, skipping the spaces in between.
+    fn word_end(&self, from: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = from;
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+        while i < chars.len() && !chars[i].is_whitespace() {
+            i += 1;
+        }
+        i
+    }
+
+    fn handle_key(&mut self, key: KeyCode, modifiers: ModifiersState) {
+        let (word, select) = (modifiers.control_key(), modifiers.shift_key());
+        match key {
+            KeyCode::ArrowLeft => self.move_caret(false, word, select),
+            KeyCode::ArrowRight => self.move_caret(true, word, select),
+            KeyCode::Home => self.move_to(0, select),
+            KeyCode::End => self.move_to(self.len(), select),
+            KeyCode::Backspace => self.backspace(word),
+            KeyCode::Delete => self.delete(word),
+            KeyCode::KeyA if modifiers.control_key() => self.select_all(),
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                if let Some(callback) = &self.on_submit {
+                    callback(&self.text);
+                }
+            }
+            KeyCode::Escape => self.set_focused(false),
+            _ => {}
+        }
+    }
+}
+
+impl Widget for TextInput {
+    fn style(&self) -> &Style { &self.style }
+    fn style_mut(&mut self) -> &mut Style { &mut self.style }
+
+    fn render(&mut self, renderer: &mut UIRenderer, rect: Rect) {
+        if !self.style.visible { return; }
+
+        let layout_rect = calculate_layout(&self.style, rect, self.content_size(renderer));
+        self.rect = layout_rect;
+        let pos = Vec2::new(layout_rect.x, layout_rect.y);
+        let size = Vec2::new(layout_rect.width, layout_rect.height);
+        renderer.render_rect(pos, size, self.style.color);
+        if self.focused {
+            let border = self.focus_color;
+            renderer.render_rect(pos, Vec2::new(size.x, 1.0), border);
+            renderer.render_rect(pos + Vec2::new(0.0, size.y - 1.0), Vec2::new(size.x, 1.0), border);
+            renderer.render_rect(pos, Vec2::new(1.0, size.y), border);
+            renderer.render_rect(pos + Vec2::new(size.x - 1.0, 0.0), Vec2::new(1.0, size.y), border);
+        }
+
+        let line_height = renderer.measure_text("", self.scale).y;
+        let text_pos = Vec2::new(pos.x + TEXT_PADDING, pos.y + (size.y - line_height) * 0.5);
+        let inner_width = (size.x - TEXT_PADDING * 2.0).max(0.0);
+
+        if self.text.is_empty() && self.preedit.is_empty() {
+            if !self.placeholder.is_empty() {
+                renderer.render_text(&self.placeholder, text_pos, self.scale, self.placeholder_color);
+            }
+            if self.focused {
+                renderer.render_rect(text_pos, Vec2::new(self.scale.max(1.0), line_height), self.text_color);
+            }
+            return;
+        }
+
+        // What is shown: the text with the IME composition at the caret
+        let mut chars: Vec<char> = self.text.chars().collect();
+        let preedit_len = if self.focused { self.preedit.chars().count() } else { 0 };
+        if preedit_len > 0 {
+            chars.splice(self.caret..self.caret, self.preedit.chars());
+        }
+        let caret = self.caret + preedit_len;
+        let width = |range: Range<usize>| renderer.measure_text(&chars[range].iter().collect::<String>(), self.scale).x;
+
+        // Scroll just enough to keep the caret inside the box
+        self.scroll = self.scroll.min(caret);
+        while self.scroll < caret && width(self.scroll..caret) > inner_width {
+            self.scroll += 1;
+        }
+        let mut end = caret;
+        while end < chars.len() && width(self.scroll..end + 1) <= inner_width {
+            end += 1;
+        }
+        let offsets: Vec<f32> = (self.scroll..=end).map(|i| text_pos.x + width(self.scroll..i)).collect();
+        let x_of = |i: usize| offsets[i.clamp(self.scroll, end) - self.scroll];
+
+        if let Some(selection) = self.selection() {
+            // Selection is in text positions, shift the part after the caret past the preedit
+            let shown = |i: usize| if i > self.caret { i + preedit_len } else { i };
+            let (x0, x1) = (x_of(shown(selection.start)), x_of(shown(selection.end)));
+            renderer.render_rect(Vec2::new(x0, text_pos.y), Vec2::new(x1 - x0, line_height), self.selection_color);
+        }
+        let visible: String = chars[self.scroll..end].iter().collect();
+        renderer.render_text(&visible, text_pos, self.scale, self.text_color);
+        if preedit_len > 0 {
+            let (x0, x1) = (x_of(self.caret), x_of(caret));
+            renderer.render_rect(Vec2::new(x0, text_pos.y + line_height - 1.0), Vec2::new(x1 - x0, 1.0), self.text_color);
+        }
+        if self.focused {
+            renderer.render_rect(Vec2::new(x_of(caret), text_pos.y), Vec2::new(self.scale.max(1.0), line_height), self.text_color);
+        }
+    }
+
+    fn handle_click(&self, point: Vec2) -> bool {
+        self.style.visible && self.rect.contains(point)
+    }
+
+    fn content_size(&self, renderer: &UIRenderer) -> Vec2 {
+        let text = if self.text.is_empty() { &self.placeholder } else { &self.text };
+        renderer.measure_text(text, self.scale) + Vec2::splat(TEXT_PADDING * 2.0)
+    }
+
+    fn is_focusable(&self) -> bool { self.style.visible }
+    fn is_focused(&self) -> bool { self.focused }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        if !focused {
+            self.anchor = None;
+            self.preedit.clear();
+        }
+    }
+
+    fn bounds(&self) -> Option<Rect> { Some(self.rect) }
+
+    /// Typed text wins over the text the rebuilt field was created with;
+    /// set it through `UI::update` to replace it.
+    fn retain_state(&mut self, previous: &dyn Widget) {
+        if let Some(previous) = (previous as &dyn Any).downcast_ref::<TextInput>() {
+            self.text = previous.text.clone();
+            self.caret = previous.caret;
+            self.anchor = previous.anchor;
+            self.preedit = previous.preedit.clone();
+            self.focused = previous.focused;
+            self.scroll = previous.scroll;
+            self.rect = previous.rect;
+        }
+    }
+
+    fn handle_input(&mut self, event: &InputEvent, modifiers: ModifiersState) -> bool {
+        match event {
+            InputEvent::KeyPressed(key) => {
+                self.handle_key(*key, modifiers);
+                // Keys typed into the field are not game controls
+                true
+            }
+            InputEvent::Text(text) => {
+                // Ctrl shortcuts are handled by key, their text is not typed
+                if !modifiers.control_key() || modifiers.alt_key() {
+                    self.insert(text);
+                }
+                true
+            }
+            InputEvent::ImePreedit(text, _) => {
+                self.preedit = text.clone();
+                true
+            }
+            InputEvent::ImeCommit(text) => {
+                self.preedit.clear();
+                self.insert(text);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edits_unicode_text_at_the_caret() {
+        let mut input = TextInput::new().with_text("Привет мир");
+        assert_eq!(input.caret(), 10);
+
+        input.move_caret(false, true, false);
+        assert_eq!(input.caret(), 7);
+        input.insert("весь ");
+        assert_eq!(input.text, "Привет весь мир");
+
+        input.backspace(true);
+        assert_eq!(input.text, "Привет мир");
+        input.move_to(0, false);
+        input.delete(false);
+        assert_eq!(input.text, "ривет мир");
+
+        input.set_text("héllo");
+        input.move_caret(false, false, false);
+        input.backspace(false);
+        assert_eq!((input.text.as_str(), input.caret()), ("hélo", 3));
+    }
+
+    #[test]
+    fn selection_is_replaced_by_typing() {
+        let mut input = TextInput::new().with_text("one two three").with_max_length(14);
+        input.move_caret(false, true, true);
+        assert_eq!(input.selected_text(), "three");
+        input.insert("2");
+        assert_eq!(input.text, "one two 2");
+
+        input.select_all();
+        assert_eq!(input.selected_text(), "one two 2");
+        input.move_caret(false, false, false);
+        assert_eq!((input.caret(), input.selection()), (0, None));
+
+        input.insert("longer than the limit");
+        assert_eq!(input.text, "longeone two 2");
+    }
+
+    #[test]
+    fn keys_and_text_events_edit_the_field() {
+        let submitted = std::rc::Rc::new(std::cell::RefCell::new(String::new()));
+        let sink = submitted.clone();
+        let mut input = TextInput::new().on_submit(move |text| *sink.borrow_mut() = text.to_string());
+        let none = ModifiersState::empty();
+
+        assert!(input.handle_input(&InputEvent::Text("ab".to_string()), none));
+        assert!(input.handle_input(&InputEvent::ImePreedit("日".to_string(), None), none));
+        assert_eq!(input.text, "ab");
+        assert!(input.handle_input(&InputEvent::ImeCommit("日本".to_string()), none));
+        input.handle_input(&InputEvent::KeyPressed(KeyCode::KeyA), ModifiersState::CONTROL);
+        input.handle_input(&InputEvent::Text("a".to_string()), ModifiersState::CONTROL);
+        assert_eq!(input.selected_text(), "ab日本");
+
+        input.handle_input(&InputEvent::KeyPressed(KeyCode::Backspace), none);
+        input.handle_input(&InputEvent::Text("ok".to_string()), none);
+        input.handle_input(&InputEvent::KeyPressed(KeyCode::Enter), none);
+        assert_eq!(*submitted.borrow(), "ok");
+        assert!(!input.handle_input(&InputEvent::MouseMotion(1.0, 1.0), none));
+    }
+}
@@ -1,12 +1,49 @@
 use glam::{Vec2, Vec4};
 
-use crate::engine::{UIRenderer, ui::{Rect, Style, calculate_layout}};
-pub trait Widget {
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+use winit::keyboard::ModifiersState;
+
+use crate::engine::{InputEvent, UIRenderer, ui::{Rect, Style, calculate_layout}};
+pub trait Widget: Any {
     fn style(&self) -> &Style;
     fn style_mut(&mut self) -> &mut Style;
     fn render(&mut self, renderer: &mut UIRenderer, rect: Rect);
     fn handle_click(&self, point: Vec2) -> bool { false }
-    fn content_size(&self) -> Vec2 { Vec2::ZERO }
+    /// Size the widget needs for `SizeMode::FitContent`; `renderer` measures text.
+    fn content_size(&self, _renderer: &UIRenderer) -> Vec2 { Vec2::ZERO }
+
+    /// Widgets inside this one, for walking the tree (focus, lookups).
+    fn children(&self) -> &[Arc<Mutex<dyn Widget>>] { &[] }
+
+    // Keyboard focus, managed by `UI`. Only focusable widgets get keyboard and text events.
+
+    fn is_focusable(&self) -> bool { false }
+    fn is_focused(&self) -> bool { false }
+    fn set_focused(&mut self, _focused: bool) {}
+    /// Where the widget was last drawn, for focusing it with a click.
+    fn bounds(&self) -> Option<Rect> { None }
+    /// Keyboard and text events while focused; returns whether the event was used.
+    fn handle_input(&mut self, _event: &InputEvent, _modifiers: ModifiersState) -> bool { false }
+
+    // Retained state. The UI keeps its tree between frames and widgets with an id can be
+    // found and changed in place; when the tree is rebuilt, they take over the state of the
+    // widget with the same id in the old one.
+
+    fn id(&self) -> Option<&str> { self.style().id.as_deref() }
+
+    fn with_id(mut self, id: &str) -> Self
+    where Self: Sized {
+        self.style_mut().id = Some(id.to_string());
+        self
+    }
+
+    /// The pointer moved onto (or off) the widget's `bounds`.
+    fn set_hovered(&mut self, _hovered: bool) {}
+    /// Copies state the user changed (hover, typed text, caret, focus) from the widget with
+    /// the same id in the previous tree.
+    fn retain_state(&mut self, _previous: &dyn Widget) {}
 }
 
 pub struct Text {
@@ -42,12 +79,12 @@ impl Widget for Text {
 
     fn render(&mut self, renderer: &mut UIRenderer, rect: Rect) {
         if !self.style.visible || self.style.color.w <= 0.0 { return; }
-        let layout_rect = calculate_layout(&self.style, rect, self.content_size());
+        let layout_rect = calculate_layout(&self.style, rect, self.content_size(renderer));
         renderer.render_text(&self.text, Vec2::new(layout_rect.x, layout_rect.y), self.scale, self.style.color);
     }
 
-    fn content_size(&self) -> Vec2 {
-        Vec2::new(self.text.len() as f32 * 8.0 * self.scale, 8.0 * self.scale)
+    fn content_size(&self, renderer: &UIRenderer) -> Vec2 {
+        renderer.measure_text(&self.text, self.scale)
     }
 }
 
@@ -89,6 +126,7 @@ pub struct Button {
     pub scale: f32,
     pub on_click: Option<Box<dyn Fn()>>,
     pub rect: Rect,
+    hovered: bool,
 }
 
 impl Button {
@@ -103,6 +141,7 @@ impl Button {
             scale: 1.0,
             on_click: None,
             rect: Rect::new(0.0, 0.0, 0.0, 0.0),
+            hovered: false,
         }
     }
 
@@ -136,12 +175,18 @@ impl Widget for Button {
     fn render(&mut self, renderer: &mut UIRenderer, rect: Rect) {
         if !self.style.visible { return; }
         
-        let layout_rect = calculate_layout(&self.style, rect, self.content_size());
+        let layout_rect = calculate_layout(&self.style, rect, self.content_size(renderer));
         self.rect = layout_rect;
         
-        renderer.render_rect(Vec2::new(layout_rect.x, layout_rect.y), Vec2::new(layout_rect.width, layout_rect.height), self.style.color);
+        // Подсвечиваем кнопку под курсором
+        let color = if self.hovered {
+            (self.style.color * 1.3).min(Vec4::ONE).with_w(self.style.color.w)
+        } else {
+            self.style.color
+        };
+        renderer.render_rect(Vec2::new(layout_rect.x, layout_rect.y), Vec2::new(layout_rect.width, layout_rect.height), color);
         
-        let text_size = Vec2::new(self.text.len() as f32 * 8.0 * self.scale, 8.0 * self.scale);
+        let text_size = renderer.measure_text(&self.text, self.scale);
         let text_pos = Vec2::new(
             layout_rect.x + (layout_rect.width - text_size.x) * 0.5,
             layout_rect.y + (layout_rect.height - text_size.y) * 0.5
@@ -160,8 +205,19 @@ impl Widget for Button {
         false
     }
 
-    fn content_size(&self) -> Vec2 {
-        Vec2::new(self.text.len() as f32 * 8.0 * self.scale + 20.0, 8.0 * self.scale + 10.0)
+    fn content_size(&self, renderer: &UIRenderer) -> Vec2 {
+        renderer.measure_text(&self.text, self.scale) + Vec2::new(20.0, 10.0)
+    }
+
+    fn bounds(&self) -> Option<Rect> { Some(self.rect) }
+    fn set_hovered(&mut self, hovered: bool) { self.hovered = hovered; }
+
+    fn retain_state(&mut self, previous: &dyn Widget) {
+        if let Some(previous) = (previous as &dyn Any).downcast_ref::<Button>() {
+            self.hovered = previous.hovered;
+            // Bounds too, so the hover test works before the next render
+            self.rect = previous.rect;
+        }
     }
 }
 
@@ -193,12 +249,12 @@ impl Widget for Image {
 
     fn render(&mut self, renderer: &mut UIRenderer, rect: Rect) {
         if !self.style.visible { return; }
-        let layout_rect = calculate_layout(&self.style, rect, self.content_size());
+        let layout_rect = calculate_layout(&self.style, rect, self.content_size(renderer));
         self.rect = layout_rect;
         renderer.render_image(&self.texture_name, Vec2::new(layout_rect.x, layout_rect.y), Vec2::new(layout_rect.width, layout_rect.height));
     }
 
-    fn content_size(&self) -> Vec2 {
+    fn content_size(&self, _renderer: &UIRenderer) -> Vec2 {
         // Default size, can be overridden by style
         Vec2::new(64.0, 64.0)
     }
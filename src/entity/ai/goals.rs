@@ -0,0 +1,268 @@
+use glam::Vec3;
+use rand::Rng;
+
+use crate::entity::ai::navigator::{node_at, Navigator};
+use crate::entity::ai::pathfinding::{is_walkable, Node, PathConfig};
+use crate::entity::entity::EntityBody;
+use crate::world::world::World;
+
+/// What a goal gets to look at and steer each tick.
+pub struct GoalContext<'a> {
+    pub world: &'a World,
+    pub body: &'a EntityBody,
+    pub navigator: &'a mut Navigator,
+    pub path_config: &'a PathConfig,
+}
+
+/// One behaviour of a mob. The mob runs the highest priority goal that wants to run.
+pub trait Goal {
+    fn get_name(&self) -> &'static str;
+    /// Higher runs first.
+    fn priority(&self) -> u8;
+    fn can_start(&mut self, context: &GoalContext) -> bool;
+    fn should_continue(&mut self, context: &GoalContext) -> bool {
+        self.can_start(context)
+    }
+    fn start(&mut self, _context: &mut GoalContext) {}
+    fn tick(&mut self, context: &mut GoalContext);
+    fn stop(&mut self, context: &mut GoalContext) {
+        context.navigator.stop();
+    }
+}
+
+/// Closest walkable node to `pos` within a few blocks up or down.
+fn walkable_near(world: &World, pos: Node, config: &PathConfig) -> Option<Node> {
+    for dy in [0, 1, -1, 2, -2, 3, -3] {
+        let candidate = (pos.0, pos.1 + dy, pos.2);
+        if is_walkable(world, candidate, config) {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+fn distance_to_player(context: &GoalContext) -> Option<f32> {
+    context.world.player_position.map(|player| player.distance(context.body.position))
+}
+
+/// Occasionally walks to a random spot nearby.
+pub struct WanderGoal {
+    /// Chance per tick of picking a new spot while idle.
+    pub chance: f32,
+    pub radius: i32,
+}
+
+impl Goal for WanderGoal {
+    fn get_name(&self) -> &'static str { "wander" }
+    fn priority(&self) -> u8 { 1 }
+
+    fn can_start(&mut self, context: &GoalContext) -> bool {
+        context.navigator.is_idle() && rand::rng().random::<f32>() < self.chance
+    }
+
+    fn should_continue(&mut self, context: &GoalContext) -> bool {
+        !context.navigator.is_idle()
+    }
+
+    fn start(&mut self, context: &mut GoalContext) {
+        let mut rng = rand::rng();
+        let origin = node_at(context.body.position);
+        for _ in 0..10 {
+            let pos = (
+                origin.0 + rng.random_range(-self.radius..=self.radius),
+                origin.1,
+                origin.2 + rng.random_range(-self.radius..=self.radius),
+            );
+            if let Some(target) = walkable_near(context.world, pos, context.path_config) {
+                if context.navigator.move_to(context.world, context.body, target, context.path_config) {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn tick(&mut self, _context: &mut GoalContext) {}
+}
+
+/// Walks towards the player while they are within `range`, stopping `stop_distance` short.
+pub struct FollowPlayerGoal {
+    pub range: f32,
+    pub stop_distance: f32,
+    /// Ticks between path updates, the player keeps moving.
+    pub repath_interval: u32,
+    ticks: u32,
+}
+
+impl FollowPlayerGoal {
+    pub fn new(range: f32, stop_distance: f32) -> Self {
+        Self { range, stop_distance, repath_interval: 20, ticks: 0 }
+    }
+}
+
+impl Goal for FollowPlayerGoal {
+    fn get_name(&self) -> &'static str { "follow_player" }
+    fn priority(&self) -> u8 { 5 }
+
+    fn can_start(&mut self, context: &GoalContext) -> bool {
+        distance_to_player(context).map_or(false, |d| d <= self.range && d > self.stop_distance)
+    }
+
+    fn start(&mut self, _context: &mut GoalContext) {
+        self.ticks = 0;
+    }
+
+    fn tick(&mut self, context: &mut GoalContext) {
+        let Some(player) = context.world.player_position else { return };
+        if self.ticks % self.repath_interval == 0 {
+            if let Some(target) = walkable_near(context.world, node_at(player), context.path_config) {
+                context.navigator.move_to(context.world, context.body, target, context.path_config);
+            }
+        }
+        self.ticks += 1;
+    }
+}
+
+/// Runs away from the player when they come closer than `range`.
+pub struct FleePlayerGoal {
+    pub range: f32,
+    /// How far past its current spot the mob runs.
+    pub distance: f32,
+}
+
+impl Goal for FleePlayerGoal {
+    fn get_name(&self) -> &'static str { "flee_player" }
+    fn priority(&self) -> u8 { 10 }
+
+    fn can_start(&mut self, context: &GoalContext) -> bool {
+        distance_to_player(context).map_or(false, |d| d < self.range)
+    }
+
+    fn should_continue(&mut self, context: &GoalContext) -> bool {
+        !context.navigator.is_idle() || self.can_start(context)
+    }
+
+    fn tick(&mut self, context: &mut GoalContext) {
+        if !context.navigator.is_idle() {
+            return;
+        }
+        let Some(player) = context.world.player_position else { return };
+        let mut away = context.body.position - player;
+        away.y = 0.0;
+        let away = if away.length_squared() > 0.0 { away.normalize() } else { Vec3::X };
+        let spot = context.body.position + away * self.distance;
+        if let Some(target) = walkable_near(context.world, node_at(spot), context.path_config) {
+            context.navigator.move_to(context.world, context.body, target, context.path_config);
+        }
+    }
+}
+
+/// Picks and runs goals by priority.
+pub struct GoalSelector {
+    goals: Vec<Box<dyn Goal>>,
+    running: Option<usize>,
+}
+
+impl GoalSelector {
+    pub fn new() -> Self {
+        Self { goals: Vec::new(), running: None }
+    }
+
+    pub fn with_goal(mut self, goal: Box<dyn Goal>) -> Self {
+        self.goals.push(goal);
+        self.goals.sort_by(|a, b| b.priority().cmp(&a.priority()));
+        self
+    }
+
+    pub fn get_running(&self) -> Option<&'static str> {
+        self.running.map(|index| self.goals[index].get_name())
+    }
+
+    pub fn tick(&mut self, context: &mut GoalContext) {
+        // Goals are sorted by priority, so only the ones before the running goal can interrupt it
+        let candidates = self.running.unwrap_or(self.goals.len());
+        let mut next = None;
+        for index in 0..candidates {
+            if self.goals[index].can_start(context) {
+                next = Some(index);
+                break;
+            }
+        }
+
+        if next.is_none() {
+            if let Some(running) = self.running {
+                if self.goals[running].should_continue(context) {
+                    next = Some(running);
+                }
+            }
+        }
+
+        if next != self.running {
+            if let Some(running) = self.running {
+                self.goals[running].stop(context);
+            }
+            if let Some(index) = next {
+                self.goals[index].start(context);
+            }
+            self.running = next;
+        }
+
+        if let Some(running) = self.running {
+            self.goals[running].tick(context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn selector() -> GoalSelector {
+        GoalSelector::new()
+            .with_goal(Box::new(WanderGoal { chance: 0.0, radius: 4 }))
+            .with_goal(Box::new(FollowPlayerGoal::new(24.0, 1.5)))
+            .with_goal(Box::new(FleePlayerGoal { range: 4.0, distance: 8.0 }))
+    }
+
+    fn tick(goals: &mut GoalSelector, world: &World, body: &EntityBody, navigator: &mut Navigator) {
+        let config = PathConfig { height: 1, ..PathConfig::default() };
+        goals.tick(&mut GoalContext { world, body, navigator, path_config: &config });
+    }
+
+    #[test]
+    fn idle_without_a_player() {
+        let world = World::flat(2, "grass");
+        let body = EntityBody::new(Vec3::new(10.5, 1.0, 10.5), Vec3::splat(0.5));
+        let mut navigator = Navigator::new();
+        let mut goals = selector();
+        tick(&mut goals, &world, &body, &mut navigator);
+        assert_eq!(goals.get_running(), None);
+        assert!(navigator.is_idle());
+    }
+
+    #[test]
+    fn follows_a_distant_player() {
+        let mut world = World::flat(2, "grass");
+        world.player_position = Some(Vec3::new(20.5, 1.0, 10.5));
+        let body = EntityBody::new(Vec3::new(10.5, 1.0, 10.5), Vec3::splat(0.5));
+        let mut navigator = Navigator::new();
+        let mut goals = selector();
+        tick(&mut goals, &world, &body, &mut navigator);
+        assert_eq!(goals.get_running(), Some("follow_player"));
+        assert_eq!(navigator.get_target(), Some((20, 1, 10)));
+    }
+
+    #[test]
+    fn flee_interrupts_follow() {
+        let mut world = World::flat(2, "grass");
+        world.player_position = Some(Vec3::new(20.5, 1.0, 10.5));
+        let body = EntityBody::new(Vec3::new(10.5, 1.0, 10.5), Vec3::splat(0.5));
+        let mut navigator = Navigator::new();
+        let mut goals = selector();
+        tick(&mut goals, &world, &body, &mut navigator);
+
+        world.player_position = Some(Vec3::new(12.5, 1.0, 10.5));
+        tick(&mut goals, &world, &body, &mut navigator);
+        assert_eq!(goals.get_running(), Some("flee_player"));
+        assert!(navigator.get_target().unwrap().0 < 10);
+    }
+}
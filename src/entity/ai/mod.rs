@@ -0,0 +1,3 @@
+pub mod pathfinding;
+pub mod navigator;
+pub mod goals;
@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+use glam::Vec3;
+
+use crate::entity::ai::pathfinding::{find_path, Node, PathConfig};
+use crate::entity::entity::EntityBody;
+use crate::world::world::World;
+
+/// Vertical speed of a jump; clears a bit more than one block under `physics::GRAVITY`.
+const JUMP_VELOCITY: f32 = 7.5;
+/// Horizontal distance at which a path node counts as reached.
+const REACH_DISTANCE: f32 = 0.3;
+/// Ticks without getting closer to the next node before the path is dropped.
+const STUCK_TICKS: u32 = 40;
+
+/// Follows a path computed by `find_path`, one node at a time.
+pub struct Navigator {
+    path: VecDeque<Node>,
+    target: Option<Node>,
+    closest_distance: f32,
+    stuck_ticks: u32,
+}
+
+pub fn node_at(position: Vec3) -> Node {
+    (position.x.floor() as i32, position.y.round() as i32, position.z.floor() as i32)
+}
+
+impl Navigator {
+    pub fn new() -> Self {
+        Self {
+            path: VecDeque::new(),
+            target: None,
+            closest_distance: f32::MAX,
+            stuck_ticks: 0,
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    pub fn get_target(&self) -> Option<Node> {
+        self.target
+    }
+
+    /// Plans a path from where the body stands to `target`; keeps the old path if none is found.
+    pub fn move_to(&mut self, world: &World, body: &EntityBody, target: Node, config: &PathConfig) -> bool {
+        match find_path(world, node_at(body.position), target, config) {
+            Some(path) => {
+                self.path = path.into();
+                self.target = Some(target);
+                self.closest_distance = f32::MAX;
+                self.stuck_ticks = 0;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.path.clear();
+        self.target = None;
+    }
+
+    /// Sets the body's horizontal velocity towards the next node and jumps onto higher ones.
+    pub fn steer(&mut self, body: &mut EntityBody, speed: f32) {
+        let Some(next) = self.path.front().copied() else {
+            self.target = None;
+            return;
+        };
+        let goal = Vec3::new(next.0 as f32 + 0.5, next.1 as f32, next.2 as f32 + 0.5);
+        let mut to_goal = goal - body.position;
+        to_goal.y = 0.0;
+        let distance = to_goal.length();
+
+        if distance < REACH_DISTANCE && (body.position.y - goal.y).abs() < 0.5 {
+            self.path.pop_front();
+            self.closest_distance = f32::MAX;
+            self.stuck_ticks = 0;
+            return;
+        }
+
+        if distance < self.closest_distance - 0.01 {
+            self.closest_distance = distance;
+            self.stuck_ticks = 0;
+        } else {
+            self.stuck_ticks += 1;
+            if self.stuck_ticks > STUCK_TICKS {
+                self.stop();
+                return;
+            }
+        }
+
+        let direction = if distance > 0.0 { to_goal / distance } else { Vec3::ZERO };
+        body.velocity.x = direction.x * speed;
+        body.velocity.z = direction.z * speed;
+        if body.on_ground && goal.y > body.position.y + 0.5 {
+            body.velocity.y = JUMP_VELOCITY;
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::entity::physics::is_solid_at;
+use crate::world::world::World;
+
+/// A block position a mob can stand in (its feet), on top of a solid block.
+pub type Node = (i32, i32, i32);
+
+const HORIZONTAL: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+// Costs are in tenths of a block walked
+const WALK_COST: u32 = 10;
+const CLIMB_COST: u32 = 5;
+const FALL_COST: u32 = 5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PathConfig {
+    /// Body height in blocks, rounded up.
+    pub height: i32,
+    /// How many blocks the mob can climb in one move (1 = a jump).
+    pub step_height: i32,
+    /// Highest drop the mob is willing to take.
+    pub max_fall: i32,
+    /// Search budget; the search gives up after expanding this many nodes.
+    pub max_nodes: usize,
+}
+
+impl Default for PathConfig {
+    fn default() -> Self {
+        Self {
+            height: 2,
+            step_height: 1,
+            max_fall: 3,
+            max_nodes: 2000,
+        }
+    }
+}
+
+fn offset(pos: Node, dx: i32, dy: i32, dz: i32) -> Node {
+    (pos.0 + dx, pos.1 + dy, pos.2 + dz)
+}
+
+/// Mobs avoid walking into lava.
+fn is_passable(world: &World, pos: Node) -> bool {
+    if is_solid_at(world, pos) {
+        return false;
+    }
+    world.get_block(pos).map_or(true, |block| block.get_id() != "lava")
+}
+
+/// The whole body fits at `pos`.
+fn has_room(world: &World, pos: Node, height: i32) -> bool {
+    (0..height).all(|dy| is_passable(world, offset(pos, 0, dy, 0)))
+}
+
+pub fn is_walkable(world: &World, pos: Node, config: &PathConfig) -> bool {
+    is_solid_at(world, offset(pos, 0, -1, 0)) && has_room(world, pos, config.height)
+}
+
+/// Moves reachable from `node`: walking, jumping up to `step_height` and dropping up to `max_fall`.
+fn neighbours(world: &World, node: Node, config: &PathConfig) -> Vec<(Node, u32)> {
+    let mut result = Vec::new();
+    for (dx, dz) in HORIZONTAL {
+        let side = offset(node, dx, 0, dz);
+        if is_walkable(world, side, config) {
+            result.push((side, WALK_COST));
+            continue;
+        }
+
+        if has_room(world, side, config.height) {
+            // Step off the edge and fall until something is underneath
+            for drop in 1..=config.max_fall {
+                let target = offset(side, 0, -drop, 0);
+                if !is_passable(world, target) {
+                    break;
+                }
+                if is_walkable(world, target, config) {
+                    result.push((target, WALK_COST + FALL_COST * drop as u32));
+                    break;
+                }
+            }
+            continue;
+        }
+
+        // Jump: needs headroom above the current node for the whole climb
+        for climb in 1..=config.step_height {
+            if !is_passable(world, offset(node, 0, config.height + climb - 1, 0)) {
+                break;
+            }
+            let target = offset(side, 0, climb, 0);
+            if is_walkable(world, target, config) {
+                result.push((target, WALK_COST + CLIMB_COST * climb as u32));
+                break;
+            }
+        }
+    }
+    result
+}
+
+/// Never overestimates: every move covers one horizontal block, climbs and drops add at least `CLIMB_COST`/`FALL_COST` per block.
+fn heuristic(a: Node, b: Node) -> u32 {
+    let horizontal = (a.0 - b.0).unsigned_abs() + (a.2 - b.2).unsigned_abs();
+    let vertical = (a.1 - b.1).unsigned_abs();
+    horizontal * WALK_COST + vertical * CLIMB_COST.min(FALL_COST)
+}
+
+/// A* over walkable nodes. Returns the nodes to visit after `start`, ending with `goal`.
+pub fn find_path(world: &World, start: Node, goal: Node, config: &PathConfig) -> Option<Vec<Node>> {
+    if start == goal {
+        return Some(Vec::new());
+    }
+    if !is_walkable(world, goal, config) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Node, Node> = HashMap::new();
+    let mut cost: HashMap<Node, u32> = HashMap::new();
+    cost.insert(start, 0);
+    open.push(Reverse((heuristic(start, goal), 0u32, start)));
+
+    let mut expanded = 0;
+    while let Some(Reverse((_, node_cost, node))) = open.pop() {
+        if node == goal {
+            let mut path = vec![node];
+            let mut current = node;
+            while let Some(previous) = came_from.get(&current) {
+                if *previous == start {
+                    break;
+                }
+                path.push(*previous);
+                current = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // Stale heap entry, a cheaper route was found after it was queued
+        if node_cost > cost[&node] {
+            continue;
+        }
+        expanded += 1;
+        if expanded > config.max_nodes {
+            return None;
+        }
+
+        for (next, step_cost) in neighbours(world, node, config) {
+            let next_cost = node_cost + step_cost;
+            if cost.get(&next).map_or(true, |c| next_cost < *c) {
+                cost.insert(next, next_cost);
+                came_from.insert(next, node);
+                open.push(Reverse((next_cost + heuristic(next, goal), next_cost, next)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn flat_world() -> World {
+        World::flat(2, "stone")
+    }
+
+    #[test]
+    fn walks_straight_on_flat_ground() {
+        let world = flat_world();
+        let path = find_path(&world, (2, 1, 2), (8, 1, 2), &PathConfig::default()).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(*path.last().unwrap(), (8, 1, 2));
+        assert!(path.iter().all(|node| node.1 == 1));
+    }
+
+    #[test]
+    fn goes_around_walls() {
+        let mut world = flat_world();
+        for z in 0..10 {
+            world.set_block((5, 1, z), "stone", 0);
+            world.set_block((5, 2, z), "stone", 0);
+        }
+        let path = find_path(&world, (2, 1, 2), (8, 1, 2), &PathConfig::default()).unwrap();
+        assert!(path.iter().all(|node| node.0 != 5 || node.2 >= 10));
+        assert_eq!(*path.last().unwrap(), (8, 1, 2));
+    }
+
+    #[test]
+    fn jumps_one_block_but_not_two() {
+        let mut world = flat_world();
+        world.set_block((5, 1, 2), "stone", 0);
+        let path = find_path(&world, (4, 1, 2), (5, 2, 2), &PathConfig::default()).unwrap();
+        assert_eq!(path, vec![(5, 2, 2)]);
+
+        // A two block ledge needs a detour, and there is none in a one block wide corridor
+        let mut world = flat_world();
+        for z in 0..32 {
+            world.set_block((5, 1, z), "stone", 0);
+            world.set_block((5, 2, z), "stone", 0);
+        }
+        assert!(find_path(&world, (4, 1, 2), (5, 3, 2), &PathConfig::default()).is_none());
+    }
+
+    #[test]
+    fn drops_down_within_max_fall() {
+        let mut world = flat_world();
+        for x in 0..4 {
+            for y in 1..4 {
+                world.set_block((x, y, 2), "stone", 0);
+            }
+        }
+        let config = PathConfig::default();
+        let path = find_path(&world, (3, 4, 2), (4, 1, 2), &config).unwrap();
+        assert_eq!(path, vec![(4, 1, 2)]);
+
+        let timid = PathConfig { max_fall: 2, ..config };
+        assert!(find_path(&world, (3, 4, 2), (4, 1, 2), &timid).is_none());
+    }
+
+    #[test]
+    fn avoids_lava_and_low_ceilings() {
+        let mut world = flat_world();
+        for z in 0..32 {
+            world.set_block((5, 1, z), "lava", 0);
+        }
+        assert!(find_path(&world, (2, 1, 2), (8, 1, 2), &PathConfig::default()).is_none());
+
+        let mut world = flat_world();
+        for x in 0..32 {
+            world.set_block((x, 2, 3), "stone", 0);
+        }
+        let tall = PathConfig::default();
+        let short = PathConfig { height: 1, ..tall };
+        assert!(!is_walkable(&world, (4, 1, 3), &tall));
+        assert!(is_walkable(&world, (4, 1, 3), &short));
+    }
+}
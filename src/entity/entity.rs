@@ -0,0 +1,63 @@
+use std::any::Any;
+
+use glam::Vec3;
+
+use crate::engine::model::aabb::Aabb;
+use crate::engine::Engine;
+use crate::world::world::World;
+
+pub type EntityId = u64;
+
+/// Physical state every entity has; moved by `physics::step` each tick.
+pub struct EntityBody {
+    /// Bottom centre of the collision box.
+    pub position: Vec3,
+    /// Blocks per second.
+    pub velocity: Vec3,
+    /// Width along x and z, height along y.
+    pub size: Vec3,
+    pub on_ground: bool,
+    /// Set by the entity itself to be dropped from the world after its update.
+    pub removed: bool,
+}
+
+impl EntityBody {
+    pub fn new(position: Vec3, size: Vec3) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            size,
+            on_ground: false,
+            removed: false,
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        let half = Vec3::new(self.size.x * 0.5, 0.0, self.size.z * 0.5);
+        Aabb::new(self.position - half, self.position + half + Vec3::new(0.0, self.size.y, 0.0))
+    }
+}
+
+pub trait Entity {
+    /// Type name used to recreate the entity when its chunk is loaded (see `entity::create_entity`).
+    fn get_type(&self) -> &'static str;
+    /// Collision box size: width (x and z) and height.
+    fn get_size(&self) -> Vec3;
+
+    fn has_gravity(&self) -> bool {
+        true
+    }
+
+    /// Called once per world tick after physics has moved the body.
+    fn update(&mut self, _body: &mut EntityBody, _world: &mut World) {}
+
+    /// Called every frame; entities add their own meshes to the renderer.
+    fn render(&self, _body: &EntityBody, _world: &World, _engine: &mut Engine) {}
+
+    /// Entity-specific save data; position and velocity are stored by the chunk.
+    fn save(&self, _data: &mut Vec<u8>) {}
+
+    /// Downcasting to the concrete type, for systems that only care about one kind of entity.
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
@@ -0,0 +1,104 @@
+use std::any::Any;
+use std::io::Result;
+
+use glam::{Mat4, Vec3};
+
+use crate::common::block::RenderLayer;
+use crate::common::inventory::ItemStack;
+use crate::engine::Engine;
+use crate::entity::cube_mesh;
+use crate::entity::entity::{Entity, EntityBody};
+use crate::utils::bytes::{self, ByteReader};
+use crate::world::world::World;
+
+/// A gravity block that lost its support; re-placed on the grid when it lands.
+pub struct FallingBlockEntity {
+    pub block_id: String,
+}
+
+impl FallingBlockEntity {
+    pub fn new(block_id: &str) -> Self {
+        Self { block_id: block_id.to_string() }
+    }
+
+    pub fn load(data: &mut ByteReader) -> Result<Self> {
+        Ok(Self { block_id: data.read_str()? })
+    }
+
+    fn land(&self, body: &EntityBody, world: &mut World) {
+        let pos = (
+            body.position.x.floor() as i32,
+            body.position.y.round() as i32,
+            body.position.z.floor() as i32,
+        );
+        if !world.can_fall_into(pos) {
+            // The cell was taken while falling (or is not loaded): drop the block as an item
+            world.spawn_item(ItemStack::new(&self.block_id, 1), body.aabb().center());
+            return;
+        }
+        world.set_block(pos, &self.block_id, 0);
+        world.with_block(pos, |block, world| block.on_placed(world, pos));
+        world.schedule_gravity_check(pos);
+    }
+}
+
+impl Entity for FallingBlockEntity {
+    fn get_type(&self) -> &'static str {
+        "falling_block"
+    }
+
+    fn get_size(&self) -> Vec3 {
+        Vec3::splat(0.98)
+    }
+
+    fn update(&mut self, body: &mut EntityBody, world: &mut World) {
+        if body.on_ground {
+            self.land(body, world);
+            body.removed = true;
+        }
+    }
+
+    fn render(&self, body: &EntityBody, world: &World, engine: &mut Engine) {
+        let Some(block) = world.registry.get_block(&self.block_id) else { return };
+        let tex_index = world.registry.get_texture_index(&self.block_id);
+        let Some(texture_info) = engine.renderer.texture_manager.get_texture_info_by_id(tex_index) else { return };
+
+        let aabb = body.aabb();
+        let transform = Mat4::from_translation(aabb.min) * Mat4::from_scale(aabb.max - aabb.min);
+        let (vertices, indices) = cube_mesh(transform, texture_info);
+        let vertex_data = bytemuck::cast_slice(&vertices);
+        if block.get_render_layer() == RenderLayer::Translucent {
+            engine.renderer.add_translucent_mesh(vertex_data, &indices, aabb);
+        } else {
+            engine.renderer.add_mesh(vertex_data, &indices, aabb);
+        }
+    }
+
+    fn save(&self, data: &mut Vec<u8>) {
+        bytes::write_str(data, &self.block_id);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn block_without_room_to_land_drops_as_item() {
+        let mut world = World::flat(1, "stone");
+        let falling = FallingBlockEntity::new("sand");
+        falling.land(&EntityBody::new(Vec3::new(1.5, 0.0, 1.5), falling.get_size()), &mut world);
+
+        assert_eq!(world.get_block_at((1, 0, 1)), "stone");
+        let (_, entry) = world.entities.iter().next().expect("dropped item");
+        assert_eq!(entry.entity.get_type(), "item");
+    }
+}
@@ -0,0 +1,269 @@
+use std::any::Any;
+use std::io::Result;
+
+use glam::{Mat4, Vec3};
+use rand::Rng;
+
+use crate::engine::model::aabb::Aabb;
+use crate::common::block::RenderLayer;
+use crate::common::inventory::{Inventory, ItemStack, MAX_STACK_SIZE};
+use crate::engine::Engine;
+use crate::entity::cube_mesh;
+use crate::entity::entity::{Entity, EntityBody, EntityId};
+use crate::utils::bytes::{self, ByteReader};
+use crate::world::world::{World, TICKS_PER_SECOND};
+
+/// Dropped items disappear after five minutes.
+const DESPAWN_TICKS: u32 = 5 * 60 * TICKS_PER_SECOND as u32;
+/// Freshly dropped items can't be picked up straight away.
+const PICKUP_DELAY_TICKS: u32 = 10;
+/// Identical items closer than this merge into one stack.
+const MERGE_RADIUS: f32 = 0.75;
+/// Items are only checked for merging every few ticks.
+const MERGE_INTERVAL_TICKS: u32 = 10;
+const GROUND_FRICTION: f32 = 0.6;
+const AIR_FRICTION: f32 = 0.98;
+/// Rendered cube edge length.
+const ITEM_SCALE: f32 = 0.25;
+
+/// A stack of items lying in the world.
+pub struct ItemEntity {
+    pub stack: ItemStack,
+    pub age: u32,
+}
+
+impl ItemEntity {
+    pub fn new(stack: ItemStack) -> Self {
+        Self { stack, age: 0 }
+    }
+
+    pub fn load(data: &mut ByteReader) -> Result<Self> {
+        let item_id = data.read_str()?;
+        let count = data.read_u32()?;
+        let age = data.read_u32()?;
+        Ok(Self { stack: ItemStack::new(&item_id, count), age })
+    }
+
+    pub fn can_pick_up(&self) -> bool {
+        self.age >= PICKUP_DELAY_TICKS
+    }
+
+    /// Absorbs nearby stacks of the same item as long as the result fits in one stack.
+    fn merge_nearby(&mut self, body: &EntityBody, world: &mut World) {
+        let area = Aabb::new(body.aabb().min - Vec3::splat(MERGE_RADIUS), body.aabb().max + Vec3::splat(MERGE_RADIUS));
+        for id in world.entities.query_aabb(&area) {
+            let mergeable = world.entities.get(id)
+                .and_then(|entry| entry.entity.as_any().downcast_ref::<ItemEntity>())
+                .map_or(false, |other| other.stack.item_id == self.stack.item_id
+                    && self.stack.count + other.stack.count <= MAX_STACK_SIZE);
+            if !mergeable {
+                continue;
+            }
+            if let Some(entry) = world.entities.remove(id) {
+                if let Some(other) = entry.entity.as_any().downcast_ref::<ItemEntity>() {
+                    self.stack.count += other.stack.count;
+                    // The merged stack keeps the younger timer
+                    self.age = self.age.min(other.age);
+                }
+            }
+        }
+    }
+}
+
+impl Entity for ItemEntity {
+    fn get_type(&self) -> &'static str {
+        "item"
+    }
+
+    fn get_size(&self) -> Vec3 {
+        Vec3::splat(ITEM_SCALE)
+    }
+
+    fn update(&mut self, body: &mut EntityBody, world: &mut World) {
+        self.age += 1;
+        if self.age >= DESPAWN_TICKS || self.stack.count == 0 {
+            body.removed = true;
+            return;
+        }
+
+        let friction = if body.on_ground { GROUND_FRICTION } else { AIR_FRICTION };
+        body.velocity.x *= friction;
+        body.velocity.z *= friction;
+
+        if self.age % MERGE_INTERVAL_TICKS == 0 {
+            self.merge_nearby(body, world);
+        }
+    }
+
+    fn render(&self, body: &EntityBody, world: &World, engine: &mut Engine) {
+        let Some(block) = world.registry.get_block(&self.stack.item_id) else { return };
+        let tex_index = world.registry.get_texture_index(&self.stack.item_id);
+        let Some(texture_info) = engine.renderer.texture_manager.get_texture_info_by_id(tex_index) else { return };
+
+        // Покачивание и вращение зависят только от возраста предмета
+        let time = self.age as f32 / TICKS_PER_SECOND;
+        let bob = (time * 2.5).sin() * 0.08 + 0.12;
+        let center = body.position + Vec3::new(0.0, ITEM_SCALE * 0.5 + bob, 0.0);
+        let transform = Mat4::from_translation(center)
+            * Mat4::from_rotation_y(time * 1.5)
+            * Mat4::from_scale(Vec3::splat(ITEM_SCALE))
+            * Mat4::from_translation(Vec3::splat(-0.5));
+        let (vertices, indices) = cube_mesh(transform, texture_info);
+        // Loose enough to hold the cube at any rotation
+        let bounds = Aabb::new(center - Vec3::splat(ITEM_SCALE), center + Vec3::splat(ITEM_SCALE));
+
+        let vertex_data = bytemuck::cast_slice(&vertices);
+        if block.get_render_layer() == RenderLayer::Translucent {
+            engine.renderer.add_translucent_mesh(vertex_data, &indices, bounds);
+        } else {
+            engine.renderer.add_mesh(vertex_data, &indices, bounds);
+        }
+    }
+
+    fn save(&self, data: &mut Vec<u8>) {
+        bytes::write_str(data, &self.stack.item_id);
+        bytes::write_u32(data, self.stack.count);
+        bytes::write_u32(data, self.age);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl World {
+    /// Drops an item stack at `position` with a small random toss.
+    pub fn spawn_item(&mut self, stack: ItemStack, position: Vec3) -> EntityId {
+        let mut rng = rand::rng();
+        let id = self.spawn_entity(Box::new(ItemEntity::new(stack)), position);
+        if let Some(body) = self.entities.get_body_mut(id) {
+            body.velocity = Vec3::new(
+                rng.random_range(-1.0..1.0),
+                rng.random_range(3.0..5.0),
+                rng.random_range(-1.0..1.0),
+            );
+        }
+        id
+    }
+
+    /// Moves items within `radius` of `center` into `inventory`. Items that only
+    /// partly fit stay in the world with the remainder.
+    pub fn pick_up_items(&mut self, center: Vec3, radius: f32, inventory: &mut Inventory) -> u32 {
+        let area = Aabb::new(center - Vec3::splat(radius), center + Vec3::splat(radius));
+        let mut picked_up = 0;
+        for id in self.entities.query_aabb(&area) {
+            let Some(entry) = self.entities.get_mut(id) else { continue };
+            if entry.body.aabb().center().distance(center) > radius {
+                continue;
+            }
+            let Some(item) = entry.entity.as_any_mut().downcast_mut::<ItemEntity>() else { continue };
+            if !item.can_pick_up() {
+                continue;
+            }
+            let remaining = inventory.add(&item.stack);
+            picked_up += item.stack.count - remaining;
+            item.stack.count = remaining;
+            if remaining == 0 {
+                self.entities.remove(id);
+            }
+        }
+        picked_up
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn drop_item(world: &mut World, item_id: &str, count: u32, position: Vec3) -> EntityId {
+        // Without the toss of spawn_item, so items stay where they are put
+        world.spawn_entity(Box::new(ItemEntity::new(ItemStack::new(item_id, count))), position)
+    }
+
+    fn stacks(world: &World) -> Vec<(String, u32)> {
+        let mut stacks: Vec<_> = world.entities.iter()
+            .filter_map(|(_, entry)| entry.entity.as_any().downcast_ref::<ItemEntity>())
+            .map(|item| (item.stack.item_id.clone(), item.stack.count))
+            .collect();
+        stacks.sort();
+        stacks
+    }
+
+    fn run_ticks(world: &mut World, ticks: u32) {
+        for _ in 0..ticks {
+            world.update_entities();
+        }
+    }
+
+    #[test]
+    fn identical_items_within_radius_merge() {
+        let mut world = World::flat(1, "stone");
+        drop_item(&mut world, "sand", 5, Vec3::new(4.5, 1.0, 4.5));
+        drop_item(&mut world, "sand", 7, Vec3::new(5.0, 1.0, 4.5));
+        drop_item(&mut world, "dirt", 3, Vec3::new(4.5, 1.0, 4.8));
+        drop_item(&mut world, "sand", 2, Vec3::new(10.5, 1.0, 10.5));
+        drop_item(&mut world, "gravel", 60, Vec3::new(8.5, 1.0, 4.5));
+        drop_item(&mut world, "gravel", 10, Vec3::new(8.8, 1.0, 4.5));
+
+        run_ticks(&mut world, MERGE_INTERVAL_TICKS - 1);
+        assert_eq!(world.entities.len(), 6);
+        run_ticks(&mut world, 1);
+        // Different items, far away items and stacks that would overflow stay apart
+        assert_eq!(stacks(&world), vec![
+            ("dirt".to_string(), 3),
+            ("gravel".to_string(), 10),
+            ("gravel".to_string(), 60),
+            ("sand".to_string(), 2),
+            ("sand".to_string(), 12),
+        ]);
+    }
+
+    #[test]
+    fn items_despawn_after_timeout() {
+        let mut world = World::flat(1, "stone");
+        let id = drop_item(&mut world, "sand", 1, Vec3::new(4.5, 1.0, 4.5));
+        let entry = world.entities.get_mut(id).unwrap();
+        entry.entity.as_any_mut().downcast_mut::<ItemEntity>().unwrap().age = DESPAWN_TICKS - 2;
+
+        run_ticks(&mut world, 1);
+        assert!(world.entities.get(id).is_some());
+        run_ticks(&mut world, 1);
+        assert!(world.entities.get(id).is_none());
+    }
+
+    #[test]
+    fn fresh_items_wait_before_pickup() {
+        let mut world = World::flat(1, "stone");
+        let mut inventory = Inventory::new(4);
+        let center = Vec3::new(4.5, 1.0, 4.5);
+        let id = drop_item(&mut world, "sand", 3, center);
+
+        run_ticks(&mut world, PICKUP_DELAY_TICKS - 1);
+        assert_eq!(world.pick_up_items(center, 1.5, &mut inventory), 0);
+        run_ticks(&mut world, 1);
+        assert_eq!(world.pick_up_items(center, 1.5, &mut inventory), 3);
+        assert!(world.entities.get(id).is_none());
+        assert_eq!(inventory.get_slots()[0], Some(ItemStack::new("sand", 3)));
+    }
+
+    #[test]
+    fn pickup_takes_only_what_fits_and_only_within_radius() {
+        let mut world = World::flat(1, "stone");
+        let mut inventory = Inventory::new(1);
+        inventory.add(&ItemStack::new("sand", 60));
+        let center = Vec3::new(4.5, 1.0, 4.5);
+        let near = drop_item(&mut world, "sand", 10, center);
+        let far = drop_item(&mut world, "sand", 1, Vec3::new(8.5, 1.0, 4.5));
+        run_ticks(&mut world, PICKUP_DELAY_TICKS);
+
+        assert_eq!(world.pick_up_items(center, 1.5, &mut inventory), 4);
+        assert_eq!(inventory.get_slots()[0], Some(ItemStack::new("sand", MAX_STACK_SIZE)));
+        let left = world.entities.get(near).unwrap().entity.as_any().downcast_ref::<ItemEntity>().unwrap();
+        assert_eq!(left.stack.count, 6);
+        assert!(world.entities.get(far).is_some());
+    }
+}
@@ -0,0 +1,172 @@
+use std::any::Any;
+use std::io::{Error, ErrorKind, Result};
+
+use glam::{Mat4, Vec3};
+
+use crate::engine::Engine;
+use crate::entity::ai::goals::{FleePlayerGoal, FollowPlayerGoal, GoalContext, GoalSelector, WanderGoal};
+use crate::entity::ai::navigator::Navigator;
+use crate::entity::ai::pathfinding::PathConfig;
+use crate::entity::entity::{Entity, EntityBody};
+use crate::utils::bytes::{self, ByteReader};
+use crate::world::biome::Biome;
+use crate::world::world::{World, TICKS_PER_SECOND};
+
+/// Where and when a mob type may appear on its own.
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnRule {
+    pub biomes: &'static [Biome],
+    pub min_light: u8,
+    pub max_light: u8,
+    pub max_per_chunk: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MobType {
+    pub id: &'static str,
+    pub size: Vec3,
+    /// Blocks per second.
+    pub speed: f32,
+    pub path_config: PathConfig,
+    pub spawn: SpawnRule,
+    /// Name in the world's `ModelRegistry`.
+    pub model: &'static str,
+}
+
+pub const MOB_TYPES: [&str; 2] = ["rabbit", "zombie"];
+
+pub fn get_mob_type(id: &str) -> Option<MobType> {
+    match id {
+        "rabbit" => Some(MobType {
+            id: "rabbit",
+            size: Vec3::new(0.5, 0.5, 0.5),
+            speed: 3.0,
+            path_config: PathConfig { height: 1, ..PathConfig::default() },
+            spawn: SpawnRule {
+                biomes: &[Biome::Plains],
+                min_light: 9,
+                max_light: 15,
+                max_per_chunk: 3,
+            },
+            model: "rabbit",
+        }),
+        "zombie" => Some(MobType {
+            id: "zombie",
+            size: Vec3::new(0.6, 1.8, 0.6),
+            speed: 2.0,
+            path_config: PathConfig::default(),
+            spawn: SpawnRule {
+                biomes: &[Biome::Plains, Biome::Desert, Biome::Barren],
+                min_light: 0,
+                max_light: 7,
+                max_per_chunk: 2,
+            },
+            model: "zombie",
+        }),
+        _ => None,
+    }
+}
+
+fn create_goals(id: &str) -> GoalSelector {
+    let wander = Box::new(WanderGoal { chance: 0.02, radius: 8 });
+    match id {
+        "rabbit" => GoalSelector::new()
+            .with_goal(Box::new(FleePlayerGoal { range: 6.0, distance: 10.0 }))
+            .with_goal(wander),
+        "zombie" => GoalSelector::new()
+            .with_goal(Box::new(FollowPlayerGoal::new(24.0, 1.5)))
+            .with_goal(wander),
+        _ => GoalSelector::new().with_goal(wander),
+    }
+}
+
+/// A creature driven by its goals and navigator.
+pub struct MobEntity {
+    pub mob_type: MobType,
+    pub goals: GoalSelector,
+    pub navigator: Navigator,
+    /// Facing around the y axis, follows the walking direction.
+    pub yaw: f32,
+    walking: bool,
+    /// Seconds since the mob started or stopped walking, drives its animation.
+    animation_time: f32,
+}
+
+impl MobEntity {
+    pub fn new(id: &str) -> Option<Self> {
+        let mob_type = get_mob_type(id)?;
+        Some(Self {
+            mob_type,
+            goals: create_goals(id),
+            navigator: Navigator::new(),
+            yaw: 0.0,
+            walking: false,
+            animation_time: 0.0,
+        })
+    }
+
+    pub fn load(data: &mut ByteReader) -> Result<Self> {
+        let id = data.read_str()?;
+        Self::new(&id).ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("unknown mob type '{}'", id)))
+    }
+}
+
+impl Entity for MobEntity {
+    fn get_type(&self) -> &'static str {
+        "mob"
+    }
+
+    fn get_size(&self) -> Vec3 {
+        self.mob_type.size
+    }
+
+    fn update(&mut self, body: &mut EntityBody, world: &mut World) {
+        let mut context = GoalContext {
+            world,
+            body,
+            navigator: &mut self.navigator,
+            path_config: &self.mob_type.path_config,
+        };
+        self.goals.tick(&mut context);
+
+        if self.navigator.is_idle() {
+            if body.on_ground {
+                body.velocity.x *= 0.5;
+                body.velocity.z *= 0.5;
+            }
+        } else {
+            self.navigator.steer(body, self.mob_type.speed);
+        }
+
+        let horizontal_speed = Vec3::new(body.velocity.x, 0.0, body.velocity.z).length();
+        let walking = horizontal_speed > 0.1;
+        if walking != self.walking {
+            self.walking = walking;
+            self.animation_time = 0.0;
+        }
+        self.animation_time += 1.0 / TICKS_PER_SECOND;
+        if walking {
+            // Models face +z
+            self.yaw = body.velocity.x.atan2(body.velocity.z);
+        }
+    }
+
+    fn render(&self, body: &EntityBody, world: &World, engine: &mut Engine) {
+        let Some(model) = world.models.get(self.mob_type.model) else { return };
+        let transform = Mat4::from_translation(body.position) * Mat4::from_rotation_y(self.yaw);
+        let animation = if self.walking { "walk" } else { "idle" };
+        model.draw(engine, transform, animation, self.animation_time);
+    }
+
+    fn save(&self, data: &mut Vec<u8>) {
+        bytes::write_str(data, self.mob_type.id);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
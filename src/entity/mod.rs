@@ -0,0 +1,57 @@
+pub mod entity;
+pub mod store;
+pub mod physics;
+pub mod falling_block;
+pub mod item;
+pub mod mob;
+pub mod spawning;
+pub mod ai;
+pub mod models;
+
+use std::io::Result;
+
+use glam::{Mat4, Vec3};
+
+use crate::engine::render::texture_manager::TextureInfo;
+use crate::engine::Vertex;
+use crate::entity::entity::Entity;
+use crate::entity::falling_block::FallingBlockEntity;
+use crate::entity::item::ItemEntity;
+use crate::entity::mob::MobEntity;
+use crate::utils::bytes::ByteReader;
+use crate::world::chunk::FACES;
+
+/// Recreates an entity from its chunk save data, `None` for unknown types.
+pub fn create_entity(entity_type: &str, data: &mut ByteReader) -> Result<Option<Box<dyn Entity>>> {
+    Ok(match entity_type {
+        "falling_block" => Some(Box::new(FallingBlockEntity::load(data)?)),
+        "item" => Some(Box::new(ItemEntity::load(data)?)),
+        "mob" => Some(Box::new(MobEntity::load(data)?)),
+        _ => None,
+    })
+}
+
+/// Unlit, unoccluded unit cube moved into place by `transform`, same texture on every face.
+pub fn cube_mesh(transform: Mat4, texture_info: &TextureInfo) -> (Vec<Vertex>, Vec<u16>) {
+    let (u_min, v_min, u_max, v_max) = texture_info.uvs;
+    let uvs = [[u_min, v_max], [u_max, v_max], [u_max, v_min], [u_min, v_min]];
+    let layer = texture_info.atlas_position.2;
+
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+    for (normal, corners) in FACES.iter() {
+        let base_index = vertices.len() as u16;
+        let normal = transform
+            .transform_vector3(Vec3::new(normal[0] as f32, normal[1] as f32, normal[2] as f32))
+            .normalize();
+        for i in 0..4 {
+            let corner = transform.transform_point3(Vec3::from(corners[i]));
+            vertices.push(Vertex::new(corner.into(), normal.into(), uvs[i], layer, 1.0));
+        }
+        indices.extend_from_slice(&[
+            base_index, base_index + 1, base_index + 2,
+            base_index + 2, base_index + 3, base_index,
+        ]);
+    }
+    (vertices, indices)
+}
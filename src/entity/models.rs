@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use glam::{Mat4, Vec3};
+
+use crate::engine::model::animation::Animation;
+use crate::engine::model::box_model::{BoxModel, ModelBox};
+use crate::engine::render::renderer::ModelId;
+use crate::engine::Engine;
+
+const ENTITY_TEXTURES: [&str; 2] = [
+    "assets/textures/entity/zombie.png",
+    "assets/textures/entity/rabbit.png",
+];
+
+// Regions of the zombie texture
+const ZOMBIE_SKIN: [f32; 4] = [0.0, 0.0, 1.0, 0.5];
+const ZOMBIE_SHIRT: [f32; 4] = [0.0, 0.5, 0.5, 1.0];
+const ZOMBIE_TROUSERS: [f32; 4] = [0.5, 0.5, 1.0, 1.0];
+const RABBIT_TAIL: [f32; 4] = [0.75, 0.75, 1.0, 1.0];
+
+pub struct EntityModel {
+    pub model: BoxModel,
+    pub animations: HashMap<String, Animation>,
+    /// Set once the model is uploaded to the renderer.
+    gpu_id: Option<ModelId>,
+}
+
+impl EntityModel {
+    fn new(model: BoxModel, animations: Vec<Animation>) -> Self {
+        Self {
+            model,
+            animations: animations.into_iter().map(|a| (a.name.clone(), a)).collect(),
+            gpu_id: None,
+        }
+    }
+
+    /// Queues the model at `transform`, posed by `animation` at `time` seconds (rest pose if it doesn't exist).
+    pub fn draw(&self, engine: &mut Engine, transform: Mat4, animation: &str, time: f32) {
+        let Some(gpu_id) = self.gpu_id else { return };
+        let rotations = self.animations.get(animation).map(|a| a.sample(time)).unwrap_or_default();
+        let pose = self.model.pose(&rotations);
+        engine.renderer.draw_model(gpu_id, transform, &pose);
+    }
+}
+
+/// Box models of all entity types, by name.
+pub struct ModelRegistry {
+    models: HashMap<String, EntityModel>,
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        let mut models = HashMap::new();
+        models.insert("zombie".to_string(), zombie_model());
+        models.insert("rabbit".to_string(), rabbit_model());
+        Self { models }
+    }
+
+    /// Loads entity textures and uploads every model to the renderer.
+    pub fn upload(&mut self, engine: &mut Engine) {
+        for path in ENTITY_TEXTURES {
+            if let Err(e) = engine.add_texture(path, None) {
+                println!("Failed to load entity texture: {}", e);
+            }
+        }
+        for (name, model) in self.models.iter_mut() {
+            model.gpu_id = Some(engine.renderer.register_model(&model.model));
+            println!("Registered entity model {} ({} parts)", name, model.model.parts.len());
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EntityModel> {
+        self.models.get(name)
+    }
+}
+
+fn zombie_model() -> EntityModel {
+    let model = BoxModel::new()
+        .with_part("body", None, Vec3::new(0.0, 0.7, 0.0), vec![
+            ModelBox::new(Vec3::new(-0.25, 0.7, -0.125), Vec3::new(0.5, 0.7, 0.25), "zombie").with_uv(ZOMBIE_SHIRT),
+        ])
+        .with_part("head", Some("body"), Vec3::new(0.0, 1.4, 0.0), vec![
+            ModelBox::new(Vec3::new(-0.2, 1.4, -0.2), Vec3::new(0.4, 0.4, 0.4), "zombie").with_uv(ZOMBIE_SKIN),
+        ])
+        .with_part("left_arm", Some("body"), Vec3::new(0.35, 1.3, 0.0), vec![
+            ModelBox::new(Vec3::new(0.25, 0.7, -0.1), Vec3::new(0.2, 0.7, 0.2), "zombie").with_uv(ZOMBIE_SKIN),
+        ])
+        .with_part("right_arm", Some("body"), Vec3::new(-0.35, 1.3, 0.0), vec![
+            ModelBox::new(Vec3::new(-0.45, 0.7, -0.1), Vec3::new(0.2, 0.7, 0.2), "zombie").with_uv(ZOMBIE_SKIN),
+        ])
+        .with_part("left_leg", Some("body"), Vec3::new(0.125, 0.7, 0.0), vec![
+            ModelBox::new(Vec3::new(0.0, 0.0, -0.125), Vec3::new(0.25, 0.7, 0.25), "zombie").with_uv(ZOMBIE_TROUSERS),
+        ])
+        .with_part("right_leg", Some("body"), Vec3::new(-0.125, 0.7, 0.0), vec![
+            ModelBox::new(Vec3::new(-0.25, 0.0, -0.125), Vec3::new(0.25, 0.7, 0.25), "zombie").with_uv(ZOMBIE_TROUSERS),
+        ]);
+
+    // Arms stretched forward, legs swing opposite to each other
+    let arms_forward = Vec3::new(-1.5, 0.0, 0.0);
+    let swing = Vec3::new(0.6, 0.0, 0.0);
+    let idle = Animation::new("idle", 2.0, true)
+        .with_track("left_arm", &[(0.0, arms_forward), (1.0, arms_forward + Vec3::new(0.1, 0.0, 0.0)), (2.0, arms_forward)])
+        .with_track("right_arm", &[(0.0, arms_forward), (1.0, arms_forward + Vec3::new(0.1, 0.0, 0.0)), (2.0, arms_forward)]);
+    let walk = Animation::new("walk", 1.0, true)
+        .with_track("left_arm", &[(0.0, arms_forward)])
+        .with_track("right_arm", &[(0.0, arms_forward)])
+        .with_track("left_leg", &[(0.0, Vec3::ZERO), (0.25, swing), (0.75, -swing), (1.0, Vec3::ZERO)])
+        .with_track("right_leg", &[(0.0, Vec3::ZERO), (0.25, -swing), (0.75, swing), (1.0, Vec3::ZERO)]);
+    EntityModel::new(model, vec![idle, walk])
+}
+
+fn rabbit_model() -> EntityModel {
+    let model = BoxModel::new()
+        .with_part("body", None, Vec3::new(0.0, 0.1, -0.2), vec![
+            ModelBox::new(Vec3::new(-0.15, 0.1, -0.25), Vec3::new(0.3, 0.25, 0.45), "rabbit"),
+            ModelBox::new(Vec3::new(-0.06, 0.2, -0.31), Vec3::new(0.12, 0.12, 0.06), "rabbit").with_uv(RABBIT_TAIL),
+        ])
+        .with_part("head", Some("body"), Vec3::new(0.0, 0.3, 0.2), vec![
+            ModelBox::new(Vec3::new(-0.11, 0.25, 0.15), Vec3::new(0.22, 0.2, 0.2), "rabbit"),
+            ModelBox::new(Vec3::new(-0.09, 0.45, 0.2), Vec3::new(0.06, 0.18, 0.04), "rabbit"),
+            ModelBox::new(Vec3::new(0.03, 0.45, 0.2), Vec3::new(0.06, 0.18, 0.04), "rabbit"),
+        ])
+        .with_part("front_legs", Some("body"), Vec3::new(0.0, 0.15, 0.12), vec![
+            ModelBox::new(Vec3::new(-0.12, 0.0, 0.08), Vec3::new(0.24, 0.15, 0.08), "rabbit"),
+        ])
+        .with_part("back_legs", Some("body"), Vec3::new(0.0, 0.15, -0.15), vec![
+            ModelBox::new(Vec3::new(-0.14, 0.0, -0.22), Vec3::new(0.28, 0.12, 0.16), "rabbit"),
+        ]);
+
+    // Hops: the body tips forward while the legs kick back
+    let walk = Animation::new("walk", 0.5, true)
+        .with_track("body", &[(0.0, Vec3::ZERO), (0.2, Vec3::new(0.3, 0.0, 0.0)), (0.5, Vec3::ZERO)])
+        .with_track("back_legs", &[(0.0, Vec3::ZERO), (0.2, Vec3::new(-0.8, 0.0, 0.0)), (0.5, Vec3::ZERO)])
+        .with_track("front_legs", &[(0.0, Vec3::ZERO), (0.2, Vec3::new(0.6, 0.0, 0.0)), (0.5, Vec3::ZERO)]);
+    let idle = Animation::new("idle", 3.0, true)
+        .with_track("head", &[(0.0, Vec3::ZERO), (2.6, Vec3::ZERO), (2.8, Vec3::new(0.2, 0.0, 0.0)), (3.0, Vec3::ZERO)]);
+    EntityModel::new(model, vec![idle, walk])
+}
@@ -0,0 +1,142 @@
+use glam::Vec3;
+
+use crate::entity::entity::EntityBody;
+use crate::world::chunk::CHUNK_HEIGHT;
+use crate::world::world::World;
+
+/// Blocks per second squared.
+pub const GRAVITY: f32 = 20.0;
+/// Blocks per second.
+pub const TERMINAL_VELOCITY: f32 = 40.0;
+/// Longest move checked at once, so fast entities can't tunnel through a single block.
+const MAX_STEP: f32 = 0.45;
+const EPSILON: f32 = 1e-4;
+
+/// Solid blocks collide; below the world and unloaded chunks count as solid, above the world is open.
+pub fn is_solid_at(world: &World, pos: (i32, i32, i32)) -> bool {
+    if pos.1 < 0 {
+        return true;
+    }
+    if pos.1 >= CHUNK_HEIGHT as i32 {
+        return false;
+    }
+    if !world.is_loaded(pos) {
+        return true;
+    }
+    world.get_block(pos).map_or(false, |block| block.is_solid())
+}
+
+/// Applies gravity and moves the body, resolving block collisions one axis at a time.
+pub fn step(world: &World, body: &mut EntityBody, gravity: bool, dt: f32) {
+    if gravity {
+        body.velocity.y = (body.velocity.y - GRAVITY * dt).max(-TERMINAL_VELOCITY);
+    }
+
+    let motion = body.velocity * dt;
+    let steps = (motion.abs().max_element() / MAX_STEP).ceil().max(1.0) as usize;
+    let mut delta = motion / steps as f32;
+
+    body.on_ground = false;
+    for _ in 0..steps {
+        for axis in 0..3 {
+            if delta[axis] == 0.0 {
+                continue;
+            }
+            if move_axis(world, body, axis, delta[axis]) {
+                if axis == 1 && delta[axis] < 0.0 {
+                    body.on_ground = true;
+                }
+                body.velocity[axis] = 0.0;
+                delta[axis] = 0.0;
+            }
+        }
+    }
+}
+
+/// Moves along one axis and pushes the body back out of the nearest solid block it entered.
+fn move_axis(world: &World, body: &mut EntityBody, axis: usize, delta: f32) -> bool {
+    body.position[axis] += delta;
+    let aabb = body.aabb();
+    let min = (aabb.min + Vec3::splat(EPSILON)).floor();
+    let max = (aabb.max - Vec3::splat(EPSILON)).floor();
+
+    let mut hit: Option<i32> = None;
+    for x in min.x as i32..=max.x as i32 {
+        for y in min.y as i32..=max.y as i32 {
+            for z in min.z as i32..=max.z as i32 {
+                if !is_solid_at(world, (x, y, z)) {
+                    continue;
+                }
+                let cell = [x, y, z][axis];
+                hit = Some(match hit {
+                    Some(h) if delta > 0.0 => h.min(cell),
+                    Some(h) => h.max(cell),
+                    None => cell,
+                });
+            }
+        }
+    }
+
+    match hit {
+        Some(cell) => {
+            if delta > 0.0 {
+                body.position[axis] = cell as f32 - (aabb.max[axis] - body.position[axis]);
+            } else {
+                body.position[axis] = (cell + 1) as f32 + (body.position[axis] - aabb.min[axis]);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn player_sized(position: Vec3) -> EntityBody {
+        EntityBody::new(position, Vec3::new(0.6, 1.8, 0.6))
+    }
+
+    #[test]
+    fn falling_body_lands_on_the_floor() {
+        let world = World::flat(1, "stone");
+        let mut body = player_sized(Vec3::new(4.5, 5.0, 4.5));
+        for _ in 0..40 {
+            step(&world, &mut body, true, 0.05);
+        }
+        assert_eq!(body.position.y, 1.0);
+        assert!(body.on_ground);
+        assert_eq!(body.velocity.y, 0.0);
+    }
+
+    #[test]
+    fn wall_stops_one_axis_and_the_body_slides_along_it() {
+        let mut world = World::flat(1, "stone");
+        for z in 0..16 {
+            world.set_block((3, 1, z), "stone", 0);
+        }
+        let mut body = player_sized(Vec3::new(1.5, 1.0, 1.5));
+        body.velocity = Vec3::new(20.0, 0.0, 20.0);
+        step(&world, &mut body, false, 0.1);
+
+        assert!((body.position.x - 2.7).abs() < 1e-4, "x = {}", body.position.x);
+        assert_eq!(body.velocity.x, 0.0);
+        assert!((body.position.z - 3.5).abs() < 1e-4, "z = {}", body.position.z);
+        assert_eq!(body.velocity.z, 20.0);
+    }
+
+    #[test]
+    fn unloaded_chunks_are_solid() {
+        let world = World::flat(1, "stone");
+        assert!(is_solid_at(&world, (16, 1, 8)));
+        assert!(is_solid_at(&world, (8, -1, 8)));
+        assert!(!is_solid_at(&world, (8, CHUNK_HEIGHT as i32, 8)));
+
+        let mut body = player_sized(Vec3::new(15.0, 1.0, 8.5));
+        body.velocity = Vec3::new(20.0, 0.0, 0.0);
+        step(&world, &mut body, false, 0.1);
+        assert!((body.position.x - 15.7).abs() < 1e-4, "x = {}", body.position.x);
+        assert_eq!(body.velocity.x, 0.0);
+    }
+}
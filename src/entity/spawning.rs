@@ -0,0 +1,137 @@
+use glam::Vec3;
+use rand::Rng;
+
+use crate::entity::ai::pathfinding::is_walkable;
+use crate::entity::entity::EntityId;
+use crate::entity::mob::{get_mob_type, MobEntity, MobType, MOB_TYPES};
+use crate::world::chunk::CHUNK_SIZE;
+use crate::world::world::World;
+
+/// Ticks between spawn attempts.
+const SPAWN_INTERVAL_TICKS: u64 = 40;
+/// Mobs never appear right next to the player.
+const MIN_PLAYER_DISTANCE: f32 = 12.0;
+
+impl World {
+    /// Checks the biome, light level, free space, per-chunk cap and player distance for `mob_type` at `pos` (feet).
+    pub fn can_spawn_mob(&self, mob_type: &MobType, pos: (i32, i32, i32)) -> bool {
+        let rule = &mob_type.spawn;
+        if !rule.biomes.contains(&self.get_biome(pos.0, pos.2)) {
+            return false;
+        }
+        let light = self.get_light_level(pos);
+        if light < rule.min_light || light > rule.max_light {
+            return false;
+        }
+        if !is_walkable(self, pos, &mob_type.path_config) {
+            return false;
+        }
+        if let Some(player) = self.player_position {
+            let feet = Vec3::new(pos.0 as f32 + 0.5, pos.1 as f32, pos.2 as f32 + 0.5);
+            if feet.distance(player) < MIN_PLAYER_DISTANCE {
+                return false;
+            }
+        }
+
+        let chunk = (pos.0.div_euclid(CHUNK_SIZE as i32), pos.2.div_euclid(CHUNK_SIZE as i32));
+        let count = self.entities.in_chunk(chunk).into_iter()
+            .filter_map(|id| self.entities.get(id))
+            .filter_map(|entry| entry.entity.as_any().downcast_ref::<MobEntity>())
+            .filter(|mob| mob.mob_type.id == mob_type.id)
+            .count();
+        count < rule.max_per_chunk
+    }
+
+    pub fn spawn_mob(&mut self, id: &str, pos: (i32, i32, i32)) -> Option<EntityId> {
+        let mob = MobEntity::new(id)?;
+        let position = Vec3::new(pos.0 as f32 + 0.5, pos.1 as f32, pos.2 as f32 + 0.5);
+        Some(self.spawn_entity(Box::new(mob), position))
+    }
+
+    /// One attempt per mob type and chunk on a random surface column, every `SPAWN_INTERVAL_TICKS`.
+    pub(crate) fn run_mob_spawning(&mut self) {
+        if !self.mob_spawning || self.get_tick() % SPAWN_INTERVAL_TICKS != 0 {
+            return;
+        }
+
+        let mut rng = rand::rng();
+        let chunks: Vec<(i32, i32)> = self.chunks.keys().copied().collect();
+        for chunk in chunks {
+            for id in MOB_TYPES {
+                let Some(mob_type) = get_mob_type(id) else { continue };
+                let x = chunk.0 * CHUNK_SIZE as i32 + rng.random_range(0..CHUNK_SIZE as i32);
+                let z = chunk.1 * CHUNK_SIZE as i32 + rng.random_range(0..CHUNK_SIZE as i32);
+                let Some(y) = self.get_surface_height(x, z) else { continue };
+                if self.can_spawn_mob(&mob_type, (x, y, z)) {
+                    self.spawn_mob(id, (x, y, z));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::world::biome::Biome;
+
+    fn rabbit() -> MobType {
+        get_mob_type("rabbit").unwrap()
+    }
+
+    fn zombie() -> MobType {
+        get_mob_type("zombie").unwrap()
+    }
+
+    /// Grass floor under a stone roof: plains at the floor, but in the dark.
+    fn roofed_world() -> World {
+        let mut world = World::flat(1, "grass");
+        for x in 0..16 {
+            for z in 0..16 {
+                world.set_block((x, 5, z), "stone", 0);
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn light_decides_between_day_and_night_mobs() {
+        let world = World::flat(1, "grass");
+        assert_eq!(world.get_light_level((4, 1, 4)), 15);
+        assert!(world.can_spawn_mob(&rabbit(), (4, 1, 4)));
+        assert!(!world.can_spawn_mob(&zombie(), (4, 1, 4)));
+
+        let world = roofed_world();
+        assert_eq!(world.get_light_level((4, 1, 4)), 0);
+        assert!(!world.can_spawn_mob(&rabbit(), (4, 1, 4)));
+        assert!(world.can_spawn_mob(&zombie(), (4, 1, 4)));
+    }
+
+    #[test]
+    fn biome_is_checked() {
+        let world = World::flat(1, "sand");
+        assert_eq!(world.get_biome(4, 4), Biome::Desert);
+        assert!(!world.can_spawn_mob(&rabbit(), (4, 1, 4)));
+    }
+
+    #[test]
+    fn body_needs_room() {
+        let mut world = roofed_world();
+        world.set_block((4, 2, 4), "stone", 0);
+        assert!(!world.can_spawn_mob(&zombie(), (4, 1, 4)));
+        assert!(world.can_spawn_mob(&zombie(), (5, 1, 4)));
+    }
+
+    #[test]
+    fn chunk_cap_and_player_distance() {
+        let mut world = World::flat(1, "grass");
+        for _ in 0..rabbit().spawn.max_per_chunk {
+            assert!(world.spawn_mob("rabbit", (4, 1, 4)).is_some());
+        }
+        assert!(!world.can_spawn_mob(&rabbit(), (8, 1, 8)));
+
+        let mut world = World::flat(1, "grass");
+        world.player_position = Some(Vec3::new(8.0, 1.0, 8.0));
+        assert!(!world.can_spawn_mob(&rabbit(), (9, 1, 9)));
+    }
+}
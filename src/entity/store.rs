@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec3;
+
+use crate::engine::model::aabb::Aabb;
+use crate::engine::Engine;
+use crate::entity::entity::{Entity, EntityBody, EntityId};
+use crate::entity::physics;
+use crate::world::chunk::CHUNK_SIZE;
+use crate::world::world::{World, TICKS_PER_SECOND};
+
+pub struct EntityEntry {
+    pub body: EntityBody,
+    pub entity: Box<dyn Entity>,
+}
+
+/// All entities of a world, indexed by the chunk their position is in.
+pub struct EntityStore {
+    next_id: EntityId,
+    entities: HashMap<EntityId, EntityEntry>,
+    chunk_index: HashMap<(i32, i32), HashSet<EntityId>>,
+}
+
+pub fn chunk_of(position: Vec3) -> (i32, i32) {
+    (
+        (position.x.floor() as i32).div_euclid(CHUNK_SIZE as i32),
+        (position.z.floor() as i32).div_euclid(CHUNK_SIZE as i32),
+    )
+}
+
+impl EntityStore {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            entities: HashMap::new(),
+            chunk_index: HashMap::new(),
+        }
+    }
+
+    pub fn spawn(&mut self, entity: Box<dyn Entity>, position: Vec3) -> EntityId {
+        let body = EntityBody::new(position, entity.get_size());
+        self.insert(EntityEntry { body, entity })
+    }
+
+    pub fn insert(&mut self, entry: EntityEntry) -> EntityId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.put_back(id, entry);
+        id
+    }
+
+    pub fn remove(&mut self, id: EntityId) -> Option<EntityEntry> {
+        let entry = self.entities.remove(&id)?;
+        let chunk = chunk_of(entry.body.position);
+        if let Some(ids) = self.chunk_index.get_mut(&chunk) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.chunk_index.remove(&chunk);
+            }
+        }
+        Some(entry)
+    }
+
+    fn put_back(&mut self, id: EntityId, entry: EntityEntry) {
+        self.chunk_index.entry(chunk_of(entry.body.position)).or_default().insert(id);
+        self.entities.insert(id, entry);
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&EntityEntry> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut EntityEntry> {
+        self.entities.get_mut(&id)
+    }
+
+    /// Mutable access to the body only; moving it across chunks is fixed up on the next tick.
+    pub fn get_body_mut(&mut self, id: EntityId) -> Option<&mut EntityBody> {
+        self.entities.get_mut(&id).map(|entry| &mut entry.body)
+    }
+
+    pub fn ids(&self) -> Vec<EntityId> {
+        self.entities.keys().copied().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &EntityEntry)> {
+        self.entities.iter().map(|(id, entry)| (*id, entry))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn in_chunk(&self, chunk: (i32, i32)) -> Vec<EntityId> {
+        self.chunk_index.get(&chunk).map_or(Vec::new(), |ids| ids.iter().copied().collect())
+    }
+
+    /// Entities whose collision box overlaps `area`, only visiting the chunks it covers.
+    pub fn query_aabb(&self, area: &Aabb) -> Vec<EntityId> {
+        let (min_x, min_z) = chunk_of(area.min);
+        let (max_x, max_z) = chunk_of(area.max);
+        let mut found = Vec::new();
+        for cx in min_x..=max_x {
+            for cz in min_z..=max_z {
+                let Some(ids) = self.chunk_index.get(&(cx, cz)) else { continue };
+                for id in ids {
+                    if self.entities[id].body.aabb().intersects(area) {
+                        found.push(*id);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Drops every entity of a chunk, e.g. when it is unloaded.
+    pub fn remove_chunk(&mut self, chunk: (i32, i32)) -> Vec<EntityEntry> {
+        let ids = self.chunk_index.remove(&chunk).unwrap_or_default();
+        ids.into_iter().filter_map(|id| self.entities.remove(&id)).collect()
+    }
+}
+
+impl World {
+    pub fn spawn_entity(&mut self, entity: Box<dyn Entity>, position: Vec3) -> EntityId {
+        self.entities.spawn(entity, position)
+    }
+
+    /// Runs physics and the update hook of every entity. Each entity is taken out of the
+    /// store while it updates, so its hook can freely query and modify the world.
+    pub(crate) fn update_entities(&mut self) {
+        let dt = 1.0 / TICKS_PER_SECOND;
+        for id in self.entities.ids() {
+            let Some(mut entry) = self.entities.remove(id) else { continue };
+            physics::step(self, &mut entry.body, entry.entity.has_gravity(), dt);
+            entry.entity.update(&mut entry.body, self);
+            if !entry.body.removed {
+                self.entities.put_back(id, entry);
+            }
+        }
+    }
+
+    pub(crate) fn render_entities(&self, engine: &mut Engine) {
+        for (_, entry) in self.entities.iter() {
+            entry.entity.render(&entry.body, self, engine);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::inventory::ItemStack;
+    use crate::entity::item::ItemEntity;
+
+    fn item() -> Box<dyn Entity> {
+        Box::new(ItemEntity::new(ItemStack::new("stone", 1)))
+    }
+
+    #[test]
+    fn entity_moves_to_the_chunk_it_walks_into() {
+        let mut world = World::flat(2, "stone");
+        let id = world.spawn_entity(item(), Vec3::new(15.5, 1.0, 8.5));
+        assert_eq!(world.entities.in_chunk((0, 0)), vec![id]);
+
+        // One block per tick
+        world.entities.get_body_mut(id).unwrap().velocity.x = TICKS_PER_SECOND;
+        world.update_entities();
+
+        assert!(world.entities.get(id).unwrap().body.position.x > 16.0);
+        assert!(world.entities.in_chunk((0, 0)).is_empty());
+        assert_eq!(world.entities.in_chunk((1, 0)), vec![id]);
+        assert_eq!(world.entities.remove_chunk((1, 0)).len(), 1);
+        assert_eq!(world.entities.len(), 0);
+    }
+
+    #[test]
+    fn query_aabb_finds_overlapping_entities_across_chunks() {
+        let mut store = EntityStore::new();
+        let left = store.spawn(item(), Vec3::new(15.9, 1.0, 4.0));
+        let right = store.spawn(item(), Vec3::new(16.1, 1.0, 4.0));
+        let negative = store.spawn(item(), Vec3::new(-0.5, 1.0, 4.0));
+        let far = store.spawn(item(), Vec3::new(40.0, 1.0, 4.0));
+        let above = store.spawn(item(), Vec3::new(16.0, 5.0, 4.0));
+
+        let mut found = store.query_aabb(&Aabb::new(Vec3::new(15.5, 0.0, 3.5), Vec3::new(16.5, 2.0, 4.5)));
+        found.sort();
+        assert_eq!(found, vec![left, right]);
+
+        let found = store.query_aabb(&Aabb::new(Vec3::new(-1.0, 0.0, 3.0), Vec3::new(0.0, 2.0, 5.0)));
+        assert_eq!(found, vec![negative]);
+        let found = store.query_aabb(&Aabb::new(Vec3::ZERO, Vec3::new(32.0, 4.0, 32.0)));
+        assert!(!found.contains(&far));
+        assert!(!found.contains(&above));
+        assert_eq!(found.len(), 2);
+    }
+}
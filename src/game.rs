@@ -34,6 +34,11 @@ impl GameApp for Game {
             eprintln!("Error loading mods: {}", e);
         }
         
+        // Mod folders can override and add assets
+        for (name, dir) in &mod_loader.mod_dirs {
+            engine.assets.write().unwrap().add_mod(name, dir);
+        }
+        
         let mut game_state = GameState::new(engine);
         
         // Register Lua blocks
@@ -44,6 +49,10 @@ impl GameApp for Game {
         
         // Load textures
         game_state.world.registry.load_textures(engine);
+        game_state.world.models.upload(engine);
+        
+        // Saved chunks may contain Lua blocks, so they are loaded after registration
+        game_state.world.load();
         
         engine.lock_cursor();
         
@@ -56,9 +65,10 @@ impl GameApp for Game {
     
     fn update(&mut self, engine: &mut Engine, delta_time: f32) {
         if let (Some(game_state), Some(ui_system)) = (self.game_state.as_mut(), self.ui_system.as_mut()) {
-            game_state.update(engine, delta_time, ui_system.is_open);
+            game_state.update(engine, delta_time, ui_system.is_screen_open());
+            engine.tick_animations(game_state.world.get_tick());
             
-            if ui_system.is_open {
+            if ui_system.is_screen_open() {
                 engine.unlock_cursor();
             } else {
                 engine.lock_cursor();
@@ -85,4 +95,12 @@ impl GameApp for Game {
     fn resize(&mut self, engine: &mut Engine, new_size: winit::dpi::PhysicalSize<u32>) {
         engine.resize(new_size);
     }
+    
+    fn exiting(&mut self, _engine: &mut Engine) {
+        if let Some(game_state) = self.game_state.as_ref() {
+            if let Err(e) = game_state.world.save() {
+                eprintln!("Error saving world: {}", e);
+            }
+        }
+    }
 }
\ No newline at end of file
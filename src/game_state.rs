@@ -1,16 +1,21 @@
 use glam::{Vec2, Vec3};
 use crate::engine::{Engine, InputEvent};
-use crate::player::GamePlayer;
+use crate::player::{GamePlayer, PICKUP_RADIUS};
 use crate::systems::raycast::Raycast;
-use crate::world::world::World;
+use crate::world::world::{World, TICKS_PER_SECOND};
 use std::collections::HashSet;
 use winit::keyboard::KeyCode;
 
+/// Blocks selectable with the number keys for placing.
+const HOTBAR: [&str; 5] = ["example:ruby_block", "stone", "water", "lava", "sand"];
+
 pub struct GameState {
     pub player: GamePlayer,
     pub world: World,
     pressed_keys: HashSet<KeyCode>,
     mouse_position: Option<glam::Vec2>,
+    tick_accumulator: f32,
+    selected_block: usize,
 }
 
 impl GameState {
@@ -20,6 +25,8 @@ impl GameState {
             world: World::new(engine),
             pressed_keys: HashSet::new(),
             mouse_position: None,
+            tick_accumulator: 0.0,
+            selected_block: 0,
         }
     }
 
@@ -54,13 +61,50 @@ impl GameState {
             }
         }
         
+        self.world.player_position = Some(self.player.get_position());
+        self.world.stream_chunks(self.player.get_position(), engine.camera_settings.render_distance);
+        
+        // Fixed-rate world simulation
+        self.tick_accumulator += dt;
+        while self.tick_accumulator >= 1.0 / TICKS_PER_SECOND {
+            self.tick_accumulator -= 1.0 / TICKS_PER_SECOND;
+            self.world.tick();
+        }
+        
+        let picked_up = self.world.pick_up_items(
+            self.player.get_position() + Vec3::new(0.0, 0.9, 0.0),
+            PICKUP_RADIUS,
+            &mut self.player.inventory,
+        );
+        if picked_up > 0 {
+            println!("Picked up {} item(s)", picked_up);
+        }
+        
+        let body = self.player.get_position() + Vec3::new(0.0, 0.5, 0.0);
+        let fluid = self.world
+            .get_block((body.x.floor() as i32, body.y.floor() as i32, body.z.floor() as i32))
+            .and_then(|block| block.get_fluid());
+        self.player.set_fluid(fluid);
+        
         self.player.update(dt);
     }
 
-    pub fn handle_input(&mut self, engine: &Engine, input: &InputEvent, ui_open: bool) {
+    pub fn handle_input(&mut self, input: &InputEvent, ui_open: bool) {
         match input {
             InputEvent::KeyPressed(key) => {
                 self.pressed_keys.insert(*key);
+                let slot = match key {
+                    KeyCode::Digit1 => Some(0),
+                    KeyCode::Digit2 => Some(1),
+                    KeyCode::Digit3 => Some(2),
+                    KeyCode::Digit4 => Some(3),
+                    KeyCode::Digit5 => Some(4),
+                    _ => None,
+                };
+                if let Some(slot) = slot {
+                    self.selected_block = slot;
+                    println!("Selected block: {}", HOTBAR[slot]);
+                }
             }
             InputEvent::KeyReleased(key) => {
                 self.pressed_keys.remove(key);
@@ -83,7 +127,7 @@ impl GameState {
                             let ray_dir = (self.player.get_camera_target() - ray_pos).normalize();
                             
                             if let Some(hit) = Raycast::cast_ray(ray_pos, ray_dir, 10.0, &self.world) {
-                                self.world.break_block(engine, hit.block_pos);
+                                self.world.break_block(hit.block_pos);
                             }
                         }
                         winit::event::MouseButton::Right => {
@@ -93,13 +137,15 @@ impl GameState {
                             
                             if let Some(hit) = Raycast::cast_ray(ray_pos, ray_dir, 10.0, &self.world) {
                                 let place_pos = Raycast::get_adjacent_block_pos(&hit);
-                                self.world.place_block(engine, place_pos, "example:ruby_block");
+                                self.world.place_block(place_pos, HOTBAR[self.selected_block]);
                             }
                         }
                         _ => {}
                     }
                 }
             }
+            // Typed text only matters to focused UI widgets
+            _ => {}
         }
     }
-}
\ No newline at end of file
+}
@@ -5,16 +5,20 @@ mod game;
 mod player;
 mod game_state;
 mod world;
+mod entity;
 mod systems;
 mod utils;
 mod modding;
 mod ui;
-fn main() -> Result<(), winit::error::EventLoopError> {
+fn main() {
     // Устанавливаем рабочую директорию в папку game
     if let Err(_) = std::env::set_current_dir("game") {
         // Если мы уже в папке game, ничего не делаем
     }
     
     let game = game::Game::default();
-    engine::run_app(game)
+    if let Err(e) = engine::run_app(game) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 }
\ No newline at end of file
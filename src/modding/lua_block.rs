@@ -1,25 +1,31 @@
-use crate::common::block::Block;
+use crate::common::block::{Block, RenderLayer};
+use crate::engine::TextureAnimation;
 
+/// Block registered from a mod script. The strings are leaked once when the mod registers
+/// the block, so the `Block` getters can hand out `&'static str` like the built-in blocks.
 #[derive(Clone)]
 pub struct LuaBlock {
-    pub id: String,
-    pub name: String,
-    pub texture_path: String,
+    pub id: &'static str,
+    pub name: &'static str,
+    pub texture_path: &'static str,
     pub solid: bool,
     pub transparent: bool,
+    pub render_layer: RenderLayer,
+    pub gravity: bool,
+    pub animation: Option<TextureAnimation>,
 }
 
 impl Block for LuaBlock {
     fn get_id(&self) -> &'static str {
-        Box::leak(self.id.clone().into_boxed_str())
+        self.id
     }
     
     fn get_name(&self) -> &'static str {
-        Box::leak(self.name.clone().into_boxed_str())
+        self.name
     }
     
     fn get_texture_path(&self) -> &'static str {
-        Box::leak(self.texture_path.clone().into_boxed_str())
+        self.texture_path
     }
     
     fn is_solid(&self) -> bool {
@@ -29,4 +35,16 @@ impl Block for LuaBlock {
     fn is_transparent(&self) -> bool {
         self.transparent
     }
+    
+    fn get_render_layer(&self) -> RenderLayer {
+        self.render_layer
+    }
+    
+    fn has_gravity(&self) -> bool {
+        self.gravity
+    }
+    
+    fn get_texture_animation(&self) -> Option<TextureAnimation> {
+        self.animation.clone()
+    }
 }
@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use crate::common::block::RenderLayer;
+use crate::engine::TextureAnimation;
 use crate::modding::lua_block::LuaBlock;
 
 pub struct ModInfo {
@@ -14,6 +16,8 @@ pub struct ModInfo {
 pub struct ModLoader {
     lua: Lua,
     pub blocks: HashMap<String, (LuaBlock, String)>,
+    /// Loaded mods and their folders, added as asset layers by the game.
+    pub mod_dirs: Vec<(String, PathBuf)>,
 }
 
 impl ModLoader {
@@ -21,6 +25,7 @@ impl ModLoader {
         Ok(Self {
             lua: Lua::new(),
             blocks: HashMap::new(),
+            mod_dirs: Vec::new(),
         })
     }
     
@@ -41,7 +46,8 @@ impl ModLoader {
                 if init_lua.exists() {
                     let mod_name = path.file_name().unwrap().to_string_lossy().to_string();
                     println!("Loading mod: {}", mod_name);
-                    self.load_mod(init_lua.to_str().unwrap(), &path, &mod_name)?;
+                    self.load_mod(init_lua.to_str().unwrap(), &mod_name)?;
+                    self.mod_dirs.push((mod_name, path));
                 }
             }
         }
@@ -49,10 +55,9 @@ impl ModLoader {
         Ok(())
     }
     
-    fn load_mod(&mut self, lua_path: &str, mod_dir: &Path, mod_name: &str) -> LuaResult<()> {
+    fn load_mod(&mut self, lua_path: &str, mod_name: &str) -> LuaResult<()> {
         let blocks = Arc::new(Mutex::new(Vec::new()));
         let blocks_clone = Arc::clone(&blocks);
-        let mod_dir_clone = mod_dir.to_path_buf();
         let mod_info = Arc::new(Mutex::new(None));
         let mod_info_clone = Arc::clone(&mod_info);
         
@@ -75,19 +80,35 @@ impl ModLoader {
             let texture: String = block_table.get("texture")?;
             let solid: bool = block_table.get("solid").unwrap_or(true);
             let transparent: bool = block_table.get("transparent").unwrap_or(false);
+            let gravity: bool = block_table.get("gravity").unwrap_or(false);
+            let render_layer = match block_table.get::<_, Option<String>>("render_layer")?.as_deref() {
+                Some("opaque") => RenderLayer::Opaque,
+                Some("cutout") => RenderLayer::Cutout,
+                Some("translucent") => RenderLayer::Translucent,
+                Some(other) => {
+                    return Err(LuaError::RuntimeError(format!("Unknown render_layer '{}' for block '{}'", other, id)));
+                }
+                None if transparent => RenderLayer::Translucent,
+                None => RenderLayer::Opaque,
+            };
             
-            let texture_path = if texture.starts_with("assets/") {
-                mod_dir_clone.join(&texture).to_string_lossy().to_string()
-            } else {
-                texture
+            let animation = match block_table.get::<_, Option<LuaTable>>("animation")? {
+                Some(table) => Some(parse_animation(&table, &id)?),
+                None => None,
             };
             
+            // Resolved through the asset layers, where the mod folder sits above the game and packs
+            let texture_path = texture;
+            
             let lua_block = LuaBlock {
-                id: id.clone(),
-                name,
-                texture_path,
+                id: Box::leak(id.into_boxed_str()),
+                name: Box::leak(name.into_boxed_str()),
+                texture_path: Box::leak(texture_path.into_boxed_str()),
                 solid,
                 transparent,
+                render_layer,
+                gravity,
+                animation,
             };
             
             blocks_clone.lock().unwrap().push(lua_block);
@@ -111,9 +132,31 @@ impl ModLoader {
         
         for block in blocks.lock().unwrap().iter() {
             println!("  Registered block: {} from mod {}", block.id, mod_name);
-            self.blocks.insert(block.id.clone(), (block.clone(), mod_name.to_string()));
+            self.blocks.insert(block.id.to_string(), (block.clone(), mod_name.to_string()));
         }
         
         Ok(())
     }
 }
+
+/// `animation = { frametime = 2, frames = { 0, 1, { 2, 10 } } }`: ticks per frame and an optional
+/// frame order, where `{ 2, 10 }` shows frame 2 for 10 ticks.
+fn parse_animation(table: &LuaTable, block_id: &str) -> LuaResult<TextureAnimation> {
+    let frame_time: u32 = table.get::<_, Option<u32>>("frametime")?.unwrap_or(1);
+    let mut frames = Vec::new();
+    if let Some(list) = table.get::<_, Option<LuaTable>>("frames")? {
+        for frame in list.sequence_values::<LuaValue>() {
+            let frame = match frame? {
+                LuaValue::Integer(index) => (index as u32, None),
+                LuaValue::Table(pair) => (pair.get(1)?, Some(pair.get(2)?)),
+                other => {
+                    return Err(LuaError::RuntimeError(format!(
+                        "Invalid animation frame {:?} for block '{}'", other, block_id
+                    )));
+                }
+            };
+            frames.push(frame);
+        }
+    }
+    Ok(TextureAnimation::new(frame_time).with_frames(frames))
+}
@@ -1,5 +1,12 @@
 use glam::Vec3;
 
+use crate::common::block::FluidProperties;
+use crate::common::inventory::Inventory;
+
+pub const INVENTORY_SIZE: usize = 16;
+/// Items closer than this to the player's body are picked up.
+pub const PICKUP_RADIUS: f32 = 1.5;
+
 pub struct GamePlayer {
     position: Vec3,
     velocity: Vec3,
@@ -7,6 +14,8 @@ pub struct GamePlayer {
     pitch: f32,
     speed: f32,
     sensitivity: f32,
+    fluid: Option<FluidProperties>,
+    pub inventory: Inventory,
 }
 
 impl GamePlayer {
@@ -19,14 +28,41 @@ impl GamePlayer {
             pitch: 0.0,
             speed: 40.0,
             sensitivity: 0.005,
+            fluid: None,
+            inventory: Inventory::new(INVENTORY_SIZE),
         }
     }
 
     pub fn update(&mut self, dt: f32) {
+        if let Some(fluid) = self.fluid {
+            // The player flies, so there is no gravity for buoyancy to balance: it only
+            // slows sinking and never lifts a player who is not moving down.
+            if self.velocity.y < 0.0 {
+                self.velocity.y = (self.velocity.y + fluid.buoyancy * dt).min(0.0);
+            }
+            self.velocity *= (1.0 - fluid.drag * dt).max(0.0);
+        }
         self.position += self.velocity * dt;
         self.velocity *= 0.9;
     }
 
+    pub fn get_position(&self) -> Vec3 {
+        self.position
+    }
+
+    /// Fluid the player's body is currently in, if any. Drives drag, buoyancy and swim speed.
+    pub fn set_fluid(&mut self, fluid: Option<FluidProperties>) {
+        self.fluid = fluid;
+    }
+
+    pub fn is_swimming(&self) -> bool {
+        self.fluid.is_some()
+    }
+
+    fn move_speed(&self) -> f32 {
+        if self.is_swimming() { self.speed * 0.5 } else { self.speed }
+    }
+
     pub fn get_camera_position(&self) -> Vec3 {
         self.position + Vec3::new(0.0, 1.8, 0.0)
     }
@@ -46,7 +82,7 @@ impl GamePlayer {
 
     pub fn move_forward(&mut self, amount: f32) {
         let forward = Vec3::new(self.yaw.cos(), 0.0, self.yaw.sin()).normalize();
-        self.velocity += forward * amount * self.speed;
+        self.velocity += forward * amount * self.move_speed();
     }
 
     pub fn move_backward(&mut self, amount: f32) {
@@ -55,7 +91,7 @@ impl GamePlayer {
 
     pub fn move_left(&mut self, amount: f32) {
         let right = Vec3::new(self.yaw.sin(), 0.0, -self.yaw.cos()).normalize();
-        self.velocity += right * amount * self.speed;
+        self.velocity += right * amount * self.move_speed();
     }
 
     pub fn move_right(&mut self, amount: f32) {
@@ -63,11 +99,11 @@ impl GamePlayer {
     }
 
     pub fn move_up(&mut self, amount: f32) {
-        self.velocity.y += amount * self.speed;
+        self.velocity.y += amount * self.move_speed();
     }
 
     pub fn move_down(&mut self, amount: f32) {
-        self.velocity.y -= amount * self.speed;
+        self.velocity.y -= amount * self.move_speed();
     }
 
     pub fn look(&mut self, yaw: f32, pitch: f32) {
@@ -75,4 +111,41 @@ impl GamePlayer {
         self.pitch += pitch * self.sensitivity;
         self.pitch = self.pitch.clamp(-1.5, 1.5);
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blocks::water::WaterBlock;
+    use crate::common::block::Block;
+
+    fn swimming_player() -> GamePlayer {
+        let mut player = GamePlayer::new(Vec3::ZERO);
+        player.set_fluid(WaterBlock.get_fluid());
+        player
+    }
+
+    #[test]
+    fn idle_player_does_not_float_up() {
+        let mut player = swimming_player();
+        for _ in 0..60 {
+            player.update(1.0 / 60.0);
+        }
+        assert_eq!(player.get_position(), Vec3::ZERO);
+    }
+
+    #[test]
+    fn swimming_up_and_down_follows_input() {
+        let mut player = swimming_player();
+        for _ in 0..10 {
+            player.move_up(1.0 / 60.0);
+            player.update(1.0 / 60.0);
+        }
+        assert!(player.get_position().y > 0.0);
+
+        for _ in 0..30 {
+            player.move_down(1.0 / 60.0);
+            player.update(1.0 / 60.0);
+        }
+        assert!(player.get_position().y < 0.0);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::engine::{InputEvent, Engine};
 use winit::keyboard::KeyCode;
+use crate::world::time::DAY_LENGTH;
 use crate::{game_state::GameState, systems::ui_system::UISystem};
 
 pub struct InputSystem;
@@ -10,11 +11,43 @@ impl InputSystem {
     }
 
     pub fn handle_input(&self, engine: &mut Engine, input: &InputEvent, game_state: &mut GameState, ui_system: &mut UISystem) {
+        // Focused text fields take the keyboard: typing "i" must not open the inventory
+        if ui_system.handle_input(engine, input) {
+            return;
+        }
         match input {
             InputEvent::KeyPressed(key) => {
                 if *key == KeyCode::KeyI {
                     ui_system.toggle();
                 }
+                if *key == KeyCode::KeyO {
+                    ui_system.toggle_options();
+                }
+                if *key == KeyCode::F2 {
+                    engine.take_screenshot();
+                }
+                if *key == KeyCode::F8 {
+                    engine.reload_resource_packs();
+                }
+                if *key == KeyCode::F4 {
+                    let enabled = !game_state.world.is_ambient_occlusion_enabled();
+                    game_state.world.set_ambient_occlusion(enabled);
+                    println!("Ambient occlusion: {}", if enabled { "on" } else { "off" });
+                }
+                if *key == KeyCode::F6 {
+                    let frozen = !game_state.world.is_time_frozen();
+                    game_state.world.set_time_frozen(frozen);
+                    println!("Daylight cycle: {}", if frozen { "frozen" } else { "running" });
+                }
+                if *key == KeyCode::F7 {
+                    // Skip ahead a quarter of a day: sunrise -> noon -> sunset -> midnight
+                    let time = game_state.world.get_time_of_day() + DAY_LENGTH / 4;
+                    game_state.world.set_time_of_day(time - time % (DAY_LENGTH / 4));
+                    println!("Time of day: {}", game_state.world.get_time_of_day());
+                }
+            }
+            InputEvent::CursorMoved(x, y) => {
+                ui_system.handle_cursor_moved(engine, glam::Vec2::new(*x, *y));
             }
             InputEvent::MouseButton(button, state) => {
                 if *state == winit::event::ElementState::Pressed {
@@ -27,6 +60,6 @@ impl InputSystem {
         }
         
         // Всегда передаем события игровому состоянию
-        game_state.handle_input(engine, input, ui_system.is_open);
+        game_state.handle_input(input, ui_system.is_screen_open());
     }
 }
\ No newline at end of file
@@ -35,8 +35,8 @@ impl Raycast {
                 current_pos.z.floor() as i32,
             );
             
-            // Check if we hit a solid block (not air)
-            if world.get_block_at(block_pos) != "air" {
+            // Check if we hit a solid block (not air or fluid)
+            if world.get_block(block_pos).map_or(false, |block| block.get_fluid().is_none()) {
                 let face = Self::get_hit_face(current_pos - step, current_pos, block_pos);
                 let chunk_pos = Self::world_to_chunk_pos(block_pos);
                 
@@ -1,5 +1,18 @@
+use glam::Vec3;
 use crate::engine::Engine;
+use crate::engine::render::sky::UnderwaterFog;
+use crate::world::chunk::CHUNK_SIZE;
+use crate::world::world::World;
 use crate::{game_state::GameState, systems::ui_system::UISystem};
+
+const DAY_ZENITH: Vec3 = Vec3::new(0.32, 0.55, 0.95);
+const DAY_HORIZON: Vec3 = Vec3::new(0.72, 0.84, 0.98);
+const NIGHT_ZENITH: Vec3 = Vec3::new(0.01, 0.01, 0.04);
+const NIGHT_HORIZON: Vec3 = Vec3::new(0.04, 0.05, 0.10);
+const SUNSET_HORIZON: Vec3 = Vec3::new(0.95, 0.50, 0.25);
+const SUNLIGHT: Vec3 = Vec3::new(1.0, 0.97, 0.9);
+const MOONLIGHT: Vec3 = Vec3::new(0.5, 0.55, 0.7);
+
 pub struct RenderSystem;
 
 impl RenderSystem {
@@ -16,6 +29,17 @@ impl RenderSystem {
             player.get_camera_up()
         );
         
+        Self::update_daylight(engine, &game_state.world);
+        
+        // Fog ends where the streamed world does; inside a fluid the fluid's own fog takes over
+        let view_distance = (engine.camera_settings.render_distance as usize * CHUNK_SIZE) as f32;
+        engine.renderer.sky.set_view_distance(view_distance);
+        let eye = player.get_camera_position().floor().as_ivec3();
+        engine.renderer.sky.underwater = game_state.world
+            .get_block((eye.x, eye.y, eye.z))
+            .and_then(|block| block.get_fluid())
+            .map(|fluid| UnderwaterFog { color: fluid.fog_color.into(), distance: fluid.fog_distance });
+        
         // Render UI
         ui_system.render(engine, player.get_camera_position(), game_state);
         
@@ -25,4 +49,29 @@ impl RenderSystem {
         // Final render
         engine.render()
     }
+
+    /// Sky colours and the sun/moon light for the world's time of day.
+    fn update_daylight(engine: &mut Engine, world: &World) {
+        let sun = world.sun_direction();
+        let daylight = world.daylight();
+        // Strongest while the sun crosses the horizon
+        let sunset = (1.0 - sun.y.abs() * 4.0).clamp(0.0, 1.0);
+
+        let sky = &mut engine.renderer.sky;
+        sky.sun_direction = sun;
+        sky.zenith_color = NIGHT_ZENITH.lerp(DAY_ZENITH, daylight);
+        sky.horizon_color = NIGHT_HORIZON.lerp(DAY_HORIZON, daylight).lerp(SUNSET_HORIZON, sunset * 0.6);
+
+        let light = &mut engine.renderer.light;
+        if sun.y > 0.0 {
+            light.direction = -sun;
+            light.color = SUNLIGHT.lerp(SUNSET_HORIZON, sunset * 0.5);
+            light.intensity = daylight;
+        } else {
+            light.direction = sun;
+            light.color = MOONLIGHT;
+            light.intensity = 0.25;
+        }
+        light.ambient = 0.1 + 0.2 * daylight;
+    }
 }
\ No newline at end of file
@@ -1,8 +1,36 @@
+use std::cell::RefCell;
+use std::rc::Rc;
 use glam::Vec2;
-use crate::engine::{ui::*, Engine};
+use crate::common::inventory::ItemStack;
+use crate::engine::{ui::*, Engine, InputEvent};
+use crate::engine::settings::MAX_ANISOTROPY;
+
+const FOV_STEP: f32 = 5.0;
+const FAR_STEP: f32 = 64.0;
+const INVENTORY_SEARCH: &str = "inventory_search";
+
+/// Changes requested by the options screen buttons, applied after the click is handled.
+#[derive(Debug, Clone, Copy)]
+enum OptionsAction {
+    Fov(f32),
+    RenderDistance(i32),
+    Far(f32),
+    CycleShadows,
+    CycleTextureFilter,
+    CycleAnisotropy,
+    Close,
+}
 
 pub struct UISystem {
     pub is_open: bool,
+    pub options_open: bool,
+    options_actions: Rc<RefCell<Vec<OptionsAction>>>,
+    /// Set when the widget tree must be rebuilt, see `invalidate`.
+    dirty: bool,
+    /// Inventory and search filter the current tree shows.
+    shown_inventory: Vec<Option<ItemStack>>,
+    shown_search: String,
+    ime_allowed: bool,
 }
 
 pub struct UIState {
@@ -16,11 +44,37 @@ pub trait Component {
 
 impl UISystem {
     pub fn new() -> Self {
-        Self { is_open: false }
+        Self {
+            is_open: false,
+            options_open: false,
+            options_actions: Rc::new(RefCell::new(Vec::new())),
+            dirty: true,
+            shown_inventory: Vec::new(),
+            shown_search: String::new(),
+            ime_allowed: false,
+        }
     }
 
     pub fn toggle(&mut self) {
         self.is_open = !self.is_open;
+        self.options_open = false;
+        self.invalidate();
+    }
+
+    pub fn toggle_options(&mut self) {
+        self.options_open = !self.options_open;
+        self.is_open = false;
+        self.invalidate();
+    }
+
+    /// Any screen that takes the mouse away from the game.
+    pub fn is_screen_open(&self) -> bool {
+        self.is_open || self.options_open
+    }
+
+    /// Rebuilds the widget tree on the next frame, for when what the screens show changed.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
     }
 
     pub fn render(
@@ -29,52 +83,59 @@ impl UISystem {
         player_pos: glam::Vec3,
         game_state: &crate::game_state::GameState,
     ) {
-        let mut ui = UI::new();
+        // The tree is only rebuilt when a screen opens or its contents change
+        let search = engine.renderer.ui
+            .update_widget(INVENTORY_SEARCH, |input: &mut TextInput| input.text.to_lowercase())
+            .unwrap_or_default();
+        let inventory = game_state.player.inventory.get_slots();
+        if self.is_open && (inventory != self.shown_inventory.as_slice() || search != self.shown_search) {
+            self.dirty = true;
+        }
+        if self.dirty {
+            self.dirty = false;
+            self.shown_inventory = inventory.to_vec();
+            self.shown_search = search;
+            let ui = self.build(engine, game_state);
+            engine.renderer.ui.set_ui(ui);
+            self.sync_ime(engine);
+        }
 
-        // Всегда показываем координаты
-        ui = ui.add_widget(
-            Text::new(&format!(
-                "x: {:.1} y: {:.1} z: {:.1}",
-                player_pos.x, player_pos.y, player_pos.z
-            ))
-            .with_style(|s| {
-                s.position = Vec2::new(16.0, 16.0);
-                s.color = WHITE;
-            }),
-        );
+        // HUD text changes every frame and is updated in place
+        let ui = &engine.renderer.ui;
+        ui.update_widget("hud_position", |text: &mut Text| {
+            text.text = format!("x: {:.1} y: {:.1} z: {:.1}", player_pos.x, player_pos.y, player_pos.z);
+        });
 
         // Показываем информацию о блоке
         let ray_pos = game_state.player.get_camera_position();
         let ray_dir = (game_state.player.get_camera_target() - ray_pos).normalize();
-        if let Some(hit) =
-            crate::systems::raycast::Raycast::cast_ray(ray_pos, ray_dir, 10.0, &game_state.world)
-        {
-            let block_id = game_state.world.get_block_at(hit.block_pos);
-            ui = ui.add_widget(
-                Text::new(&format!("Looking at: {} At {:?}", block_id, hit.block_pos)).with_style(
-                    |s| {
-                        s.position = Vec2::new(16.0, 40.0);
-                        s.color = WHITE;
-                    },
-                ),
-            );
-
-            ui = ui.add_widget(
-                Text::new(&format!(
-                    "Face: {:?} Distance: {:.1}",
-                    hit.face, hit.distance
-                ))
-                .with_style(|s| {
-                    s.position = Vec2::new(16.0, 64.0);
-                    s.color = WHITE;
-                }),
-            );
-        } else {
-            ui = ui.add_widget(Text::new("Looking at: air").with_style(|s| {
-                s.position = Vec2::new(16.0, 40.0);
-                s.color = WHITE;
-            }));
-        }
+        let (looking_at, face) = match crate::systems::raycast::Raycast::cast_ray(ray_pos, ray_dir, 10.0, &game_state.world) {
+            Some(hit) => (
+                format!("Looking at: {} At {:?}", game_state.world.get_block_at(hit.block_pos), hit.block_pos),
+                format!("Face: {:?} Distance: {:.1}", hit.face, hit.distance),
+            ),
+            None => ("Looking at: air".to_string(), String::new()),
+        };
+        ui.update_widget("hud_looking_at", |text: &mut Text| text.text = looking_at);
+        ui.update_widget("hud_face", |text: &mut Text| text.text = face);
+
+        let stats = engine.renderer.get_stats();
+        ui.update_widget("hud_meshes", |text: &mut Text| {
+            text.text = format!("Meshes: {} drawn, {} culled", stats.meshes_drawn, stats.meshes_culled);
+        });
+    }
+
+    fn build(&mut self, engine: &Engine, game_state: &crate::game_state::GameState) -> UI {
+        // Всегда показываем координаты и информацию о блоке, текст задаётся в `render`
+        let hud_text = |id: &str, y: f32| Text::new("").with_id(id).with_style(|s| {
+            s.position = Vec2::new(16.0, y);
+            s.color = WHITE;
+        });
+        let mut ui = UI::new()
+            .add_widget(hud_text("hud_position", 16.0))
+            .add_widget(hud_text("hud_looking_at", 40.0))
+            .add_widget(hud_text("hud_face", 64.0))
+            .add_widget(hud_text("hud_meshes", 88.0));
 
         // Прицел
         ui = ui.add_widget(Text::new("+").with_style(|s| {
@@ -84,13 +145,39 @@ impl UISystem {
         }));
 
         if self.is_open {
-            ui = ui.add_widget(self.create_inventory_ui());
+            ui = ui.add_widget(self.create_inventory_ui(game_state));
         }
-
-        engine.renderer.ui.set_ui(ui);
+        if self.options_open {
+            ui = ui.add_widget(self.create_options_ui(engine));
+        }
+        ui
     }
 
-    fn create_inventory_ui(&mut self) -> Container {
+    fn create_inventory_ui(&self, game_state: &crate::game_state::GameState) -> Container {
+        let mut slots = Container::new(LayoutType::Grid {
+            columns: 4,
+            spacing: 5.0,
+        })
+        .with_style(|s| {
+            s.size = Vec2::new(360.0, 260.0);
+            s.color = GRAY;
+            s.padding = Vec2::new(10.0, 10.0);
+        });
+        let search = &self.shown_search;
+        for slot in game_state.player.inventory.get_slots() {
+            if !search.is_empty() && !slot.as_ref().is_some_and(|stack| stack.item_id.to_lowercase().contains(search)) {
+                continue;
+            }
+            let (label, color) = match slot {
+                Some(stack) => (format!("{} x{}", stack.item_id, stack.count), LIGHT_GRAY),
+                None => (String::new(), DARK_GRAY),
+            };
+            slots = slots.add_button(Button::new(&label).with_style(|s| {
+                s.color = color;
+                s.size_mode = SizeMode::FillParent;
+            }));
+        }
+
         Container::new(LayoutType::Vertical { spacing: 10.0 })
             .with_style(|s| {
                 s.anchor = Anchor::Center;
@@ -112,36 +199,140 @@ impl UISystem {
                     .with_text_color(WHITE)
                     .on_click(|| println!("Close button clicked!")),
             )
+            .add_text_input(
+                TextInput::new()
+                    .with_id(INVENTORY_SEARCH)
+                    .with_placeholder("Search...")
+                    .with_max_length(32)
+                    .with_style(|s| s.size = Vec2::new(360.0, 30.0)),
+            )
+            .add_container(slots)
+    }
+
+    fn create_options_ui(&self, engine: &Engine) -> Container {
+        let camera = &engine.camera_settings;
+        Container::new(LayoutType::Vertical { spacing: 10.0 })
+            .with_style(|s| {
+                s.anchor = Anchor::Center;
+                s.size = Vec2::new(400.0, 300.0);
+                s.color = DARK_GRAY;
+                s.padding = Vec2::new(20.0, 20.0);
+            })
+            .add_text(
+                Text::new("Options")
+                    .with_style(|s| s.color = WHITE)
+                    .with_scale(2.0),
+            )
+            .add_container(self.create_option_row(
+                &format!("FOV: {:.0}", camera.fov),
+                OptionsAction::Fov(-FOV_STEP),
+                OptionsAction::Fov(FOV_STEP),
+            ))
+            .add_container(self.create_option_row(
+                &format!("Render distance: {}", camera.render_distance),
+                OptionsAction::RenderDistance(-1),
+                OptionsAction::RenderDistance(1),
+            ))
+            .add_container(self.create_option_row(
+                &format!("Far plane: {:.0}", camera.far),
+                OptionsAction::Far(-FAR_STEP),
+                OptionsAction::Far(FAR_STEP),
+            ))
             .add_container(
-                Container::new(LayoutType::Grid {
-                    columns: 4,
-                    spacing: 5.0,
-                })
-                .with_style(|s| {
-                    s.size = Vec2::new(360.0, 100.0);
-                    s.color = GRAY;
-                    s.padding = Vec2::new(10.0, 10.0);
-                })
-                .add_button(Button::new("Stone").with_style(|s| {
-                    s.color = LIGHT_GRAY;
-                    s.size_mode = SizeMode::FillParent;
-                }))
-                .add_button(Button::new("Dirt").with_style(|s| {
-                    s.color = BROWN;
-                    s.size_mode = SizeMode::FillParent;
-                }))
-                .add_button(Button::new("Grass").with_style(|s| {
-                    s.color = GREEN;
-                    s.size_mode = SizeMode::FillParent;
-                }))
-                .add_button(Button::new("Wood").with_style(|s| {
-                    s.color = BROWN;
-                    s.size_mode = SizeMode::FillParent;
-                })),
+                Container::new(LayoutType::Horizontal { spacing: 10.0 })
+                    .with_style(|s| s.size = Vec2::new(360.0, 30.0))
+                    .add_button(
+                        self.create_option_button(&format!("Shadows: {}", engine.graphics_settings.shadows.name()), OptionsAction::CycleShadows)
+                            .with_style(|s| s.size = Vec2::new(160.0, 30.0)),
+                    ),
             )
+            .add_container(
+                Container::new(LayoutType::Horizontal { spacing: 10.0 })
+                    .with_style(|s| s.size = Vec2::new(360.0, 30.0))
+                    .add_button(
+                        self.create_option_button(&format!("Textures: {}", engine.graphics_settings.texture_filter.name()), OptionsAction::CycleTextureFilter)
+                            .with_style(|s| s.size = Vec2::new(200.0, 30.0)),
+                    )
+                    .add_button(
+                        self.create_option_button(&format!("AF: {}x", engine.graphics_settings.anisotropy), OptionsAction::CycleAnisotropy)
+                            .with_style(|s| s.size = Vec2::new(100.0, 30.0)),
+                    ),
+            )
+            .add_button(self.create_option_button("Done", OptionsAction::Close).with_style(|s| {
+                s.size = Vec2::new(100.0, 30.0);
+                s.color = RED;
+            }))
+    }
+
+    fn create_option_row(&self, label: &str, decrease: OptionsAction, increase: OptionsAction) -> Container {
+        Container::new(LayoutType::Horizontal { spacing: 10.0 })
+            .with_style(|s| {
+                s.size = Vec2::new(360.0, 30.0);
+            })
+            .add_button(self.create_option_button("-", decrease).with_style(|s| s.size = Vec2::new(30.0, 30.0)))
+            .add_button(self.create_option_button("+", increase).with_style(|s| s.size = Vec2::new(30.0, 30.0)))
+            .add_text(Text::new(label).with_style(|s| s.color = WHITE))
+    }
+
+    fn create_option_button(&self, label: &str, action: OptionsAction) -> Button {
+        let actions = self.options_actions.clone();
+        Button::new(label)
+            .with_text_color(WHITE)
+            .on_click(move || actions.borrow_mut().push(action))
+    }
+
+    /// Keyboard and text input for a focused text field; returns whether it was used.
+    pub fn handle_input(&mut self, engine: &mut Engine, input: &InputEvent) -> bool {
+        let handled = engine.renderer.ui.handle_input(input);
+        self.sync_ime(engine);
+        handled
+    }
+
+    /// The IME is only on while a text field has focus, so it does not catch game keys.
+    fn sync_ime(&mut self, engine: &mut Engine) {
+        let focused = engine.renderer.ui.has_focus();
+        if focused != self.ime_allowed {
+            self.ime_allowed = focused;
+            engine.set_ime_allowed(focused);
+        }
+    }
+
+    pub fn handle_cursor_moved(&mut self, engine: &mut Engine, pos: Vec2) {
+        engine.renderer.ui.handle_cursor_moved(pos);
     }
 
     pub fn handle_click(&mut self, engine: &mut Engine, pos: Vec2) {
         engine.renderer.ui.handle_click(pos);
+        self.sync_ime(engine);
+        
+        let actions: Vec<OptionsAction> = self.options_actions.borrow_mut().drain(..).collect();
+        if actions.is_empty() {
+            return;
+        }
+        let mut camera = engine.camera_settings;
+        for action in actions {
+            match action {
+                OptionsAction::Fov(delta) => camera.fov += delta,
+                OptionsAction::RenderDistance(delta) => {
+                    camera.render_distance = camera.render_distance.saturating_add_signed(delta);
+                }
+                OptionsAction::Far(delta) => camera.far += delta,
+                OptionsAction::CycleShadows => {
+                    engine.graphics_settings.shadows = engine.graphics_settings.shadows.next();
+                }
+                OptionsAction::CycleTextureFilter => {
+                    engine.graphics_settings.texture_filter = engine.graphics_settings.texture_filter.next();
+                }
+                OptionsAction::CycleAnisotropy => {
+                    // 1x -> 2x -> ... -> 16x -> 1x
+                    let anisotropy = engine.graphics_settings.anisotropy * 2;
+                    engine.graphics_settings.anisotropy = if anisotropy > MAX_ANISOTROPY { 1 } else { anisotropy };
+                }
+                OptionsAction::Close => self.options_open = false,
+            }
+        }
+        engine.camera_settings = camera.clamped();
+        engine.save_settings();
+        self.invalidate();
     }
 }
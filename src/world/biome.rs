@@ -0,0 +1,35 @@
+use crate::world::chunk::CHUNK_HEIGHT;
+use crate::world::world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    Plains,
+    Desert,
+    Ocean,
+    Barren,
+}
+
+impl World {
+    /// Height of the first free block above the highest non-air block of a column, `None` for an empty or unloaded column.
+    pub fn get_surface_height(&self, x: i32, z: i32) -> Option<i32> {
+        (0..CHUNK_HEIGHT as i32)
+            .rev()
+            .find(|y| self.get_block((x, *y, z)).is_some())
+            .map(|y| y + 1)
+    }
+
+    /// There is no biome map yet, so the biome follows the block on top of the column.
+    pub fn get_biome(&self, x: i32, z: i32) -> Biome {
+        let Some(surface) = self.get_surface_height(x, z) else { return Biome::Barren };
+        let top = self.get_block((x, surface - 1, z));
+        match top {
+            Some(block) if block.get_fluid().is_some() => Biome::Ocean,
+            Some(block) => match block.get_id() {
+                "grass" | "dirt" => Biome::Plains,
+                "sand" => Biome::Desert,
+                _ => Biome::Barren,
+            },
+            None => Biome::Barren,
+        }
+    }
+}
@@ -1,18 +1,52 @@
-use crate::engine::{Engine, Vertex, render::texture_manager::TextureInfo};
-use crate::common::{block::Block, block_registry::BlockRegistry};
+use glam::Vec3;
+
+use crate::engine::{Engine, Vertex, model::aabb::Aabb, render::texture_manager::TextureInfo};
+use crate::common::{block::{Block, RenderLayer}, block_registry::BlockRegistry};
+use crate::world::fluid;
 
 pub const CHUNK_SIZE: usize = 16;
 pub const CHUNK_HEIGHT: usize = 64;
+pub const SEA_LEVEL: usize = 34;
+
+/// Brightness multiplier for each ambient occlusion level (0 = darkest corner).
+const AO_CURVE: [f32; 4] = [0.45, 0.65, 0.82, 1.0];
+
+/// Face normal and the four corners of the face relative to the block origin,
+/// in the winding order expected by the back-face culled pipeline.
+pub(crate) const FACES: [([i32; 3], [[f32; 3]; 4]); 6] = [
+    ([0, 0, 1], [[0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [1.0, 1.0, 1.0], [0.0, 1.0, 1.0]]),   // Front
+    ([0, 0, -1], [[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]]),  // Back
+    ([1, 0, 0], [[1.0, 0.0, 1.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [1.0, 1.0, 1.0]]),   // Right
+    ([-1, 0, 0], [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 1.0], [0.0, 1.0, 0.0]]),  // Left
+    ([0, 1, 0], [[0.0, 1.0, 1.0], [1.0, 1.0, 1.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]),   // Top
+    ([0, -1, 0], [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 1.0], [0.0, 0.0, 1.0]]),  // Bottom
+];
 
 pub struct Chunk {
     pub blocks: Vec<Vec<Vec<Option<Box<dyn Block>>>>>,
+    /// Per-block state byte (fluid level etc.), same layout as `blocks`.
+    pub states: Vec<Vec<Vec<u8>>>,
     pub position: (i32, i32),
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u16>,
+    pub translucent_vertices: Vec<Vertex>,
+    pub translucent_indices: Vec<u16>,
+    pub ambient_occlusion: bool,
+    /// Blocks changed since the last mesh rebuild.
+    pub dirty: bool,
 }
 
 impl Chunk {
-    pub fn new(engine: &Engine, x: i32, z: i32, registry: &BlockRegistry) -> Self {
+    /// A freshly generated chunk; its mesh is built on the next `World::render`.
+    pub fn generated(x: i32, z: i32, registry: &BlockRegistry) -> Self {
+        let mut chunk = Self::empty(x, z);
+        chunk.generate_terrain(registry);
+        chunk.dirty = true;
+        chunk
+    }
+    
+    /// A chunk full of air with no mesh yet.
+    pub fn empty(x: i32, z: i32) -> Self {
         let mut blocks = Vec::with_capacity(CHUNK_SIZE);
         for _ in 0..CHUNK_SIZE {
             let mut y_vec = Vec::with_capacity(CHUNK_HEIGHT);
@@ -26,16 +60,19 @@ impl Chunk {
             blocks.push(y_vec);
         }
         
-        let mut chunk = Self {
+        let states = vec![vec![vec![0u8; CHUNK_SIZE]; CHUNK_HEIGHT]; CHUNK_SIZE];
+        
+        Self {
             blocks,
+            states,
             position: (x, z),
             vertices: Vec::new(),
             indices: Vec::new(),
-        };
-        
-        chunk.generate_terrain(registry);
-        chunk.generate_mesh(engine, registry);
-        chunk
+            translucent_vertices: Vec::new(),
+            translucent_indices: Vec::new(),
+            ambient_occlusion: true,
+            dirty: false,
+        }
     }
     
     fn generate_terrain(&mut self, registry: &BlockRegistry) {
@@ -43,8 +80,10 @@ impl Chunk {
             for z in 0..CHUNK_SIZE {
                 let height = 32 + ((x + z) % 8) as usize;
                 
-                for y in 0..height.min(CHUNK_HEIGHT) {
-                    let block_name = if y < height - 4 {
+                for y in 0..height.max(SEA_LEVEL).min(CHUNK_HEIGHT) {
+                    let block_name = if y >= height {
+                        "water"
+                    } else if y < height - 4 {
                         "stone"
                     } else if y < height - 1 {
                         "dirt"
@@ -60,18 +99,6 @@ impl Chunk {
         }
     }
     
-    fn get_block_name(&self, x: i32, y: i32, z: i32) -> &str {
-        if x < 0 || x >= CHUNK_SIZE as i32 || y < 0 || y >= CHUNK_HEIGHT as i32 || z < 0 || z >= CHUNK_SIZE as i32 {
-            return "air";
-        }
-        
-        match &self.blocks[x as usize][y as usize][z as usize] {
-            Some(block) => block.get_name(),
-            None => "air",
-        }
-    }
-
-    
     fn get_block_id(&self, x: i32, y: i32, z: i32) -> &str {
         if x < 0 || x >= CHUNK_SIZE as i32 || y < 0 || y >= CHUNK_HEIGHT as i32 || z < 0 || z >= CHUNK_SIZE as i32 {
             return "air";
@@ -83,23 +110,56 @@ impl Chunk {
         }
     }
     
-    pub fn set_block(&mut self, engine: &Engine, x: usize, y: usize, z: usize, block_name: &str, registry: &BlockRegistry) {
+    /// Changes a block without rebuilding the mesh; the chunk is marked dirty instead.
+    pub fn set_block_with_state(&mut self, x: usize, y: usize, z: usize, block_name: &str, state: u8, registry: &BlockRegistry) {
         if x >= CHUNK_SIZE || y >= CHUNK_HEIGHT || z >= CHUNK_SIZE {
             return;
         }
         
         if block_name == "air" {
             self.blocks[x][y][z] = None;
+            self.states[x][y][z] = 0;
         } else if let Some(block) = registry.create_block(block_name) {
             self.blocks[x][y][z] = Some(block);
+            self.states[x][y][z] = state;
         }
-        
-        self.generate_mesh(engine, registry);
+        self.dirty = true;
     }
     
-    fn generate_mesh(&mut self, engine: &Engine, registry: &BlockRegistry) {
+    /// World-space box around the whole chunk column.
+    pub fn bounds(&self) -> Aabb {
+        let min = Vec3::new(
+            (self.position.0 * CHUNK_SIZE as i32) as f32,
+            0.0,
+            (self.position.1 * CHUNK_SIZE as i32) as f32,
+        );
+        Aabb::new(min, min + Vec3::new(CHUNK_SIZE as f32, CHUNK_HEIGHT as f32, CHUNK_SIZE as f32))
+    }
+    
+    pub fn get_state(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.states[x][y][z]
+    }
+    
+    /// Remeshed on the next `World::render` if the setting changed.
+    pub fn set_ambient_occlusion(&mut self, enabled: bool) {
+        if self.ambient_occlusion != enabled {
+            self.ambient_occlusion = enabled;
+            self.dirty = true;
+        }
+    }
+    
+    pub fn generate_mesh(&mut self, engine: &Engine, registry: &BlockRegistry) {
+        let textures = &engine.renderer.texture_manager;
+        self.build_mesh(registry, |index| textures.get_texture_info_by_id(index));
+    }
+    
+    /// Meshes the blocks, with `texture_info` looking up each block's texture.
+    fn build_mesh<'a>(&mut self, registry: &BlockRegistry, texture_info: impl Fn(u32) -> Option<&'a TextureInfo>) {
+        self.dirty = false;
         self.vertices.clear();
         self.indices.clear();
+        self.translucent_vertices.clear();
+        self.translucent_indices.clear();
         
         let chunk_world_x = self.position.0 * CHUNK_SIZE as i32;
         let chunk_world_z = self.position.1 * CHUNK_SIZE as i32;
@@ -107,94 +167,304 @@ impl Chunk {
         for x in 0..CHUNK_SIZE as i32 {
             for y in 0..CHUNK_HEIGHT as i32 {
                 for z in 0..CHUNK_SIZE as i32 {
-                    let block_id = self.get_block_id(x, y, z).to_owned();
-                    if block_id == "air" {
-                        continue;
-                    }
+                    let (block_id, translucent, is_fluid) = match &self.blocks[x as usize][y as usize][z as usize] {
+                        Some(block) => (block.get_id(), block.get_render_layer() == RenderLayer::Translucent, block.get_fluid().is_some()),
+                        None => continue,
+                    };
                     
-                    let world_x = chunk_world_x + x;
-                    let world_y = y;
-                    let world_z = chunk_world_z + z;
+                    let world_origin = [(chunk_world_x + x) as f32, y as f32, (chunk_world_z + z) as f32];
                     
-                    let tex_index = registry.get_texture_index(block_id.as_str());
-                    let some_tex_info = engine.renderer.texture_manager.get_texture_info_by_id(tex_index);
+                    let some_tex_info = texture_info(registry.get_texture_index(block_id));
 
-                    if let Some(texture_info) = some_tex_info{
-                        if self.get_block_name(x, y, z + 1) == "air" {
-                            self.add_face([world_x as f32, world_y as f32, (world_z + 1) as f32], [0.0, 0.0, 1.0], texture_info);
-                        }
-                        if self.get_block_name(x, y, z - 1) == "air" {
-                            self.add_face([world_x as f32, world_y as f32, world_z as f32], [0.0, 0.0, -1.0], texture_info);
-                        }
-                        if self.get_block_name(x + 1, y, z) == "air" {
-                            self.add_face([(world_x + 1) as f32, world_y as f32, world_z as f32], [1.0, 0.0, 0.0], texture_info);
-                        }
-                        if self.get_block_name(x - 1, y, z) == "air" {
-                            self.add_face([world_x as f32, world_y as f32, world_z as f32], [-1.0, 0.0, 0.0], texture_info);
-                        }
-                        if self.get_block_name(x, y + 1, z) == "air" {
-                            self.add_face([world_x as f32, (world_y + 1) as f32, world_z as f32], [0.0, 1.0, 0.0], texture_info);
+                    // Check each face and add if exposed
+                    if let Some(texture_info) = some_tex_info {
+                        if is_fluid {
+                            self.add_fluid_faces((x, y, z), block_id, world_origin, texture_info, translucent);
+                            continue;
                         }
-                        if self.get_block_name(x, y - 1, z) == "air" {
-                            self.add_face([world_x as f32, world_y as f32, world_z as f32], [0.0, -1.0, 0.0], texture_info);
+                        for (normal, corners) in FACES.iter() {
+                            if self.is_face_visible((x, y, z), *normal) {
+                                self.add_face((x, y, z), world_origin, *normal, corners, texture_info, translucent);
+                            }
                         }
                     }
-                    // Check each face and add if exposed
                 }
             }
         }
     }
     
-    fn add_face(&mut self, position: [f32; 3], normal: [f32; 3], texture_info: &TextureInfo) {
-        let base_index = self.vertices.len() as u16;
-        // Получаем информацию о текстуре из atlas_position и dimensions
-        // Размер атласа (должен совпадать с размером в TextureManager)
-
-        let (u_min,v_min, u_max, v_max) = texture_info.uvs;
+    /// Fluid surface: the top of every face is lowered to the averaged
+    /// fluid height at each corner, which gives sloped flowing surfaces.
+    fn add_fluid_faces(&mut self, block: (i32, i32, i32), fluid_id: &str, origin: [f32; 3], texture_info: &TextureInfo, translucent: bool) {
+        let (x, y, z) = block;
+        let covered = self.get_block_id(x, y + 1, z) == fluid_id;
+        let corner_heights = [
+            [self.fluid_corner_height(block, fluid_id, 0, 0), self.fluid_corner_height(block, fluid_id, 0, 1)],
+            [self.fluid_corner_height(block, fluid_id, 1, 0), self.fluid_corner_height(block, fluid_id, 1, 1)],
+        ];
+        
+        for (normal, corners) in FACES.iter() {
+            let visible = if normal[1] == 1 {
+                !covered
+            } else {
+                self.is_face_visible(block, *normal)
+            };
+            if !visible {
+                continue;
+            }
+            
+            let corners = corners.map(|corner| {
+                if corner[1] > 0.5 {
+                    [corner[0], corner_heights[corner[0] as usize][corner[2] as usize], corner[2]]
+                } else {
+                    corner
+                }
+            });
+            self.add_face(block, origin, *normal, &corners, texture_info, translucent);
+        }
+    }
+    
+    /// Surface height of the fluid column at one of the block's top corners,
+    /// averaged over the four blocks that share that corner.
+    fn fluid_corner_height(&self, block: (i32, i32, i32), fluid_id: &str, corner_x: i32, corner_z: i32) -> f32 {
+        let mut total = 0.0;
+        let mut weight = 0.0;
+        for dx in (corner_x - 1)..=corner_x {
+            for dz in (corner_z - 1)..=corner_z {
+                let (x, y, z) = (block.0 + dx, block.1, block.2 + dz);
+                if x < 0 || x >= CHUNK_SIZE as i32 || z < 0 || z >= CHUNK_SIZE as i32 {
+                    // Соседний чанк не виден отсюда
+                    continue;
+                }
+                if self.get_block_id(x, y + 1, z) == fluid_id {
+                    return 1.0;
+                }
+                if self.get_block_id(x, y, z) == fluid_id {
+                    total += fluid::surface_height(self.get_state(x as usize, y as usize, z as usize));
+                    weight += 1.0;
+                } else if !self.is_occluder(x, y, z) {
+                    weight += 1.0;
+                }
+            }
+        }
+        
+        if weight > 0.0 {
+            total / weight
+        } else {
+            fluid::surface_height(self.get_state(block.0 as usize, block.1 as usize, block.2 as usize))
+        }
+    }
+    
+    /// A face is drawn unless the neighbour hides it: opaque neighbours hide everything,
+    /// see-through neighbours (cutout or translucent) only hide faces of the same block type
+    /// (glass next to glass).
+    fn is_face_visible(&self, block: (i32, i32, i32), normal: [i32; 3]) -> bool {
+        let (nx, ny, nz) = (block.0 + normal[0], block.1 + normal[1], block.2 + normal[2]);
+        if nx < 0 || nx >= CHUNK_SIZE as i32 || ny < 0 || ny >= CHUNK_HEIGHT as i32 || nz < 0 || nz >= CHUNK_SIZE as i32 {
+            return true;
+        }
+        
+        let neighbour = match &self.blocks[nx as usize][ny as usize][nz as usize] {
+            Some(neighbour) => neighbour,
+            None => return true,
+        };
+        if neighbour.get_render_layer() == RenderLayer::Opaque {
+            return false;
+        }
+        neighbour.get_id() != self.get_block_id(block.0, block.1, block.2)
+    }
+    
+    fn is_occluder(&self, x: i32, y: i32, z: i32) -> bool {
+        if x < 0 || x >= CHUNK_SIZE as i32 || y < 0 || y >= CHUNK_HEIGHT as i32 || z < 0 || z >= CHUNK_SIZE as i32 {
+            return false;
+        }
+        
+        match &self.blocks[x as usize][y as usize][z as usize] {
+            Some(block) => block.get_render_layer() == RenderLayer::Opaque,
+            None => false,
+        }
+    }
+    
+    /// Classic 3-neighbour ambient occlusion for one corner of a face.
+    /// Returns 0 (fully occluded) ..= 3 (unoccluded).
+    fn corner_ao(&self, block: (i32, i32, i32), normal: [i32; 3], corner: [f32; 3]) -> usize {
+        // Соседи лежат в слое перед гранью
+        let layer = [block.0 + normal[0], block.1 + normal[1], block.2 + normal[2]];
+        let mut side_offsets = [[0i32; 3]; 2];
+        let mut side = 0;
+        for axis in 0..3 {
+            if normal[axis] == 0 {
+                side_offsets[side][axis] = if corner[axis] > 0.5 { 1 } else { -1 };
+                side += 1;
+            }
+        }
+        
+        let [a, b] = side_offsets;
+        let side1 = self.is_occluder(layer[0] + a[0], layer[1] + a[1], layer[2] + a[2]);
+        let side2 = self.is_occluder(layer[0] + b[0], layer[1] + b[1], layer[2] + b[2]);
+        if side1 && side2 {
+            return 0;
+        }
+        let corner = self.is_occluder(layer[0] + a[0] + b[0], layer[1] + a[1] + b[1], layer[2] + a[2] + b[2]);
+        3 - (side1 as usize + side2 as usize + corner as usize)
+    }
+    
+    fn add_face(&mut self, block: (i32, i32, i32), origin: [f32; 3], normal: [i32; 3], corners: &[[f32; 3]; 4], texture_info: &TextureInfo, translucent: bool) {
+        let (u_min, v_min, u_max, v_max) = texture_info.uvs;
         let tex_index = texture_info.atlas_position.2;
-        match normal {
-            [0.0, 0.0, 1.0] => { // Front
-                self.vertices.push(Vertex::new([position[0], position[1], position[2]], normal, [u_min, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1], position[2]], normal, [u_max, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1] + 1.0, position[2]], normal, [u_max, v_min], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1] + 1.0, position[2]], normal, [u_min, v_min], tex_index, 1.0));
-            },
-            [0.0, 0.0, -1.0] => { // Back
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1], position[2]], normal, [u_min, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1], position[2]], normal, [u_max, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1] + 1.0, position[2]], normal, [u_max, v_min], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1] + 1.0, position[2]], normal, [u_min, v_min], tex_index, 1.0));
-            },
-            [1.0, 0.0, 0.0] => { // Right
-                self.vertices.push(Vertex::new([position[0], position[1], position[2] + 1.0], normal, [u_min, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1], position[2]], normal, [u_max, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1] + 1.0, position[2]], normal, [u_max, v_min], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1] + 1.0, position[2] + 1.0], normal, [u_min, v_min], tex_index, 1.0));
-            },
-            [-1.0, 0.0, 0.0] => { // Left
-                self.vertices.push(Vertex::new([position[0], position[1], position[2]], normal, [u_min, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1], position[2] + 1.0], normal, [u_max, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1] + 1.0, position[2] + 1.0], normal, [u_max, v_min], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1] + 1.0, position[2]], normal, [u_min, v_min], tex_index, 1.0));
-            },
-            [0.0, 1.0, 0.0] => { // Top
-                self.vertices.push(Vertex::new([position[0], position[1], position[2] + 1.0], normal, [u_min, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1], position[2] + 1.0], normal, [u_max, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1], position[2]], normal, [u_max, v_min], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1], position[2]], normal, [u_min, v_min], tex_index, 1.0));
-            },
-            [0.0, -1.0, 0.0] => { // Bottom
-                self.vertices.push(Vertex::new([position[0], position[1], position[2]], normal, [u_min, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1], position[2]], normal, [u_max, v_max], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0] + 1.0, position[1], position[2] + 1.0], normal, [u_max, v_min], tex_index, 1.0));
-                self.vertices.push(Vertex::new([position[0], position[1], position[2] + 1.0], normal, [u_min, v_min], tex_index, 1.0));
-            },
-            _ => return,
+        let uvs = [[u_min, v_max], [u_max, v_max], [u_max, v_min], [u_min, v_min]];
+        let normal_f = [normal[0] as f32, normal[1] as f32, normal[2] as f32];
+        
+        let ao = if self.ambient_occlusion {
+            corners.map(|corner| self.corner_ao(block, normal, corner))
+        } else {
+            [3; 4]
+        };
+        
+        let (vertices, indices) = if translucent {
+            (&mut self.translucent_vertices, &mut self.translucent_indices)
+        } else {
+            (&mut self.vertices, &mut self.indices)
+        };
+        let base_index = vertices.len() as u16;
+        
+        for i in 0..4 {
+            let corner = corners[i];
+            vertices.push(Vertex::new(
+                [origin[0] + corner[0], origin[1] + corner[1], origin[2] + corner[2]],
+                normal_f,
+                uvs[i],
+                tex_index,
+                AO_CURVE[ao[i]],
+            ));
         }
         
-        self.indices.extend_from_slice(&[
-            base_index, base_index + 1, base_index + 2,
-            base_index + 2, base_index + 3, base_index,
-        ]);
+        // Переворачиваем диагональ квада, чтобы AO интерполировался без анизотропии
+        if ao[0] + ao[2] < ao[1] + ao[3] {
+            indices.extend_from_slice(&[
+                base_index + 1, base_index + 2, base_index + 3,
+                base_index + 3, base_index, base_index + 1,
+            ]);
+        } else {
+            indices.extend_from_slice(&[
+                base_index, base_index + 1, base_index + 2,
+                base_index + 2, base_index + 3, base_index,
+            ]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::modding::lua_block::LuaBlock;
+    use crate::engine::render::golden::{check_golden, test::test_engine};
+
+    fn test_texture() -> TextureInfo {
+        TextureInfo {
+            id: 0,
+            path: String::new(),
+            dimensions: (16, 16),
+            atlas_position: (0, 0, 0),
+            uvs: (0.0, 0.0, 1.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn inner_corner_darkens_and_flips_the_quad() {
+        let registry = BlockRegistry::headless();
+        let mut chunk = Chunk::empty(0, 0);
+        // Floor with walls along x = 0 and z = 0 standing on it
+        for i in 0..3 {
+            for j in 0..3 {
+                chunk.set_block_with_state(i, 0, j, "stone", 0, &registry);
+            }
+            chunk.set_block_with_state(0, 1, i, "stone", 0, &registry);
+            chunk.set_block_with_state(i, 1, 0, "stone", 0, &registry);
+        }
+
+        // Top face of the floor block in the corner of the walls
+        let (normal, corners) = FACES[4];
+        let ao = corners.map(|corner| chunk.corner_ao((1, 0, 1), normal, corner));
+        // Corners [0,1,1], [1,1,1], [1,1,0], [0,1,0]: one wall, open, one wall, both walls
+        assert_eq!(ao, [1, 3, 1, 0]);
+
+        chunk.add_face((1, 0, 1), [1.0, 0.0, 1.0], normal, &corners, &test_texture(), false);
+        // Corners 0 and 2 are darker than 1 and 3 together, so the quad is split along 1-3
+        assert_eq!(chunk.indices, vec![1, 2, 3, 3, 0, 1]);
+
+        // Without walls every corner is open and the quad keeps its usual diagonal
+        let mut open = Chunk::empty(0, 0);
+        open.set_block_with_state(1, 0, 1, "stone", 0, &registry);
+        assert_eq!(corners.map(|corner| open.corner_ao((1, 0, 1), normal, corner)), [3; 4]);
+        open.add_face((1, 0, 1), [1.0, 0.0, 1.0], normal, &corners, &test_texture(), false);
+        assert_eq!(open.indices, vec![0, 1, 2, 2, 3, 0]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn lava_is_meshed_as_translucent_fluid() {
+        let registry = BlockRegistry::headless();
+        let mut chunk = Chunk::empty(0, 0);
+        chunk.set_block_with_state(1, 1, 1, "stone", 0, &registry);
+        chunk.set_block_with_state(2, 1, 1, "lava", 0, &registry);
+        chunk.set_block_with_state(3, 1, 1, "lava", 0, &registry);
+        let texture = test_texture();
+        chunk.build_mesh(&registry, |_| Some(&texture));
+
+        // Lava does not hide the stone face next to it
+        assert_eq!(chunk.vertices.len(), 6 * 4);
+        // Stone hides one face of the lava and the two lava blocks hide their shared faces
+        assert_eq!(chunk.translucent_vertices.len(), (12 - 3) * 4);
+    }
+
+    #[test]
+    fn cutout_block_does_not_hide_or_shade_neighbours() {
+        let registry = BlockRegistry::headless();
+        let mut chunk = Chunk::empty(0, 0);
+        chunk.set_block_with_state(1, 1, 1, "stone", 0, &registry);
+        // A mod block with holes in its texture that did not also set transparent = true
+        chunk.blocks[2][1][1] = Some(Box::new(LuaBlock {
+            id: "leaves",
+            name: "Leaves",
+            texture_path: "",
+            solid: true,
+            transparent: false,
+            render_layer: RenderLayer::Cutout,
+            gravity: false,
+            animation: None,
+        }));
+        let texture = test_texture();
+        chunk.build_mesh(&registry, |_| Some(&texture));
+
+        // The stone face behind the leaves stays, only the leaves face against the stone is hidden
+        assert_eq!(chunk.vertices.len(), (6 + 5) * 4);
+        assert!(!chunk.is_occluder(2, 1, 1));
+        assert!(chunk.is_occluder(1, 1, 1));
+    }
+
+    #[test]
+    #[ignore = "needs a GPU or software adapter"]
+    fn chunk_mesh_matches_golden() {
+        let mut engine = test_engine(320, 240);
+        let registry = BlockRegistry::new(&mut engine);
+        let mut chunk = Chunk::generated(0, 0, &registry);
+        chunk.generate_mesh(&engine, &registry);
+        assert!(!chunk.vertices.is_empty());
+
+        engine.renderer.set_chunk_mesh(
+            chunk.position,
+            bytemuck::cast_slice(&chunk.vertices),
+            &chunk.indices,
+            bytemuck::cast_slice(&chunk.translucent_vertices),
+            &chunk.translucent_indices,
+            chunk.bounds(),
+        );
+        engine.update_camera(Vec3::new(-12.0, 56.0, -12.0), Vec3::new(8.0, 34.0, 8.0), Vec3::Y);
+        engine.render().expect("offscreen render");
+
+        let frame = engine.renderer.read_frame().expect("read back");
+        check_golden("chunk_terrain", &frame).unwrap();
+    }
+}
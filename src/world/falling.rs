@@ -0,0 +1,40 @@
+use glam::Vec3;
+
+use crate::entity::falling_block::FallingBlockEntity;
+use crate::world::world::World;
+
+/// Delay between a gravity block losing support and starting to fall.
+pub const FALL_DELAY: u64 = 2;
+
+impl World {
+    /// Queues a support check for a gravity block, e.g. after it was placed or a neighbour changed.
+    pub fn schedule_gravity_check(&mut self, pos: (i32, i32, i32)) {
+        if self.get_block(pos).map_or(false, |block| block.has_gravity()) {
+            self.schedule_tick(pos, FALL_DELAY);
+        }
+    }
+
+    /// Turns the gravity block at `pos` into a falling block entity if nothing holds it up.
+    pub fn start_falling(&mut self, pos: (i32, i32, i32)) -> bool {
+        let block_id = match self.get_block(pos) {
+            Some(block) if block.has_gravity() => block.get_id().to_string(),
+            _ => return false,
+        };
+        if !self.can_fall_into((pos.0, pos.1 - 1, pos.2)) {
+            return false;
+        }
+
+        self.set_block(pos, "air", 0);
+        let position = Vec3::new(pos.0 as f32 + 0.5, pos.1 as f32, pos.2 as f32 + 0.5);
+        self.spawn_entity(Box::new(FallingBlockEntity::new(&block_id)), position);
+        true
+    }
+
+    /// Falling blocks pass through air and fluids; unloaded chunks count as solid ground.
+    pub(crate) fn can_fall_into(&self, pos: (i32, i32, i32)) -> bool {
+        if !self.is_loaded(pos) {
+            return false;
+        }
+        self.get_block(pos).map_or(true, |block| block.get_fluid().is_some())
+    }
+}
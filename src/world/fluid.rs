@@ -0,0 +1,269 @@
+use crate::common::block::FluidProperties;
+use crate::world::world::World;
+
+// Fluid level is stored in the block state:
+//   bits 0..3 - distance from the nearest source (0 = source block, 1..=7 = flowing)
+//   bit 3     - falling column (fed from above, always full height)
+pub const LEVEL_MASK: u8 = 0x07;
+pub const FALLING: u8 = 0x08;
+pub const SOURCE: u8 = 0;
+
+pub fn level(state: u8) -> u8 {
+    state & LEVEL_MASK
+}
+
+pub fn is_source(state: u8) -> bool {
+    state == SOURCE
+}
+
+pub fn is_falling(state: u8) -> bool {
+    state & FALLING != 0
+}
+
+/// Height of the fluid surface inside its block, 0..=1.
+pub fn surface_height(state: u8) -> f32 {
+    if is_falling(state) {
+        return 1.0;
+    }
+    (8 - level(state)) as f32 / 9.0
+}
+
+const HORIZONTAL: [(i32, i32, i32); 4] = [(1, 0, 0), (-1, 0, 0), (0, 0, 1), (0, 0, -1)];
+
+fn offset(pos: (i32, i32, i32), d: (i32, i32, i32)) -> (i32, i32, i32) {
+    (pos.0 + d.0, pos.1 + d.1, pos.2 + d.2)
+}
+
+impl World {
+    /// Schedules the next flow update of the fluid at `pos` after its fluid's tick delay.
+    pub fn schedule_fluid_tick(&mut self, pos: (i32, i32, i32)) {
+        if let Some(fluid) = self.get_block(pos).and_then(|b| b.get_fluid()) {
+            self.schedule_tick(pos, fluid.tick_delay);
+        }
+    }
+
+    /// One flow step for the fluid at `pos`; called from the fluid block's `on_tick`.
+    pub fn update_fluid(&mut self, pos: (i32, i32, i32)) {
+        let (fluid_id, fluid) = match self.get_block(pos) {
+            Some(block) => match block.get_fluid() {
+                Some(fluid) => (block.get_id(), fluid),
+                None => return,
+            },
+            None => return,
+        };
+        let mut state = self.get_block_state(pos);
+
+        // Flowing blocks re-derive their level from their neighbours, so removing a source drains them
+        if !is_source(state) {
+            match self.expected_fluid_state(pos, fluid_id, &fluid) {
+                Some(expected) if expected != state => {
+                    // Neighbours pick the change up through on_neighbour_changed
+                    self.set_block(pos, fluid_id, expected);
+                    state = expected;
+                }
+                Some(_) => {}
+                None => {
+                    self.set_block(pos, "air", 0);
+                    return;
+                }
+            }
+        }
+
+        let below = offset(pos, (0, -1, 0));
+        let flows_down = self.can_fluid_replace(below, fluid_id);
+        if flows_down {
+            let below_state = self.get_block_state(below);
+            if self.get_block_id(below) != fluid_id || !is_falling(below_state) {
+                self.set_block(below, fluid_id, FALLING);
+                self.schedule_fluid_tick(below);
+            }
+            if !is_source(state) {
+                return;
+            }
+        }
+
+        let next_level = if is_falling(state) { 1 } else { level(state) + 1 };
+        if next_level > fluid.max_spread.min(LEVEL_MASK) {
+            return;
+        }
+        for d in HORIZONTAL {
+            let target = offset(pos, d);
+            if !self.can_fluid_replace(target, fluid_id) {
+                continue;
+            }
+            if self.get_block_id(target) == fluid_id {
+                let target_state = self.get_block_state(target);
+                if is_falling(target_state) || level(target_state) <= next_level {
+                    continue;
+                }
+            }
+            self.set_block(target, fluid_id, next_level);
+            self.schedule_fluid_tick(target);
+        }
+    }
+
+    /// State a non-source fluid block should have given its surroundings, `None` if it should dry up.
+    fn expected_fluid_state(&self, pos: (i32, i32, i32), fluid_id: &str, fluid: &FluidProperties) -> Option<u8> {
+        if self.get_block_id(offset(pos, (0, 1, 0))) == fluid_id {
+            return Some(FALLING);
+        }
+
+        let mut sources = 0;
+        let mut lowest_level = u8::MAX;
+        for d in HORIZONTAL {
+            let neighbour = offset(pos, d);
+            if self.get_block_id(neighbour) != fluid_id {
+                continue;
+            }
+            let neighbour_state = self.get_block_state(neighbour);
+            if is_source(neighbour_state) {
+                sources += 1;
+            }
+            let neighbour_level = if is_falling(neighbour_state) { 0 } else { level(neighbour_state) };
+            lowest_level = lowest_level.min(neighbour_level);
+        }
+
+        if fluid.infinite_source && sources >= 2 {
+            let below = offset(pos, (0, -1, 0));
+            let below_supports = match self.get_block(below) {
+                Some(block) if block.get_id() == fluid_id => is_source(self.get_block_state(below)),
+                Some(block) => block.is_solid(),
+                None => false,
+            };
+            if below_supports {
+                return Some(SOURCE);
+            }
+        }
+
+        if lowest_level == u8::MAX {
+            return None;
+        }
+        let level = lowest_level + 1;
+        if level > fluid.max_spread.min(LEVEL_MASK) {
+            None
+        } else {
+            Some(level)
+        }
+    }
+
+    /// Fluid can flow into air and into non-source blocks of the same fluid.
+    fn can_fluid_replace(&self, pos: (i32, i32, i32), fluid_id: &str) -> bool {
+        if !self.is_loaded(pos) {
+            return false;
+        }
+        match self.get_block(pos) {
+            None => true,
+            Some(block) => block.get_id() == fluid_id && !is_source(self.get_block_state(pos)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn place_source(world: &mut World, pos: (i32, i32, i32), fluid_id: &str) {
+        world.set_block(pos, fluid_id, SOURCE);
+        world.schedule_fluid_tick(pos);
+    }
+
+    fn run_ticks(world: &mut World, ticks: u32) {
+        for _ in 0..ticks {
+            world.tick();
+        }
+    }
+
+    #[test]
+    fn water_spreads_with_decaying_level_and_drains_without_source() {
+        let mut world = World::flat(1, "stone");
+        place_source(&mut world, (8, 1, 8), "water");
+        run_ticks(&mut world, 100);
+
+        for distance in 1..=7 {
+            assert_eq!(world.get_block_id((8 + distance, 1, 8)), "water");
+            assert_eq!(world.get_block_state((8 + distance, 1, 8)), distance as u8);
+        }
+        assert_eq!(world.get_block_state((10, 1, 11)), 5);
+        // One block past max_spread
+        assert_eq!(world.get_block_id((0, 1, 8)), "air");
+        assert!(surface_height(1) > surface_height(7));
+
+        world.set_block((8, 1, 8), "air", 0);
+        run_ticks(&mut world, 400);
+        for x in 0..16 {
+            for z in 0..16 {
+                assert_eq!(world.get_block_id((x, 1, z)), "air", "water left at {}, {}", x, z);
+            }
+        }
+    }
+
+    #[test]
+    fn water_falls_as_a_full_column() {
+        let mut world = World::flat(1, "stone");
+        place_source(&mut world, (8, 5, 8), "water");
+        run_ticks(&mut world, 100);
+
+        for y in 1..5 {
+            let state = world.get_block_state((8, y, 8));
+            assert_eq!(world.get_block_id((8, y, 8)), "water");
+            assert!(is_falling(state), "state {} at y = {}", state, y);
+            assert_eq!(surface_height(state), 1.0);
+        }
+        // The source still spreads sideways and that flow falls down beside the column
+        assert_eq!(world.get_block_state((9, 5, 8)), 1);
+        assert!(is_falling(world.get_block_state((9, 1, 8))));
+        // Landed columns spread along the floor as if fed from a full block
+        assert_eq!(world.get_block_state((8, 1, 11)), 2);
+    }
+
+    #[test]
+    fn two_water_sources_create_a_third() {
+        let mut world = World::flat(1, "stone");
+        place_source(&mut world, (4, 1, 8), "water");
+        place_source(&mut world, (6, 1, 8), "water");
+        run_ticks(&mut world, 50);
+
+        assert!(is_source(world.get_block_state((5, 1, 8))));
+        // Only one source next to it
+        assert_eq!(world.get_block_state((3, 1, 8)), 1);
+    }
+
+    #[test]
+    fn lava_spreads_less_slower_and_makes_no_sources() {
+        let mut world = World::flat(1, "stone");
+        place_source(&mut world, (4, 1, 4), "lava");
+        place_source(&mut world, (4, 1, 6), "lava");
+        place_source(&mut world, (12, 1, 12), "water");
+
+        // Water is three blocks out after three of its delays, lava has not moved yet
+        run_ticks(&mut world, 15);
+        assert_eq!(world.get_block_id((15, 1, 12)), "water");
+        assert_eq!(world.get_block_id((7, 1, 4)), "air");
+        assert_eq!(world.get_block_id((4, 1, 5)), "air");
+
+        run_ticks(&mut world, 300);
+        assert_eq!(world.get_block_state((7, 1, 4)), 3);
+        assert_eq!(world.get_block_id((8, 1, 4)), "air");
+        assert_eq!(world.get_block_id((4, 1, 5)), "lava");
+        assert_eq!(world.get_block_state((4, 1, 5)), 1);
+
+        // Neither fluid flows into the other
+        assert!(!world.can_fluid_replace((4, 1, 4), "water"));
+        assert!(!world.can_fluid_replace((5, 1, 4), "water"));
+        assert!(!world.can_fluid_replace((13, 1, 12), "lava"));
+    }
+
+    #[test]
+    fn fluid_does_not_flow_into_unloaded_chunks() {
+        let mut world = World::flat(1, "stone");
+        assert!(!world.can_fluid_replace((16, 1, 8), "water"));
+        assert!(!world.can_fluid_replace((8, 1, -1), "water"));
+        assert!(world.can_fluid_replace((15, 1, 8), "water"));
+
+        place_source(&mut world, (15, 1, 8), "water");
+        run_ticks(&mut world, 50);
+        assert_eq!(world.get_block_state((14, 1, 8)), 1);
+        assert!(!world.is_loaded((16, 1, 8)));
+        assert!(world.get_block((16, 1, 8)).is_none());
+    }
+}
@@ -0,0 +1,32 @@
+use crate::world::chunk::CHUNK_HEIGHT;
+use crate::world::world::World;
+
+pub const MAX_LIGHT: u8 = 15;
+/// Light lost per fluid block the sky light passes through.
+const FLUID_ATTENUATION: u8 = 2;
+
+impl World {
+    /// Sky light reaching `pos` at the current time of day. There is no block light yet.
+    pub fn get_light_level(&self, pos: (i32, i32, i32)) -> u8 {
+        self.get_sky_exposure(pos).saturating_sub(self.sky_darkening())
+    }
+
+    /// Sky light reaching `pos` in full daylight: full under the open sky, dimmed by
+    /// fluids and cut off by any opaque block above.
+    pub fn get_sky_exposure(&self, pos: (i32, i32, i32)) -> u8 {
+        let mut light = MAX_LIGHT;
+        for y in pos.1 + 1..CHUNK_HEIGHT as i32 {
+            match self.get_block((pos.0, y, pos.2)) {
+                Some(block) if block.get_fluid().is_some() => {
+                    light = light.saturating_sub(FLUID_ATTENUATION);
+                }
+                Some(block) if !block.is_transparent() => return 0,
+                _ => {}
+            }
+            if light == 0 {
+                break;
+            }
+        }
+        light
+    }
+}
@@ -1,2 +1,10 @@
 pub mod world;
-pub mod chunk;
\ No newline at end of file
+pub mod chunk;
+pub mod fluid;
+pub mod falling;
+pub mod tick;
+pub mod storage;
+pub mod biome;
+pub mod light;
+pub mod streaming;
+pub mod time;
@@ -0,0 +1,269 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+
+use glam::Vec3;
+
+use crate::common::block_registry::BlockRegistry;
+use crate::entity;
+use crate::entity::entity::EntityBody;
+use crate::entity::store::EntityEntry;
+use crate::utils::bytes::{self, ByteReader};
+use crate::world::chunk::{Chunk, CHUNK_HEIGHT, CHUNK_SIZE};
+
+pub const SAVE_DIR: &str = "saves/world";
+
+const MAGIC: &[u8; 4] = b"VXCH";
+const VERSION: u8 = 1;
+const LEVEL_MAGIC: &[u8; 4] = b"VXLV";
+const LEVEL_VERSION: u8 = 1;
+
+// Chunk file layout (little-endian):
+//   magic, version
+//   palette: u16 count, block ids (palette index 0 is always air)
+//   blocks:  u16 palette index per block, x/y/z order
+//   states:  u8 per block, same order
+//   entities: u32 count, each: type, position, velocity, u32 data length, data
+
+/// World-wide state that does not belong to any chunk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelData {
+    pub time: u64,
+    pub time_frozen: bool,
+}
+
+pub fn level_path() -> PathBuf {
+    PathBuf::from(SAVE_DIR).join("level.bin")
+}
+
+pub fn save_level(level: &LevelData) -> Result<()> {
+    let mut data = Vec::new();
+    data.extend_from_slice(LEVEL_MAGIC);
+    bytes::write_u8(&mut data, LEVEL_VERSION);
+    bytes::write_u64(&mut data, level.time);
+    bytes::write_u8(&mut data, level.time_frozen as u8);
+
+    fs::create_dir_all(SAVE_DIR)?;
+    fs::write(level_path(), data)
+}
+
+/// `Ok(None)` for a world saved before level data existed.
+pub fn load_level() -> Result<Option<LevelData>> {
+    let path = level_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&path)?;
+    let mut reader = ByteReader::new(&data);
+    if reader.read_bytes(4)? != LEVEL_MAGIC {
+        return Err(Error::new(ErrorKind::InvalidData, format!("{} is not a level file", path.display())));
+    }
+    let version = reader.read_u8()?;
+    if version != LEVEL_VERSION {
+        return Err(Error::new(ErrorKind::InvalidData, format!("unsupported level version {}", version)));
+    }
+    let time = reader.read_u64()?;
+    let time_frozen = reader.read_u8()? != 0;
+    Ok(Some(LevelData { time, time_frozen }))
+}
+
+pub fn chunk_path(chunk_pos: (i32, i32)) -> PathBuf {
+    PathBuf::from(SAVE_DIR).join("chunks").join(format!("{}_{}.bin", chunk_pos.0, chunk_pos.1))
+}
+
+pub fn save_chunk(chunk: &Chunk, entities: &[&EntityEntry]) -> Result<()> {
+    let data = encode_chunk(chunk, entities);
+    let path = chunk_path(chunk.position);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, data)
+}
+
+/// Loads a saved chunk, `Ok(None)` if it was never saved. The chunk is returned dirty so
+/// it gets meshed on the next render.
+pub fn load_chunk(chunk_pos: (i32, i32), registry: &BlockRegistry) -> Result<Option<(Chunk, Vec<EntityEntry>)>> {
+    let path = chunk_path(chunk_pos);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&path)?;
+    decode_chunk(&data, chunk_pos, registry)
+        .map(Some)
+        .map_err(|e| Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+fn encode_chunk(chunk: &Chunk, entities: &[&EntityEntry]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(MAGIC);
+    bytes::write_u8(&mut data, VERSION);
+
+    let mut palette: Vec<&str> = vec!["air"];
+    let mut indices = Vec::with_capacity(CHUNK_SIZE * CHUNK_HEIGHT * CHUNK_SIZE);
+    for x in 0..CHUNK_SIZE {
+        for y in 0..CHUNK_HEIGHT {
+            for z in 0..CHUNK_SIZE {
+                let id = chunk.blocks[x][y][z].as_ref().map_or("air", |block| block.get_id());
+                let index = match palette.iter().position(|p| *p == id) {
+                    Some(index) => index,
+                    None => {
+                        palette.push(id);
+                        palette.len() - 1
+                    }
+                };
+                indices.push(index as u16);
+            }
+        }
+    }
+
+    bytes::write_u16(&mut data, palette.len() as u16);
+    for id in &palette {
+        bytes::write_str(&mut data, id);
+    }
+    for index in indices {
+        bytes::write_u16(&mut data, index);
+    }
+    for x in 0..CHUNK_SIZE {
+        for y in 0..CHUNK_HEIGHT {
+            for z in 0..CHUNK_SIZE {
+                bytes::write_u8(&mut data, chunk.get_state(x, y, z));
+            }
+        }
+    }
+
+    bytes::write_u32(&mut data, entities.len() as u32);
+    for entry in entities {
+        bytes::write_str(&mut data, entry.entity.get_type());
+        for v in [entry.body.position, entry.body.velocity] {
+            bytes::write_f32(&mut data, v.x);
+            bytes::write_f32(&mut data, v.y);
+            bytes::write_f32(&mut data, v.z);
+        }
+        let mut entity_data = Vec::new();
+        entry.entity.save(&mut entity_data);
+        bytes::write_u32(&mut data, entity_data.len() as u32);
+        data.extend_from_slice(&entity_data);
+    }
+
+    data
+}
+
+fn decode_chunk(data: &[u8], chunk_pos: (i32, i32), registry: &BlockRegistry) -> Result<(Chunk, Vec<EntityEntry>)> {
+    let mut reader = ByteReader::new(data);
+
+    if reader.read_bytes(4)? != MAGIC {
+        return Err(Error::new(ErrorKind::InvalidData, "not a chunk file"));
+    }
+    let version = reader.read_u8()?;
+    if version != VERSION {
+        return Err(Error::new(ErrorKind::InvalidData, format!("unsupported chunk version {}", version)));
+    }
+
+    let palette_len = reader.read_u16()? as usize;
+    let mut palette = Vec::with_capacity(palette_len);
+    for _ in 0..palette_len {
+        let id = reader.read_str()?;
+        if id != "air" && registry.get_block(&id).is_none() {
+            println!("Chunk {:?}: unknown block '{}', replacing with air", chunk_pos, id);
+            palette.push("air".to_string());
+        } else {
+            palette.push(id);
+        }
+    }
+
+    let mut chunk = Chunk::empty(chunk_pos.0, chunk_pos.1);
+    let mut indices = Vec::with_capacity(CHUNK_SIZE * CHUNK_HEIGHT * CHUNK_SIZE);
+    for _ in 0..CHUNK_SIZE * CHUNK_HEIGHT * CHUNK_SIZE {
+        indices.push(reader.read_u16()? as usize);
+    }
+    let mut i = 0;
+    for x in 0..CHUNK_SIZE {
+        for y in 0..CHUNK_HEIGHT {
+            for z in 0..CHUNK_SIZE {
+                let id = palette.get(indices[i]).map_or("air", |id| id.as_str());
+                let state = reader.read_u8()?;
+                chunk.set_block_with_state(x, y, z, id, state, registry);
+                i += 1;
+            }
+        }
+    }
+
+    let entity_count = reader.read_u32()?;
+    let mut entities = Vec::new();
+    for _ in 0..entity_count {
+        let entity_type = reader.read_str()?;
+        let mut v = [0.0f32; 6];
+        for value in v.iter_mut() {
+            *value = reader.read_f32()?;
+        }
+        let len = reader.read_u32()? as usize;
+        let mut entity_data = ByteReader::new(reader.read_bytes(len)?);
+        match entity::create_entity(&entity_type, &mut entity_data)? {
+            Some(entity) => {
+                let mut body = EntityBody::new(Vec3::new(v[0], v[1], v[2]), entity.get_size());
+                body.velocity = Vec3::new(v[3], v[4], v[5]);
+                entities.push(EntityEntry { body, entity });
+            }
+            None => println!("Chunk {:?}: unknown entity type '{}', skipping", chunk_pos, entity_type),
+        }
+    }
+
+    chunk.dirty = true;
+    Ok((chunk, entities))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::inventory::ItemStack;
+    use crate::entity::entity::Entity;
+    use crate::entity::falling_block::FallingBlockEntity;
+    use crate::entity::item::ItemEntity;
+
+    #[test]
+    fn unknown_blocks_load_as_air() {
+        let registry = BlockRegistry::headless();
+        let mut chunk = Chunk::empty(0, 0);
+        chunk.set_block_with_state(0, 0, 0, "stone", 3, &registry);
+        chunk.set_block_with_state(1, 0, 0, "sand", 0, &registry);
+        let mut data = encode_chunk(&chunk, &[]);
+
+        // Rename "stone" in the palette to a block nobody registered, as if its mod was removed
+        let at = data.windows(5).position(|w| w == b"stone").unwrap();
+        data[at..at + 5].copy_from_slice(b"stome");
+
+        let (loaded, entities) = decode_chunk(&data, (0, 0), &registry).unwrap();
+        assert!(entities.is_empty());
+        assert!(loaded.blocks[0][0][0].is_none());
+        assert_eq!(loaded.get_state(0, 0, 0), 0);
+        assert_eq!(loaded.blocks[1][0][0].as_ref().map(|block| block.get_id()), Some("sand"));
+    }
+
+    #[test]
+    fn entities_round_trip() {
+        let registry = BlockRegistry::headless();
+        let chunk = Chunk::empty(1, -2);
+        let mut item = ItemEntity::new(ItemStack::new("gravel", 12));
+        item.age = 37;
+        let mut body = EntityBody::new(Vec3::new(20.5, 3.25, -30.0), item.get_size());
+        body.velocity = Vec3::new(0.5, -2.0, 1.5);
+        let item = EntityEntry { body, entity: Box::new(item) };
+        let falling = EntityEntry {
+            body: EntityBody::new(Vec3::new(17.5, 40.0, -20.5), Vec3::ONE),
+            entity: Box::new(FallingBlockEntity::new("sand")),
+        };
+        let data = encode_chunk(&chunk, &[&item, &falling]);
+
+        let (_, entities) = decode_chunk(&data, (1, -2), &registry).unwrap();
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0].body.position, Vec3::new(20.5, 3.25, -30.0));
+        assert_eq!(entities[0].body.velocity, Vec3::new(0.5, -2.0, 1.5));
+        let loaded = entities[0].entity.as_any().downcast_ref::<ItemEntity>().expect("item");
+        assert_eq!(loaded.stack, ItemStack::new("gravel", 12));
+        assert_eq!(loaded.age, 37);
+
+        assert_eq!(entities[1].body.position, Vec3::new(17.5, 40.0, -20.5));
+        let loaded = entities[1].entity.as_any().downcast_ref::<FallingBlockEntity>().expect("falling block");
+        assert_eq!(loaded.block_id, "sand");
+    }
+}
@@ -0,0 +1,80 @@
+use glam::Vec3;
+
+use crate::entity::store::chunk_of;
+use crate::world::chunk::Chunk;
+use crate::world::storage;
+use crate::world::world::World;
+
+/// New chunks brought in per call, so walking into unloaded terrain does not stall a frame.
+const MAX_CHUNK_LOADS_PER_FRAME: usize = 4;
+/// Extra ring kept loaded past the render distance, so chunks on the border do not flicker in and out.
+const UNLOAD_MARGIN: i32 = 1;
+
+impl World {
+    /// Loads (or generates) the chunks within `render_distance` of `center`, nearest first,
+    /// and saves and drops the ones that fell out of range.
+    pub fn stream_chunks(&mut self, center: Vec3, render_distance: u32) {
+        let (center_x, center_z) = chunk_of(center);
+        let radius = render_distance as i32;
+
+        let far_away: Vec<(i32, i32)> = self.chunks.keys()
+            .filter(|(x, z)| (x - center_x).abs().max((z - center_z).abs()) > radius + UNLOAD_MARGIN)
+            .copied()
+            .collect();
+        for chunk_pos in far_away {
+            self.unload_chunk(chunk_pos);
+        }
+
+        let mut missing = Vec::new();
+        for x in center_x - radius..=center_x + radius {
+            for z in center_z - radius..=center_z + radius {
+                if !self.chunks.contains_key(&(x, z)) {
+                    missing.push((x, z));
+                }
+            }
+        }
+        missing.sort_by_key(|(x, z)| (x - center_x).pow(2) + (z - center_z).pow(2));
+        for chunk_pos in missing.into_iter().take(MAX_CHUNK_LOADS_PER_FRAME) {
+            if !self.load_saved_chunk(chunk_pos) {
+                let mut chunk = Chunk::generated(chunk_pos.0, chunk_pos.1, &self.registry);
+                chunk.ambient_occlusion = self.is_ambient_occlusion_enabled();
+                self.chunks.insert(chunk_pos, chunk);
+            }
+        }
+    }
+
+    /// Replaces the chunk at `chunk_pos` with its saved version, returns false if there is none.
+    pub(crate) fn load_saved_chunk(&mut self, chunk_pos: (i32, i32)) -> bool {
+        match storage::load_chunk(chunk_pos, &self.registry) {
+            Ok(Some((mut chunk, entities))) => {
+                chunk.ambient_occlusion = self.is_ambient_occlusion_enabled();
+                self.chunks.insert(chunk_pos, chunk);
+                self.entities.remove_chunk(chunk_pos);
+                for entry in entities {
+                    self.entities.insert(entry);
+                }
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                eprintln!("Failed to load chunk {:?}: {}", chunk_pos, e);
+                false
+            }
+        }
+    }
+
+    /// Saves the chunk with its entities and removes both from the world. A chunk that
+    /// fails to save stays loaded so nothing is lost.
+    fn unload_chunk(&mut self, chunk_pos: (i32, i32)) {
+        let Some(chunk) = self.chunks.get(&chunk_pos) else { return };
+        let ids = self.entities.in_chunk(chunk_pos);
+        let entities: Vec<_> = ids.iter().filter_map(|id| self.entities.get(*id)).collect();
+        if let Err(e) = storage::save_chunk(chunk, &entities) {
+            eprintln!("Failed to save chunk {:?}: {}", chunk_pos, e);
+            return;
+        }
+        self.chunks.remove(&chunk_pos);
+        self.entities.remove_chunk(chunk_pos);
+        self.unloaded_meshes.push(chunk_pos);
+    }
+}
@@ -0,0 +1,262 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use rand::Rng;
+
+use crate::common::block::Block;
+use crate::world::chunk::{CHUNK_HEIGHT, CHUNK_SIZE};
+use crate::world::world::World;
+
+/// Height of a chunk section used for random ticks.
+pub const SECTION_HEIGHT: usize = 16;
+/// Random ticks per chunk section per world tick.
+pub const DEFAULT_RANDOM_TICK_SPEED: u32 = 3;
+/// Upper bound on neighbour notifications handled in one world tick, so
+/// update chains that keep feeding themselves spill over to the next tick.
+const MAX_BLOCK_UPDATES_PER_TICK: usize = 65536;
+
+pub const NEIGHBOURS: [(i32, i32, i32); 6] = [
+    (1, 0, 0), (-1, 0, 0),
+    (0, 1, 0), (0, -1, 0),
+    (0, 0, 1), (0, 0, -1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ScheduledTick {
+    due: u64,
+    pos: (i32, i32, i32),
+    block_id: String,
+}
+
+/// Delayed block updates requested through `World::schedule_tick`.
+pub struct TickScheduler {
+    queue: BinaryHeap<Reverse<ScheduledTick>>,
+    /// At most one tick per position: the earliest requested one. Queue entries that no
+    /// longer match it are stale and skipped.
+    pending: HashMap<(i32, i32, i32), (u64, String)>,
+    /// Neighbour notifications: (position to notify, position that changed).
+    block_updates: VecDeque<((i32, i32, i32), (i32, i32, i32))>,
+}
+
+impl TickScheduler {
+    pub fn new() -> Self {
+        Self {
+            queue: BinaryHeap::new(),
+            pending: HashMap::new(),
+            block_updates: VecDeque::new(),
+        }
+    }
+
+    pub fn schedule(&mut self, pos: (i32, i32, i32), block_id: &str, due: u64) {
+        // A tick for the block that used to be here would be dropped anyway, so it never wins
+        if let Some((pending_due, pending_id)) = self.pending.get(&pos) {
+            if *pending_due <= due && pending_id == block_id {
+                return;
+            }
+        }
+        self.pending.insert(pos, (due, block_id.to_string()));
+        self.queue.push(Reverse(ScheduledTick { due, pos, block_id: block_id.to_string() }));
+    }
+
+    fn pop_due(&mut self, now: u64) -> Option<ScheduledTick> {
+        while self.queue.peek().map_or(false, |Reverse(tick)| tick.due <= now) {
+            let Reverse(tick) = self.queue.pop()?;
+            if matches!(self.pending.get(&tick.pos), Some((due, id)) if *due == tick.due && *id == tick.block_id) {
+                self.pending.remove(&tick.pos);
+                return Some(tick);
+            }
+        }
+        None
+    }
+}
+
+impl World {
+    /// Requests `on_tick` for the block at `pos` after `delay` ticks. The update is
+    /// dropped if a different block occupies the position by then.
+    pub fn schedule_tick(&mut self, pos: (i32, i32, i32), delay: u64) {
+        let block_id = self.get_block_id(pos).to_string();
+        let due = self.get_tick() + delay.max(1);
+        self.scheduler.schedule(pos, &block_id, due);
+    }
+
+    /// Queues `on_neighbour_changed` for the six blocks around `pos`.
+    pub fn notify_neighbours(&mut self, pos: (i32, i32, i32)) {
+        for d in NEIGHBOURS {
+            let neighbour = (pos.0 + d.0, pos.1 + d.1, pos.2 + d.2);
+            self.scheduler.block_updates.push_back((neighbour, pos));
+        }
+    }
+
+    /// Runs a hook on a detached copy of the block at `pos`, so the hook is free to mutate the world.
+    pub fn with_block<F>(&mut self, pos: (i32, i32, i32), f: F)
+    where F: FnOnce(&dyn Block, &mut World) {
+        if self.get_block(pos).is_none() {
+            return;
+        }
+        let block_id = self.get_block_id(pos).to_string();
+        if let Some(block) = self.registry.create_block(&block_id) {
+            f(block.as_ref(), self);
+        }
+    }
+
+    pub(crate) fn run_scheduled_ticks(&mut self) {
+        let now = self.get_tick();
+        while let Some(tick) = self.scheduler.pop_due(now) {
+            if self.get_block_id(tick.pos) != tick.block_id {
+                continue;
+            }
+            self.with_block(tick.pos, |block, world| {
+                if block.has_gravity() && world.start_falling(tick.pos) {
+                    return;
+                }
+                block.on_tick(world, tick.pos)
+            });
+        }
+    }
+
+    pub(crate) fn run_block_updates(&mut self) {
+        let count = self.scheduler.block_updates.len().min(MAX_BLOCK_UPDATES_PER_TICK);
+        for _ in 0..count {
+            let Some((pos, changed)) = self.scheduler.block_updates.pop_front() else { break };
+            self.with_block(pos, |block, world| {
+                block.on_neighbour_changed(world, pos, changed);
+                if block.has_gravity() {
+                    world.schedule_gravity_check(pos);
+                }
+            });
+        }
+    }
+
+    pub(crate) fn run_random_ticks(&mut self) {
+        if self.random_tick_speed == 0 {
+            return;
+        }
+
+        let mut rng = rand::rng();
+        let mut candidates = Vec::new();
+        for (chunk_pos, chunk) in &self.chunks {
+            for section in 0..CHUNK_HEIGHT / SECTION_HEIGHT {
+                for _ in 0..self.random_tick_speed {
+                    let x = rng.random_range(0..CHUNK_SIZE);
+                    let y = section * SECTION_HEIGHT + rng.random_range(0..SECTION_HEIGHT);
+                    let z = rng.random_range(0..CHUNK_SIZE);
+                    if chunk.blocks[x][y][z].as_ref().map_or(false, |block| block.has_random_ticks()) {
+                        candidates.push((
+                            chunk_pos.0 * CHUNK_SIZE as i32 + x as i32,
+                            y as i32,
+                            chunk_pos.1 * CHUNK_SIZE as i32 + z as i32,
+                        ));
+                    }
+                }
+            }
+        }
+
+        for pos in candidates {
+            self.with_block(pos, |block, world| block.on_random_tick(world, pos));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Flat world with the notifications from building the floor already handled.
+    fn flat_world(floor: &str) -> World {
+        let mut world = World::flat(1, floor);
+        world.scheduler.block_updates.clear();
+        world
+    }
+
+    fn run_ticks(world: &mut World, ticks: u32) {
+        for _ in 0..ticks {
+            world.tick();
+        }
+    }
+
+    #[test]
+    fn scheduled_tick_runs_after_its_delay() {
+        let mut world = flat_world("stone");
+        world.set_block((8, 1, 8), "water", 0);
+        world.schedule_tick((8, 1, 8), 5);
+
+        run_ticks(&mut world, 4);
+        assert_eq!(world.get_block_id((9, 1, 8)), "air");
+        run_ticks(&mut world, 1);
+        assert_eq!(world.get_block_id((9, 1, 8)), "water");
+    }
+
+    #[test]
+    fn position_keeps_only_its_earliest_tick() {
+        let mut scheduler = TickScheduler::new();
+        scheduler.schedule((8, 1, 8), "water", 20);
+        scheduler.schedule((8, 1, 8), "water", 5);
+        scheduler.schedule((8, 1, 8), "water", 10);
+        assert_eq!(scheduler.pending.len(), 1);
+
+        assert!(scheduler.pop_due(4).is_none());
+        assert_eq!(scheduler.pop_due(5).map(|tick| tick.due), Some(5));
+        // The later requests were folded into the one that already ran
+        assert!(scheduler.pop_due(u64::MAX).is_none());
+    }
+
+    #[test]
+    fn new_block_replaces_the_pending_tick() {
+        let mut scheduler = TickScheduler::new();
+        scheduler.schedule((8, 1, 8), "water", 5);
+        scheduler.schedule((8, 1, 8), "lava", 30);
+
+        let tick = scheduler.pop_due(u64::MAX).expect("lava tick");
+        assert_eq!((tick.block_id.as_str(), tick.due), ("lava", 30));
+        assert!(scheduler.pop_due(u64::MAX).is_none());
+    }
+
+    #[test]
+    fn tick_is_dropped_when_the_block_is_replaced() {
+        let mut world = flat_world("stone");
+        world.set_block((8, 1, 8), "water", 0);
+        world.schedule_tick((8, 1, 8), 5);
+        // Lava takes the spot without scheduling its own flow
+        world.set_block((8, 1, 8), "lava", 0);
+
+        run_ticks(&mut world, 50);
+        assert_eq!(world.get_block_id((9, 1, 8)), "air");
+        assert_eq!(world.get_block_id((8, 1, 8)), "lava");
+    }
+
+    #[test]
+    fn block_updates_over_the_cap_spill_into_the_next_tick() {
+        let mut world = flat_world("stone");
+        for _ in 0..MAX_BLOCK_UPDATES_PER_TICK + 10 {
+            world.scheduler.block_updates.push_back(((8, 0, 8), (8, 1, 8)));
+        }
+
+        world.run_block_updates();
+        assert_eq!(world.scheduler.block_updates.len(), 10);
+        world.run_block_updates();
+        assert!(world.scheduler.block_updates.is_empty());
+    }
+
+    #[test]
+    fn random_ticks_reach_covered_grass() {
+        let mut world = flat_world("grass");
+        for x in 0..CHUNK_SIZE as i32 {
+            for z in 0..CHUNK_SIZE as i32 {
+                world.set_block((x, 1, z), "stone", 0);
+            }
+        }
+
+        world.random_tick_speed = 0;
+        run_ticks(&mut world, 20);
+        assert_eq!(world.get_block_id((8, 0, 8)), "grass");
+
+        // Every block of the section gets a random tick about once per world tick
+        world.random_tick_speed = (SECTION_HEIGHT * CHUNK_SIZE * CHUNK_SIZE) as u32;
+        run_ticks(&mut world, 50);
+        for x in 0..CHUNK_SIZE as i32 {
+            for z in 0..CHUNK_SIZE as i32 {
+                assert_eq!(world.get_block_id((x, 0, z)), "dirt");
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::f32::consts::TAU;
+
+use glam::Vec3;
+
+use crate::world::light::MAX_LIGHT;
+use crate::world::world::World;
+
+/// Ticks in a full day/night cycle, 20 minutes at `TICKS_PER_SECOND`.
+pub const DAY_LENGTH: u64 = 24000;
+pub const SUNRISE: u64 = 0;
+pub const NOON: u64 = 6000;
+pub const SUNSET: u64 = 12000;
+pub const MIDNIGHT: u64 = 18000;
+/// Time of day of a freshly created world, early morning.
+pub const START_TIME: u64 = 1000;
+/// Sky light at night is `MAX_LIGHT` minus this.
+const NIGHT_DARKENING: f32 = 11.0;
+
+impl World {
+    /// Total ticks the sun has travelled, keeps counting across days.
+    pub fn get_time(&self) -> u64 {
+        self.time
+    }
+
+    pub fn get_time_of_day(&self) -> u64 {
+        self.time % DAY_LENGTH
+    }
+
+    /// Jumps to `time_of_day` within the current day (see `NOON`, `MIDNIGHT` etc.).
+    pub fn set_time_of_day(&mut self, time_of_day: u64) {
+        self.time = self.time - self.get_time_of_day() + time_of_day % DAY_LENGTH;
+    }
+
+    pub fn is_time_frozen(&self) -> bool {
+        self.time_frozen
+    }
+
+    /// Stops (or resumes) the day/night cycle; block ticks keep running either way.
+    pub fn set_time_frozen(&mut self, frozen: bool) {
+        self.time_frozen = frozen;
+    }
+
+    pub(crate) fn advance_time(&mut self) {
+        if !self.time_frozen {
+            self.time += 1;
+        }
+    }
+
+    /// Unit vector towards the sun. It rises in +X at `SUNRISE`, is overhead at `NOON`
+    /// and below the ground through the night.
+    pub fn sun_direction(&self) -> Vec3 {
+        let angle = self.get_time_of_day() as f32 / DAY_LENGTH as f32 * TAU;
+        Vec3::new(angle.cos(), angle.sin(), 0.25).normalize()
+    }
+
+    /// How much of the daylight reaches the ground, 0 at night to 1 during the day,
+    /// fading over dawn and dusk.
+    pub fn daylight(&self) -> f32 {
+        ((self.sun_direction().y + 0.1) / 0.3).clamp(0.0, 1.0)
+    }
+
+    /// How many levels the sky light is currently dimmed by.
+    pub fn sky_darkening(&self) -> u8 {
+        ((1.0 - self.daylight()) * NIGHT_DARKENING).round().min(MAX_LIGHT as f32) as u8
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sun_is_up_at_noon_and_down_at_midnight() {
+        let mut world = World::flat(1, "stone");
+        world.set_time_of_day(NOON);
+        assert!(world.sun_direction().y > 0.9);
+        assert_eq!(world.daylight(), 1.0);
+        assert_eq!(world.get_light_level((4, 1, 4)), MAX_LIGHT);
+
+        world.set_time_of_day(MIDNIGHT);
+        assert!(world.sun_direction().y < -0.9);
+        assert_eq!(world.daylight(), 0.0);
+        assert_eq!(world.get_light_level((4, 1, 4)), MAX_LIGHT - NIGHT_DARKENING as u8);
+    }
+
+    #[test]
+    fn frozen_time_does_not_advance() {
+        let mut world = World::flat(1, "stone");
+        world.set_time_of_day(NOON);
+        world.tick();
+        assert_eq!(world.get_time_of_day(), NOON + 1);
+
+        world.set_time_frozen(true);
+        world.tick();
+        assert_eq!(world.get_time_of_day(), NOON + 1);
+        assert_eq!(world.get_tick(), 2);
+    }
+}
@@ -1,29 +1,49 @@
 use crate::engine::Engine;
 use crate::engine::Vertex;
 use std::collections::HashMap;
+use glam::Vec3;
+use crate::common::block::Block;
 use crate::common::block_registry::BlockRegistry;
+use crate::common::inventory::ItemStack;
 use crate::world::chunk::CHUNK_HEIGHT;
 use crate::world::chunk::CHUNK_SIZE;
 use crate::world::chunk::Chunk;
+use crate::entity::models::ModelRegistry;
+use crate::entity::store::EntityStore;
+use crate::world::storage;
+use crate::world::tick::{TickScheduler, DEFAULT_RANDOM_TICK_SPEED};
+use crate::world::time::START_TIME;
 
+/// Fixed simulation rate for block updates.
+pub const TICKS_PER_SECOND: f32 = 20.0;
 
 pub struct World {
     pub chunks: HashMap<(i32, i32), Chunk>,
     pub registry: BlockRegistry,
+    ambient_occlusion: bool,
+    tick: u64,
+    /// Day/night clock, see `world::time`.
+    pub(crate) time: u64,
+    pub(crate) time_frozen: bool,
+    pub(crate) scheduler: TickScheduler,
+    pub random_tick_speed: u32,
+    pub entities: EntityStore,
+    /// Where the player stands, for mob goals and spawning; set by the game every frame.
+    pub player_position: Option<Vec3>,
+    pub mob_spawning: bool,
+    pub models: ModelRegistry,
+    /// Chunks unloaded since the last `render`, whose GPU meshes are still to be dropped.
+    pub(crate) unloaded_meshes: Vec<(i32, i32)>,
 }
 
 impl World {
     pub fn new(engine: &mut Engine) -> Self {
-        let registry = BlockRegistry::new(engine);
-        let mut world = Self {
-            chunks: HashMap::new(),
-            registry,
-        };
+        let mut world = Self::empty(BlockRegistry::new(engine));
         
         // Generate chunks around origin
         for x in -1..=1 {
             for z in -1..=1 {
-                let chunk = Chunk::new(engine, x, z, &world.registry);
+                let chunk = Chunk::generated(x, z, &world.registry);
                 world.chunks.insert((x, z), chunk);
             }
         }
@@ -31,22 +51,106 @@ impl World {
         world
     }
     
+    /// A world without chunks or GPU resources; chunks are added by the caller.
+    pub fn empty(registry: BlockRegistry) -> Self {
+        Self {
+            chunks: HashMap::new(),
+            registry,
+            ambient_occlusion: true,
+            tick: 0,
+            time: START_TIME,
+            time_frozen: false,
+            scheduler: TickScheduler::new(),
+            random_tick_speed: DEFAULT_RANDOM_TICK_SPEED,
+            entities: EntityStore::new(),
+            player_position: None,
+            mob_spawning: true,
+            models: ModelRegistry::new(),
+            unloaded_meshes: Vec::new(),
+        }
+    }
+    
     pub fn render(&mut self, engine: &mut Engine) {
         engine.renderer.clear_meshes();
         
-        // Regenerate meshes for dirty chunks
-        for chunk in self.get_chunks_mut().values_mut() {
-            // Chunk regenerates mesh automatically in set_block
+        for chunk_pos in self.unloaded_meshes.drain(..) {
+            engine.renderer.remove_chunk_mesh(chunk_pos);
         }
         
-        for chunk in self.get_chunks().values() {
-            if !chunk.vertices.is_empty() {
-                let vertex_data = bytemuck::cast_slice(&chunk.vertices);
-                engine.renderer.add_mesh(vertex_data, &chunk.indices);
+        // Chunk meshes stay on the GPU, only changed chunks are remeshed and uploaded again
+        for chunk in self.chunks.values_mut() {
+            if !chunk.dirty {
+                continue;
+            }
+            chunk.generate_mesh(engine, &self.registry);
+            engine.renderer.set_chunk_mesh(
+                chunk.position,
+                bytemuck::cast_slice(&chunk.vertices),
+                &chunk.indices,
+                bytemuck::cast_slice(&chunk.translucent_vertices),
+                &chunk.translucent_indices,
+                chunk.bounds(),
+            );
+        }
+        
+        self.render_entities(engine);
+    }
+
+
+    /// Advances the simulation by one fixed tick (see `TICKS_PER_SECOND`).
+    pub fn tick(&mut self) {
+        self.tick += 1;
+        self.advance_time();
+        self.run_scheduled_ticks();
+        self.run_block_updates();
+        self.run_random_ticks();
+        self.update_entities();
+        self.run_mob_spawning();
+    }
+    
+    /// Replaces generated chunks with their saved versions. Called once all block types,
+    /// including Lua ones, are registered.
+    pub fn load(&mut self) {
+        match storage::load_level() {
+            Ok(Some(level)) => {
+                self.time = level.time;
+                self.time_frozen = level.time_frozen;
             }
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to load level data: {}", e),
+        }
+        let positions: Vec<(i32, i32)> = self.chunks.keys().copied().collect();
+        for chunk_pos in positions {
+            self.load_saved_chunk(chunk_pos);
         }
     }
+    
+    /// Writes every loaded chunk together with the entities standing in it.
+    pub fn save(&self) -> std::io::Result<()> {
+        for (chunk_pos, chunk) in &self.chunks {
+            let ids = self.entities.in_chunk(*chunk_pos);
+            let entities: Vec<_> = ids.iter().filter_map(|id| self.entities.get(*id)).collect();
+            storage::save_chunk(chunk, &entities)?;
+        }
+        storage::save_level(&storage::LevelData { time: self.time, time_frozen: self.time_frozen })?;
+        println!("Saved {} chunks to {}", self.chunks.len(), storage::SAVE_DIR);
+        Ok(())
+    }
+    
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
 
+    pub fn is_ambient_occlusion_enabled(&self) -> bool {
+        self.ambient_occlusion
+    }
+    
+    pub fn set_ambient_occlusion(&mut self, enabled: bool) {
+        self.ambient_occlusion = enabled;
+        for chunk in self.chunks.values_mut() {
+            chunk.set_ambient_occlusion(enabled);
+        }
+    }
 
     pub fn get_chunks(&self) -> &HashMap<(i32, i32), Chunk> {
         &self.chunks
@@ -56,7 +160,7 @@ impl World {
         &mut self.chunks
     }
     
-    pub fn break_block(&mut self, engine: &Engine, world_pos: (i32, i32, i32)) -> bool {
+    pub fn break_block(&mut self, world_pos: (i32, i32, i32)) -> bool {
         let (world_x, world_y, world_z) = world_pos;
         let chunk_x = world_x.div_euclid(CHUNK_SIZE as i32);
         let chunk_z = world_z.div_euclid(CHUNK_SIZE as i32);
@@ -66,14 +170,23 @@ impl World {
         
         if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) {
             if local_y < CHUNK_HEIGHT && chunk.blocks[local_x][local_y][local_z].is_some() {
-                chunk.set_block(engine, local_x, local_y, local_z, "air", &self.registry);
+                let broken = self.registry.create_block(chunk.blocks[local_x][local_y][local_z].as_ref().unwrap().get_id());
+                chunk.set_block_with_state(local_x, local_y, local_z, "air", 0, &self.registry);
+                self.notify_neighbours(world_pos);
+                if let Some(broken) = broken {
+                    broken.on_broken(self, world_pos);
+                    if broken.get_fluid().is_none() {
+                        let center = Vec3::new(world_x as f32 + 0.5, world_y as f32 + 0.25, world_z as f32 + 0.5);
+                        self.spawn_item(ItemStack::new(broken.get_id(), 1), center);
+                    }
+                }
                 return true;
             }
         }
         false
     }
     
-    pub fn place_block(&mut self, engine: &Engine, world_pos: (i32, i32, i32), block_name: &str) -> bool {
+    pub fn place_block(&mut self, world_pos: (i32, i32, i32), block_name: &str) -> bool {
         let (world_x, world_y, world_z) = world_pos;
         let chunk_x = world_x.div_euclid(CHUNK_SIZE as i32);
         let chunk_z = world_z.div_euclid(CHUNK_SIZE as i32);
@@ -82,8 +195,12 @@ impl World {
         let local_z = world_z.rem_euclid(CHUNK_SIZE as i32) as usize;
         
         if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) {
-            if local_y < CHUNK_HEIGHT && chunk.blocks[local_x][local_y][local_z].is_none() {
-                chunk.set_block(engine, local_x, local_y, local_z, block_name, &self.registry);
+            // Fluids are replaceable like air
+            if local_y < CHUNK_HEIGHT && chunk.blocks[local_x][local_y][local_z].as_ref().map_or(true, |block| block.get_fluid().is_some()) {
+                chunk.set_block_with_state(local_x, local_y, local_z, block_name, 0, &self.registry);
+                self.notify_neighbours(world_pos);
+                self.with_block(world_pos, |block, world| block.on_placed(world, world_pos));
+                self.schedule_gravity_check(world_pos);
                 return true;
             }
         }
@@ -108,4 +225,73 @@ impl World {
         }
         "air"
     }
-}
\ No newline at end of file
+    
+    fn locate(world_pos: (i32, i32, i32)) -> Option<((i32, i32), (usize, usize, usize))> {
+        let (world_x, world_y, world_z) = world_pos;
+        if world_y < 0 || world_y >= CHUNK_HEIGHT as i32 {
+            return None;
+        }
+        let chunk_pos = (world_x.div_euclid(CHUNK_SIZE as i32), world_z.div_euclid(CHUNK_SIZE as i32));
+        let local = (
+            world_x.rem_euclid(CHUNK_SIZE as i32) as usize,
+            world_y as usize,
+            world_z.rem_euclid(CHUNK_SIZE as i32) as usize,
+        );
+        Some((chunk_pos, local))
+    }
+    
+    pub fn is_loaded(&self, world_pos: (i32, i32, i32)) -> bool {
+        match Self::locate(world_pos) {
+            Some((chunk_pos, _)) => self.chunks.contains_key(&chunk_pos),
+            None => false,
+        }
+    }
+    
+    pub fn get_block(&self, world_pos: (i32, i32, i32)) -> Option<&dyn Block> {
+        let (chunk_pos, (x, y, z)) = Self::locate(world_pos)?;
+        self.chunks.get(&chunk_pos)?.blocks[x][y][z].as_deref()
+    }
+    
+    pub fn get_block_id(&self, world_pos: (i32, i32, i32)) -> &str {
+        self.get_block(world_pos).map_or("air", |block| block.get_id())
+    }
+    
+    pub fn get_block_state(&self, world_pos: (i32, i32, i32)) -> u8 {
+        match Self::locate(world_pos) {
+            Some((chunk_pos, (x, y, z))) => self.chunks.get(&chunk_pos).map_or(0, |chunk| chunk.get_state(x, y, z)),
+            None => 0,
+        }
+    }
+    
+    /// Sets a block and its state without touching the GPU; the owning chunk is remeshed on the next `render`
+    /// and the surrounding blocks get `on_neighbour_changed` during the next tick.
+    pub fn set_block(&mut self, world_pos: (i32, i32, i32), block_name: &str, state: u8) -> bool {
+        if let Some((chunk_pos, (x, y, z))) = Self::locate(world_pos) {
+            if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+                chunk.set_block_with_state(x, y, z, block_name, state, &self.registry);
+                self.notify_neighbours(world_pos);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+impl World {
+    /// Headless world of `size` x `size` empty chunks from the origin with a floor of `floor` at y = 0.
+    pub fn flat(size: i32, floor: &str) -> Self {
+        let mut world = Self::empty(BlockRegistry::headless());
+        for cx in 0..size {
+            for cz in 0..size {
+                world.chunks.insert((cx, cz), Chunk::empty(cx, cz));
+            }
+        }
+        for x in 0..size * CHUNK_SIZE as i32 {
+            for z in 0..size * CHUNK_SIZE as i32 {
+                world.set_block((x, 0, z), floor, 0);
+            }
+        }
+        world
+    }
+}